@@ -0,0 +1,65 @@
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_segmentlookup::prover::{prove, Proof};
+use ark_segmentlookup::public_parameters::PublicParameters;
+use ark_segmentlookup::table::Table;
+use ark_segmentlookup::verifier::{verify, verify_batch};
+use ark_segmentlookup::witness::{Statement, Witness};
+use ark_std::rand::RngCore;
+use ark_std::test_rng;
+
+fn batch_verify(n: usize, k: usize, s: usize, num_proofs: usize) {
+    println!("n: {}, k: {}, s: {}, num_proofs: {}", n, k, s, num_proofs);
+    let mut rng = &mut test_rng();
+    let pp = PublicParameters::<Bn254>::builder()
+        .num_table_segments(n)
+        .num_witness_segments(k)
+        .segment_size(s)
+        .build(&mut rng)
+        .expect("Failed to setup public parameters");
+    let segments = (0..n)
+        .map(|_| {
+            (0..s)
+                .map(|_| <Bn254 as Pairing>::ScalarField::from(rng.next_u64()))
+                .collect()
+        })
+        .collect();
+    let table = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+    let tpp = table.preprocess(&pp).expect("Failed to preprocess table");
+    let vk = pp.verifier_key().expect("Failed to extract verifier key");
+    let tvk = tpp.verifier_key();
+
+    let statements_and_proofs: Vec<(Statement<Bn254>, Proof<Bn254>)> = (0..num_proofs)
+        .map(|_| {
+            let queried_segment_indices: Vec<usize> =
+                (0..k).map(|_| rng.next_u32() as usize % n).collect();
+            let witness =
+                Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+            let proof = prove(&pp, &tpp, &witness, statement, rng).expect("Failed to prove");
+
+            (statement, proof)
+        })
+        .collect();
+
+    let curr_time = std::time::Instant::now();
+    for (statement, proof) in statements_and_proofs.iter() {
+        assert!(verify(&vk, &tvk, *statement, proof, rng).is_ok());
+    }
+    println!("loop verify time: {:?} ms", curr_time.elapsed().as_millis());
+
+    let curr_time = std::time::Instant::now();
+    assert!(verify_batch(&vk, &tvk, &statements_and_proofs, rng).is_ok());
+    println!(
+        "batch verify time: {:?} ms",
+        curr_time.elapsed().as_millis()
+    );
+}
+
+fn main() {
+    const NUM_PROOFS: [usize; 5] = [1, 4, 16, 64, 256];
+
+    for num_proofs in NUM_PROOFS {
+        batch_verify(1 << 10, 1 << 4, 1 << 2, num_proofs);
+    }
+}
@@ -6,7 +6,7 @@ use ark_segmentlookup::table::Table;
 use ark_segmentlookup::verifier::verify;
 use ark_segmentlookup::witness::Witness;
 use ark_std::rand::RngCore;
-use ark_std::{test_rng, UniformRand};
+use ark_std::test_rng;
 
 fn rand_inputs<P: Pairing>(
     num_table_segments: usize,
@@ -20,7 +20,7 @@ fn rand_inputs<P: Pairing>(
         for _ in 0..num_table_segments {
             let mut segment = Vec::with_capacity(segment_size);
             for _ in 0..segment_size {
-                segment.push(P::ScalarField::rand(&mut rng));
+                segment.push(P::ScalarField::from(rng.next_u64()));
             }
             segments.push(segment);
         }
@@ -51,14 +51,16 @@ fn end_to_end(n: usize, k: usize, s: usize) {
     println!("setup time: {:?} ms", curr_time.elapsed().as_millis());
 
     let witness = Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
-    let statement = witness.generate_statement(&pp.g1_affine_srs);
+    let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
 
     let curr_time = std::time::Instant::now();
     let proof = prove(&pp, &tpp, &witness, statement, rng).expect("Failed to prove");
     println!("prove time: {:?} ms", curr_time.elapsed().as_millis());
 
+    let vk = pp.verifier_key().expect("Failed to extract verifier key");
+    let tvk = tpp.verifier_key();
     let curr_time = std::time::Instant::now();
-    let res = verify(&pp, &tpp, statement, &proof, rng);
+    let res = verify(&vk, &tvk, statement, &proof, rng);
     println!("verify time: {:?} ms", curr_time.elapsed().as_millis());
     assert!(res.is_ok());
 }
@@ -0,0 +1,574 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_segmentlookup::prover::{prove, Proof};
+use ark_segmentlookup::public_parameters::{PublicParameters, VerifierKey};
+use ark_segmentlookup::streaming::StreamingPreprocessor;
+use ark_segmentlookup::table::{Table, TablePreprocessedParameters, TableVerifierKey};
+use ark_segmentlookup::verifier::{verify, verify_debug};
+use ark_segmentlookup::witness::{Statement, Witness};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use blake2::{Blake2b512, Digest};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+type CliResult<T> = Result<T, String>;
+
+#[derive(Parser)]
+#[command(
+    name = "segment-lookup",
+    about = "Set up, preprocess, prove and verify segment lookups"
+)]
+struct Cli {
+    /// Pairing-friendly curve of the artifacts.
+    #[arg(long, value_enum, default_value_t = Curve::Bn254, global = true)]
+    curve: Curve,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Curve {
+    Bn254,
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ArtifactKind {
+    Pp,
+    Vk,
+    Tpp,
+    Tvk,
+    Proof,
+    Statement,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates the public parameters and the verifier key.
+    Setup {
+        #[arg(long)]
+        num_table_segments: usize,
+        #[arg(long)]
+        num_witness_segments: usize,
+        #[arg(long)]
+        segment_size: usize,
+        #[arg(long)]
+        zero_knowledge: bool,
+        /// Adds the Lagrange quotients needed by streaming preprocessing
+        /// (`preprocess --chunk-size`).
+        #[arg(long)]
+        lagrange_quotients: bool,
+        /// Seed of the setup randomness, for reproducible (insecure) setups.
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+    },
+    /// Preprocesses a table given as a CSV, JSON or binary file with one
+    /// segment per row, and writes the table verifier key.
+    Preprocess {
+        #[arg(long)]
+        table: PathBuf,
+        #[arg(long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "tpp.bin")]
+        out: PathBuf,
+        #[arg(long, default_value = "tvk.bin")]
+        tvk: PathBuf,
+        /// Streams a binary table with at most this many elements per chunk
+        /// in memory, instead of loading the whole table.
+        #[arg(long)]
+        chunk_size: Option<usize>,
+    },
+    /// Proves a lookup of the segment indices given as a JSON array.
+    Prove {
+        #[arg(long)]
+        queries: PathBuf,
+        #[arg(long, default_value = "pp.bin")]
+        pp: PathBuf,
+        #[arg(long, default_value = "tpp.bin")]
+        tpp: PathBuf,
+        #[arg(long, default_value = "statement.bin")]
+        statement: PathBuf,
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+        /// Seed of the prover randomness, for reproducible proofs.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Verifies a proof, and reports the failed check if it is rejected.
+    Verify {
+        #[arg(long, default_value = "vk.bin")]
+        vk: PathBuf,
+        #[arg(long, default_value = "tvk.bin")]
+        tvk: PathBuf,
+        #[arg(long, default_value = "statement.bin")]
+        statement: PathBuf,
+        #[arg(long, default_value = "proof.bin")]
+        proof: PathBuf,
+    },
+    /// Prints the sizes and hashes of an artifact.
+    Inspect {
+        #[arg(value_enum)]
+        kind: ArtifactKind,
+        path: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.curve {
+        Curve::Bn254 => run::<Bn254>(cli.command),
+        Curve::Bls12_381 => run::<Bls12_381>(cli.command),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run<P: Pairing>(command: Command) -> CliResult<()> {
+    match command {
+        Command::Setup {
+            num_table_segments,
+            num_witness_segments,
+            segment_size,
+            zero_knowledge,
+            lagrange_quotients,
+            seed,
+            pp,
+            vk,
+        } => setup::<P>(
+            num_table_segments,
+            num_witness_segments,
+            segment_size,
+            zero_knowledge,
+            lagrange_quotients,
+            seed,
+            &pp,
+            &vk,
+        ),
+        Command::Preprocess {
+            table,
+            pp,
+            out,
+            tvk,
+            chunk_size,
+        } => preprocess::<P>(&table, &pp, &out, &tvk, chunk_size),
+        Command::Prove {
+            queries,
+            pp,
+            tpp,
+            statement,
+            proof,
+            seed,
+        } => prove_queries::<P>(&queries, &pp, &tpp, &statement, &proof, seed),
+        Command::Verify {
+            vk,
+            tvk,
+            statement,
+            proof,
+        } => verify_proof::<P>(&vk, &tvk, &statement, &proof),
+        Command::Inspect { kind, path } => inspect::<P>(kind, &path),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup<P: Pairing>(
+    num_table_segments: usize,
+    num_witness_segments: usize,
+    segment_size: usize,
+    zero_knowledge: bool,
+    lagrange_quotients: bool,
+    seed: Option<u64>,
+    pp_path: &Path,
+    vk_path: &Path,
+) -> CliResult<()> {
+    let mut rng = new_rng(seed);
+    let pp = PublicParameters::<P>::builder()
+        .num_table_segments(num_table_segments)
+        .num_witness_segments(num_witness_segments)
+        .segment_size(segment_size)
+        .zero_knowledge(zero_knowledge)
+        .lagrange_quotients(lagrange_quotients)
+        .build(&mut rng)
+        .map_err(|err| format!("failed to set up public parameters: {err:?}"))?;
+
+    // The public parameters are written uncompressed so that they can be
+    // memory-mapped.
+    let mut bytes = Vec::new();
+    pp.serialize_uncompressed(&mut bytes)
+        .map_err(|err| format!("failed to serialize {}: {err}", pp_path.display()))?;
+    fs::write(pp_path, bytes)
+        .map_err(|err| format!("failed to write {}: {err}", pp_path.display()))?;
+
+    let vk = pp
+        .verifier_key()
+        .map_err(|err| format!("failed to extract the verifier key: {err:?}"))?;
+    write_artifact(vk_path, &vk)
+}
+
+fn preprocess<P: Pairing>(
+    table_path: &Path,
+    pp_path: &Path,
+    out_path: &Path,
+    tvk_path: &Path,
+    chunk_size: Option<usize>,
+) -> CliResult<()> {
+    let pp = open_public_parameters::<P>(pp_path)?;
+    if let Some(chunk_size) = chunk_size {
+        let table = fs::File::open(table_path)
+            .map_err(|err| format!("failed to read {}: {err}", table_path.display()))?;
+        let out = fs::File::create(out_path)
+            .map_err(|err| format!("failed to write {}: {err}", out_path.display()))?;
+
+        let tvk = StreamingPreprocessor::new(&pp)
+            .chunk_size(chunk_size)
+            .compress(Compress::Yes)
+            .preprocess(table, out)
+            .map_err(|err| format!("failed to preprocess table: {err:?}"))?;
+
+        return write_artifact(tvk_path, &tvk);
+    }
+
+    let table = load_table(&pp, table_path)?;
+    let tpp = table
+        .preprocess(&pp)
+        .map_err(|err| format!("failed to preprocess table: {err:?}"))?;
+
+    write_artifact(out_path, &tpp)?;
+    write_artifact(tvk_path, &tpp.verifier_key())
+}
+
+fn prove_queries<P: Pairing>(
+    queries_path: &Path,
+    pp_path: &Path,
+    tpp_path: &Path,
+    statement_path: &Path,
+    proof_path: &Path,
+    seed: Option<u64>,
+) -> CliResult<()> {
+    let pp = open_public_parameters::<P>(pp_path)?;
+    let tpp: TablePreprocessedParameters<P> = read_artifact(tpp_path)?;
+    let queries = fs::read(queries_path)
+        .map_err(|err| format!("failed to read {}: {err}", queries_path.display()))?;
+    let queried_segment_indices: Vec<usize> = serde_json::from_slice(&queries)
+        .map_err(|err| format!("invalid queries in {}: {err}", queries_path.display()))?;
+
+    let mut rng = new_rng(seed);
+    let witness = if pp.zero_knowledge {
+        Witness::new_blinded(
+            &pp,
+            &tpp.adjusted_table_values,
+            &queried_segment_indices,
+            &mut rng,
+        )
+    } else {
+        Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices)
+    }
+    .map_err(|err| format!("invalid queries: {err:?}"))?;
+    let g1_affine_srs = pp
+        .g1_affine_srs
+        .as_slice()
+        .map_err(|err| format!("failed to read the SRS: {err:?}"))?;
+    let statement = witness.generate_statement(g1_affine_srs);
+
+    let proof = prove(&pp, &tpp, &witness, statement, &mut rng)
+        .map_err(|err| format!("failed to prove: {err:?}"))?;
+
+    write_artifact(statement_path, &statement)?;
+    write_artifact(proof_path, &proof)
+}
+
+fn verify_proof<P: Pairing>(
+    vk_path: &Path,
+    tvk_path: &Path,
+    statement_path: &Path,
+    proof_path: &Path,
+) -> CliResult<()> {
+    let vk: VerifierKey<P> = read_artifact(vk_path)?;
+    let tvk: TableVerifierKey<P> = read_artifact(tvk_path)?;
+    let statement: Statement<P> = read_artifact(statement_path)?;
+    let proof: Proof<P> = read_artifact(proof_path)?;
+
+    if verify(&vk, &tvk, statement, &proof, &mut new_rng(None)).is_ok() {
+        println!("proof verified");
+        return Ok(());
+    }
+
+    // Rerun the checks one by one to report the failed one.
+    match verify_debug(&vk, &tvk, statement, &proof) {
+        Err(err) => Err(format!("proof rejected: {err:?}")),
+        Ok(()) => Err("proof rejected".to_string()),
+    }
+}
+
+fn inspect<P: Pairing>(kind: ArtifactKind, path: &Path) -> CliResult<()> {
+    let bytes =
+        fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    println!("file: {}", path.display());
+    println!("size: {} bytes", bytes.len());
+    println!("blake2b: {}", to_hex(&Blake2b512::digest(&bytes)));
+
+    match kind {
+        ArtifactKind::Pp => {
+            let pp = open_public_parameters::<P>(path)?;
+            println!(
+                "num_table_segments: {} (padded to {})",
+                pp.logical_num_table_segments, pp.num_table_segments
+            );
+            println!(
+                "num_witness_segments: {} (padded to {})",
+                pp.logical_num_witness_segments, pp.num_witness_segments
+            );
+            println!(
+                "segment_size: {} (padded to {})",
+                pp.logical_segment_size, pp.segment_size
+            );
+            println!("zero_knowledge: {}", pp.zero_knowledge);
+            println!("g1_srs_length: {}", pp.g1_affine_srs.len());
+            println!("g2_srs_length: {}", pp.g2_affine_srs.len());
+            println!("hash_representation: {}", to_hex(pp.hash_representation()));
+        },
+        ArtifactKind::Vk => {
+            let vk: VerifierKey<P> = deserialize(&bytes)?;
+            println!(
+                "num_table_segments: {} (padded to {})",
+                vk.logical_num_table_segments, vk.num_table_segments
+            );
+            println!(
+                "num_witness_segments: {} (padded to {})",
+                vk.logical_num_witness_segments, vk.num_witness_segments
+            );
+            println!(
+                "segment_size: {} (padded to {})",
+                vk.logical_segment_size, vk.segment_size
+            );
+            println!("hash_representation: {}", to_hex(vk.hash_representation()));
+        },
+        ArtifactKind::Tpp => {
+            let tpp: TablePreprocessedParameters<P> = deserialize(&bytes)?;
+            println!("table_element_size: {}", tpp.adjusted_table_values.len());
+            println!("uniqueness_encoding: {:?}", tpp.uniqueness_encoding);
+            println!("hash_representation: {}", to_hex(tpp.hash_representation()));
+        },
+        ArtifactKind::Tvk => {
+            let tvk: TableVerifierKey<P> = deserialize(&bytes)?;
+            println!("hash_representation: {}", to_hex(tvk.hash_representation()));
+        },
+        ArtifactKind::Proof => {
+            let proof: Proof<P> = deserialize(&bytes)?;
+            println!("compressed_size: {}", proof.compressed_size());
+        },
+        ArtifactKind::Statement => {
+            let statement: Statement<P> = deserialize(&bytes)?;
+            println!("g1_affine_f: {}", statement.g1_affine_f);
+            println!("num_queries: {}", statement.num_queries);
+        },
+    }
+
+    Ok(())
+}
+
+// Loads a table with one segment per row. The format follows the file
+// extension: .json, .bin, or CSV otherwise.
+fn load_table<P: Pairing>(pp: &PublicParameters<P>, path: &Path) -> CliResult<Table<P>> {
+    let file =
+        fs::File::open(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    let table = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Table::from_json(pp, file),
+        Some("bin") => Table::from_binary(pp, file),
+        _ => Table::from_csv(pp, file),
+    };
+
+    table.map_err(|err| format!("invalid table in {}: {err:?}", path.display()))
+}
+
+fn new_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+fn write_artifact<T: CanonicalSerialize>(path: &Path, artifact: &T) -> CliResult<()> {
+    let mut bytes = Vec::new();
+    artifact
+        .serialize_with_mode(&mut bytes, Compress::Yes)
+        .map_err(|err| format!("failed to serialize {}: {err}", path.display()))?;
+
+    fs::write(path, bytes).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn open_public_parameters<P: Pairing>(path: &Path) -> CliResult<PublicParameters<P>> {
+    PublicParameters::open_mapped(path).map_err(|err| format!("{}: {err:?}", path.display()))
+}
+
+fn read_artifact<T: CanonicalDeserialize>(path: &Path) -> CliResult<T> {
+    let bytes =
+        fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+
+    deserialize(&bytes).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn deserialize<T: CanonicalDeserialize>(bytes: &[u8]) -> CliResult<T> {
+    T::deserialize_with_mode(bytes, Compress::Yes, Validate::Yes)
+        .map_err(|err| format!("failed to deserialize: {err}"))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_end_to_end<P: Pairing>(name: &str) {
+        let dir =
+            std::env::temp_dir().join(format!("segment-lookup-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |file: &str| dir.join(file);
+
+        setup::<P>(
+            5,
+            3,
+            2,
+            false,
+            true,
+            Some(1),
+            &path("pp.bin"),
+            &path("vk.bin"),
+        )
+        .unwrap();
+
+        let table_csv: String = (0..5)
+            .map(|i| format!("{}, {}\n", 2 * i, 2 * i + 1))
+            .collect();
+        fs::write(path("table.csv"), format!("# segments\n{table_csv}")).unwrap();
+        preprocess::<P>(
+            &path("table.csv"),
+            &path("pp.bin"),
+            &path("tpp.bin"),
+            &path("tvk.bin"),
+            None,
+        )
+        .unwrap();
+
+        // The same table given as JSON preprocesses identically.
+        let table_json: Vec<String> = (0..5)
+            .map(|i| format!("[{}, \"0x{:x}\"]", 2 * i, 2 * i + 1))
+            .collect();
+        fs::write(path("table.json"), format!("[{}]", table_json.join(", "))).unwrap();
+        preprocess::<P>(
+            &path("table.json"),
+            &path("pp.bin"),
+            &path("tpp_json.bin"),
+            &path("tvk_json.bin"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read(path("tpp.bin")).unwrap(),
+            fs::read(path("tpp_json.bin")).unwrap()
+        );
+
+        // Streaming a binary table gives the same preprocessed parameters.
+        load_table::<P>(
+            &open_public_parameters(&path("pp.bin")).unwrap(),
+            &path("table.csv"),
+        )
+        .unwrap()
+        .write_binary(fs::File::create(path("table.bin")).unwrap())
+        .unwrap();
+        preprocess::<P>(
+            &path("table.bin"),
+            &path("pp.bin"),
+            &path("tpp_streamed.bin"),
+            &path("tvk_streamed.bin"),
+            Some(4),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read(path("tpp.bin")).unwrap(),
+            fs::read(path("tpp_streamed.bin")).unwrap()
+        );
+        assert_eq!(
+            fs::read(path("tvk.bin")).unwrap(),
+            fs::read(path("tvk_streamed.bin")).unwrap()
+        );
+
+        fs::write(path("queries.json"), "[4, 0]").unwrap();
+        prove_queries::<P>(
+            &path("queries.json"),
+            &path("pp.bin"),
+            &path("tpp.bin"),
+            &path("statement.bin"),
+            &path("proof.bin"),
+            Some(2),
+        )
+        .unwrap();
+
+        verify_proof::<P>(
+            &path("vk.bin"),
+            &path("tvk.bin"),
+            &path("statement.bin"),
+            &path("proof.bin"),
+        )
+        .unwrap();
+        for (kind, file) in [
+            (ArtifactKind::Pp, "pp.bin"),
+            (ArtifactKind::Vk, "vk.bin"),
+            (ArtifactKind::Tpp, "tpp.bin"),
+            (ArtifactKind::Tvk, "tvk.bin"),
+            (ArtifactKind::Proof, "proof.bin"),
+            (ArtifactKind::Statement, "statement.bin"),
+        ] {
+            inspect::<P>(kind, &path(file)).unwrap();
+        }
+
+        // A proof for another statement is rejected with the failed check.
+        fs::write(path("queries.json"), "[1]").unwrap();
+        prove_queries::<P>(
+            &path("queries.json"),
+            &path("pp.bin"),
+            &path("tpp.bin"),
+            &path("other_statement.bin"),
+            &path("other_proof.bin"),
+            Some(3),
+        )
+        .unwrap();
+        let result = verify_proof::<P>(
+            &path("vk.bin"),
+            &path("tvk.bin"),
+            &path("statement.bin"),
+            &path("other_proof.bin"),
+        );
+        assert!(result.unwrap_err().starts_with("proof rejected"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_end_to_end_bn254() {
+        run_end_to_end::<Bn254>("bn254");
+    }
+
+    #[test]
+    fn test_end_to_end_bls12_381() {
+        run_end_to_end::<Bls12_381>("bls12-381");
+    }
+}
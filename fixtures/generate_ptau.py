@@ -0,0 +1,155 @@
+#!/usr/bin/env python3
+"""Generates bn254_tau2_power3.ptau, a small BN254 powers-of-tau file in the
+snarkjs ptau layout with the known secrets tau = 2, alpha = 3 and beta = 5.
+
+The curve arithmetic is implemented here independently of arkworks, so the
+fixture checks the reader against the snarkjs encoding rather than against
+the writer used by the unit tests.
+
+Layout (all integers little-endian, see snarkjs src/powersoftau_new.js):
+  "ptau" | version u32 = 1 | number of sections u32 = 7
+  for each section: type u32 | size u64 | data
+  1 header:            n8 u32 = 32 | q (n8 bytes) | power u32 | ceremony power u32
+  2 tau G1:            [tau^i]_1 for i in 0..2^{power+1}-1
+  3 tau G2:            [tau^i]_2 for i in 0..2^power
+  4 alpha tau G1:      [alpha * tau^i]_1 for i in 0..2^power
+  5 beta tau G1:       [beta * tau^i]_1 for i in 0..2^power
+  6 beta G2:           [beta]_2
+  7 contributions:     number of contributions u32 = 0
+Each base field element is stored in Montgomery form x * 2^256 mod q, G2
+coordinates as c0 then c1.
+"""
+
+import struct
+import sys
+
+Q = 21888242871839275222246405745257275088696311157297823662689037894645226208583
+R = 1 << 256
+POWER = 3
+TAU, ALPHA, BETA = 2, 3, 5
+
+
+class Fq2:
+    def __init__(self, c0, c1):
+        self.c0, self.c1 = c0 % Q, c1 % Q
+
+    def __add__(self, o):
+        return Fq2(self.c0 + o.c0, self.c1 + o.c1)
+
+    def __sub__(self, o):
+        return Fq2(self.c0 - o.c0, self.c1 - o.c1)
+
+    def __mul__(self, o):
+        if isinstance(o, int):
+            return Fq2(self.c0 * o, self.c1 * o)
+        # u^2 = -1
+        return Fq2(self.c0 * o.c0 - self.c1 * o.c1, self.c0 * o.c1 + self.c1 * o.c0)
+
+    def inv(self):
+        norm_inv = pow(self.c0 * self.c0 + self.c1 * self.c1, Q - 2, Q)
+        return Fq2(self.c0 * norm_inv, -self.c1 * norm_inv)
+
+    def __eq__(self, o):
+        return self.c0 == o.c0 and self.c1 == o.c1
+
+
+class Fq1:
+    def __init__(self, c0):
+        self.c0 = c0 % Q
+
+    def __add__(self, o):
+        return Fq1(self.c0 + o.c0)
+
+    def __sub__(self, o):
+        return Fq1(self.c0 - o.c0)
+
+    def __mul__(self, o):
+        return Fq1(self.c0 * (o if isinstance(o, int) else o.c0))
+
+    def inv(self):
+        return Fq1(pow(self.c0, Q - 2, Q))
+
+    def __eq__(self, o):
+        return self.c0 == o.c0
+
+
+def add(p1, p2):
+    if p1 is None:
+        return p2
+    if p2 is None:
+        return p1
+    (x1, y1), (x2, y2) = p1, p2
+    if x1 == x2:
+        if not (y1 == y2):
+            return None
+        slope = x1 * x1 * 3 * (y1 * 2).inv()
+    else:
+        slope = (y2 - y1) * (x2 - x1).inv()
+    x3 = slope * slope - x1 - x2
+    return (x3, slope * (x1 - x3) - y1)
+
+
+def mul(p, k):
+    acc = None
+    while k:
+        if k & 1:
+            acc = add(acc, p)
+        p = add(p, p)
+        k >>= 1
+    return acc
+
+
+G1 = (Fq1(1), Fq1(2))
+G2 = (
+    Fq2(
+        10857046999023057135944570762232829481370756359578518086990519993285655852781,
+        11559732032986387107991004021392285783925812861821192530917403151452391805634,
+    ),
+    Fq2(
+        8495653923123431417604973247489272438418190587263600148770280649306958101930,
+        4082367875863433681332203403145435568316851327593401208105741076214120093531,
+    ),
+)
+
+
+def field_bytes(element):
+    return (element * R % Q).to_bytes(32, "little")
+
+
+def g1_bytes(p):
+    return field_bytes(p[0].c0) + field_bytes(p[1].c0)
+
+
+def g2_bytes(p):
+    return b"".join(field_bytes(c) for c in (p[0].c0, p[0].c1, p[1].c0, p[1].c1))
+
+
+def powers(base, scalar, count):
+    points, p = [], mul(base, scalar)
+    for _ in range(count):
+        points.append(p)
+        p = mul(p, TAU)
+    return points
+
+
+def section(section_type, data):
+    return struct.pack("<IQ", section_type, len(data)) + data
+
+
+def main(path):
+    header = struct.pack("<I", 32) + Q.to_bytes(32, "little") + struct.pack("<II", POWER, POWER)
+    sections = [
+        section(1, header),
+        section(2, b"".join(g1_bytes(p) for p in powers(G1, 1, 2 ** (POWER + 1) - 1))),
+        section(3, b"".join(g2_bytes(p) for p in powers(G2, 1, 2**POWER))),
+        section(4, b"".join(g1_bytes(p) for p in powers(G1, ALPHA, 2**POWER))),
+        section(5, b"".join(g1_bytes(p) for p in powers(G1, BETA, 2**POWER))),
+        section(6, g2_bytes(mul(G2, BETA))),
+        section(7, struct.pack("<I", 0)),
+    ]
+    with open(path, "wb") as f:
+        f.write(b"ptau" + struct.pack("<II", 1, len(sections)) + b"".join(sections))
+
+
+if __name__ == "__main__":
+    main(sys.argv[1] if len(sys.argv) > 1 else "bn254_tau2_power3.ptau")
@@ -14,7 +14,7 @@ pub(crate) fn vanishing_poly_commitment_affine<C: CurveGroup>(
 ) -> C::Affine {
     let vanishing_poly: DensePolynomial<C::ScalarField> = domain.vanishing_polynomial().into();
 
-    Kzg::<C>::commit(&affine_srs, &vanishing_poly).into_affine()
+    Kzg::<C>::commit(affine_srs, &vanishing_poly).into_affine()
 }
 
 pub(crate) fn create_sub_domain<P: Pairing>(
@@ -26,7 +26,7 @@ pub(crate) fn create_sub_domain<P: Pairing>(
         return Err(Error::InvalidSegmentSize(segment_size));
     }
     if segment_size == 1 {
-        return Ok(original_domain.clone());
+        return Ok(*original_domain);
     }
     if !order.is_power_of_two() {
         return Err(Error::InvalidEvaluationDomainSize(order));
@@ -47,7 +47,7 @@ pub(crate) fn create_sub_domain<P: Pairing>(
         return Err(Error::InvalidEvaluationDomainSize(order));
     }
 
-    let roots_of_unity_larger_domain = roots_of_unity::<P>(&original_domain);
+    let roots_of_unity_larger_domain = roots_of_unity::<P>(original_domain);
     let group_gen = roots_of_unity_larger_domain[segment_size];
     let size_as_field_element = P::ScalarField::from(size);
     let size_inv = size_as_field_element
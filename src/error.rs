@@ -9,23 +9,45 @@ pub enum Error {
     InvalidLagrangeBasisCommitments(String),
     InvalidQuotientPolynomialCommitments(String),
     InvalidStructuredReferenceStrings,
+    InvalidPowersOfTau(String),
     InvalidNumberOfSegments(usize),
     InvalidNumberOfQueries(usize),
     InvalidSegmentIndex(usize),
     InvalidSegmentElementIndex(usize),
     InvalidSegmentSize(usize),
+    InvalidNumberOfColumns(usize),
+    InvalidTableTag(usize),
+    SegmentNotFound(usize),
+    TableValuesOutOfRange(usize),
+    UnsupportedUniquenessEncoding,
+    UnsupportedPedersenStatement,
+    TableParseFailed {
+        row: usize,
+        column: usize,
+        reason: String,
+    },
     InvalidEvaluationDomainSize(usize),
     InvalidPolynomialDegree(usize),
 
     SizeNotPowerOfTwo(usize),
 
+    ZeroKnowledgeNotEnabled,
+    PedersenStatementsNotEnabled,
+    WitnessNotBlinded,
+    LagrangeQuotientsNotEnabled,
+
     Pairing1Failed,
     Pairing2Failed,
     Pairing3Failed,
     Pairing4Failed,
+    BatchedPairingFailed,
+    BatchedProofFailed(usize),
     PointCheck1Failed,
     PointCheck2Failed,
     DegreeCheckFailed,
+    PaddingMultiplicityCheckFailed,
+    PaddingPositionCheckFailed,
+    DynamicTableCommitmentsMismatch,
 
     // Caulk Sub-protocol
     FailedToDivideByVanishingPolynomial,
@@ -35,4 +57,17 @@ pub enum Error {
 
     // Transcript
     FailedToSerializeElement,
+    FailedToDeserializeElement,
+
+    // Table import and export
+    FailedToReadTable(String),
+    FailedToWriteTable(String),
+    FailedToAccessScratchFile(String),
+    InvalidChunkSize(usize),
+
+    // Memory-mapped public parameters
+    FailedToMapFile(String),
+
+    // Round-by-round prover and verifier
+    UnexpectedRound(&'static str),
 }
@@ -73,12 +73,14 @@ impl<C: CurveGroup> Kzg<C> {
         (poly.evaluate(&challenge), proof.into())
     }
 
+    // Returns the opening proof of sum_i sep^i * poly_i, and the weights sep^i
+    // of the polynomials.
     pub fn batch_open(
         affine_srs: &[C::Affine],
         poly_list: &[DensePolynomial<C::ScalarField>],
         fr_opening: C::ScalarField,
         fr_separation: C::ScalarField,
-    ) -> C::Affine {
+    ) -> (C::Affine, Vec<C::ScalarField>) {
         let num_polys = poly_list.len();
         let powers_of_sep = powers_of_scalars::<C::ScalarField>(fr_separation, num_polys);
 
@@ -107,11 +109,12 @@ impl<C: CurveGroup> Kzg<C> {
             );
         }
 
-        Self::commit(affine_srs, &q).into()
+        (Self::commit(affine_srs, &q).into(), powers_of_sep)
     }
 }
 
 /// Create srs from rng
+#[allow(clippy::type_complexity)]
 pub fn unsafe_setup_from_rng<P: Pairing, R: Rng + ?Sized>(
     max_power_g1: usize,
     max_power_caulk_g1: usize,
@@ -128,6 +131,7 @@ pub fn unsafe_setup_from_rng<P: Pairing, R: Rng + ?Sized>(
 }
 
 /// Create srs from specific tau
+#[allow(clippy::type_complexity)]
 pub fn unsafe_setup_from_tau<P: Pairing, R: Rng + ?Sized>(
     max_power_g1: usize,
     caulk_max_power_g1: usize,
@@ -153,14 +157,14 @@ pub fn unsafe_setup_from_tau<P: Pairing, R: Rng + ?Sized>(
 const CHUNK_SIZE: usize = 1024;
 
 fn powers_of_scalars<F: FftField>(s: F, size: usize) -> Vec<F> {
-    let num_chunks = (size + CHUNK_SIZE - 1) / CHUNK_SIZE;
+    let num_chunks = size.div_ceil(CHUNK_SIZE);
 
     let mut result: Vec<F> = (0..num_chunks)
         .into_par_iter()
         .flat_map(|chunk_index| {
             let start_power = chunk_index * CHUNK_SIZE;
             let mut chunk = Vec::with_capacity(CHUNK_SIZE.min(size - start_power));
-            let mut power = s.pow(&[start_power as u64]);
+            let mut power = s.pow([start_power as u64]);
 
             for _ in 0..CHUNK_SIZE.min(size - start_power) {
                 chunk.push(power);
@@ -203,12 +207,7 @@ impl<P: Pairing> CaulkKzg<P> {
 
         let global_max_deg = affine_srs.len();
 
-        let mut d: usize = 0;
-        if max_deg == None {
-            d += global_max_deg;
-        } else {
-            d += max_deg.unwrap();
-        }
+        let d = max_deg.copied().unwrap_or(global_max_deg);
         let divisor =
             DensePolynomial::from_coefficients_vec(vec![-*challenge, P::ScalarField::one()]);
         let witness_polynomial = poly / &divisor;
@@ -291,6 +290,7 @@ impl<P: Pairing> CaulkKzg<P> {
         (evaluations, res.into_affine())
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn partial_open_g1(
         g1_affine_srs: &[P::G1Affine],
         polynomials: &[DensePolynomial<P::ScalarField>],
@@ -298,7 +298,7 @@ impl<P: Pairing> CaulkKzg<P> {
         point: &P::ScalarField,
     ) -> Result<(P::G1Affine, P::G1Affine, DensePolynomial<P::ScalarField>), Error> {
         if polynomials.is_empty() {
-            let proof = Self::bi_poly_commit_g1(g1_affine_srs, &polynomials, deg_x)?;
+            let proof = Self::bi_poly_commit_g1(g1_affine_srs, polynomials, deg_x)?;
             return Ok((
                 P::G1Affine::zero(),
                 proof,
@@ -379,29 +379,29 @@ impl<P: Pairing> CaulkKzg<P> {
         // vanishing polynomial
         let z_tau = prod;
 
-        // commit to z_tau(X) in g2
+        // e(pi, [z_tau(x)]_2) is expanded into prod_j e(z_tau_j * pi, [x^j]_2),
+        // so that the G2 elements come from the SRS and can be shared across
+        // batched pairing equations.
         assert!(
             g2_affine_srs.len() >= z_tau.len(),
             "KZG verifier doesn't have enough g2 powers"
         );
-        let g2_z_tau = P::G2::msm_unchecked(&g2_affine_srs[..z_tau.len()], &z_tau.coeffs);
 
         let global_max_deg = g1_affine_srs.len();
 
-        let mut d: usize = 0;
-        if deg_max == None {
-            d += global_max_deg;
-        } else {
-            d += deg_max.unwrap();
-        }
-
-        let res = vec![
-            (
-                g1_tau - g1_affine_com,
-                g2_affine_srs[global_max_deg - d].into_group(),
-            ),
-            (pi.into_group(), g2_z_tau),
-        ];
+        let d = deg_max.copied().unwrap_or(global_max_deg);
+
+        let mut res = vec![(
+            g1_tau - g1_affine_com,
+            g2_affine_srs[global_max_deg - d].into_group(),
+        )];
+        res.extend(
+            z_tau
+                .coeffs
+                .iter()
+                .zip(g2_affine_srs)
+                .map(|(&coeff, g2_affine)| (pi.mul(coeff), g2_affine.into_group())),
+        );
 
         res
     }
@@ -419,10 +419,8 @@ impl<P: Pairing> CaulkKzg<P> {
                 partial_eval.into_group() - g1_affine_com,
                 g2_affine_srs[0].into_group(),
             ),
-            (
-                pi.into_group(),
-                g2_affine_srs[deg_x].into_group() - g2_affine_srs[0].mul(*point),
-            ),
+            (pi.into_group(), g2_affine_srs[deg_x].into_group()),
+            (pi.mul(-*point), g2_affine_srs[0].into_group()),
         ];
         res
     }
@@ -1,8 +1,9 @@
 use crate::error::Error;
 use ark_ec::pairing::Pairing;
-use ark_ec::CurveGroup;
+use ark_ec::{CurveGroup, VariableBaseMSM};
 use ark_ff::Field;
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_std::Zero;
 use rayon::prelude::*;
 use std::iter;
 use std::ops::Mul;
@@ -68,6 +69,76 @@ pub(crate) fn zero_opening_proofs<P: Pairing>(
     Ok(opening_proofs)
 }
 
+// Computes [tau^t * (L_i(tau) - L_i(0)) / tau]_1 for all i in O(n log n) time,
+// with the shift t such that t + n - 2 is at most the largest power of tau.
+// With L_i(X) = (1/n) * sum_k (w^{-i} X)^k,
+// (L_i(X) - L_i(0)) / X = (1/n) * sum_{m < n - 1} w^{-i(m + 1)} * X^m,
+// which is w^{-i} times the inverse FFT of [tau^{t + m}]_1 padded with zero.
+pub(crate) fn shifted_zero_opening_proofs<P: Pairing>(
+    srs_g1_affine: &[P::G1Affine],
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+    shift: usize,
+) -> Vec<P::G1Affine> {
+    let domain_size = domain.size();
+    let mut g1_list: Vec<P::G1> = srs_g1_affine[shift..shift + domain_size - 1]
+        .par_iter()
+        .map(|&g1_affine| g1_affine.into())
+        .collect();
+    g1_list.push(P::G1::zero());
+    domain.ifft_in_place(&mut g1_list);
+    g1_list
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, g1)| *g1 *= domain.element((domain_size - i) % domain_size));
+
+    P::G1::normalize_batch(&g1_list)
+}
+
+// Computes [(L_i(tau) - 1) / (tau - w^i)]_1 for all i in O(n log n) time.
+// With L_i(X) = (1/n) * sum_k (w^{-i} X)^k,
+// (L_i(X) - 1) / (X - w^i) = (1/n) * sum_m (n - 1 - m) * w^{-i(m + 1)} * X^m,
+// which is w^{-i} times the inverse FFT of [(n - 1 - m) * tau^m]_1.
+pub(crate) fn lagrange_basis_quotients<P: Pairing>(
+    srs_g1_affine: &[P::G1Affine],
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+) -> Vec<P::G1Affine> {
+    let domain_size = domain.size();
+    let mut g1_list: Vec<P::G1> = srs_g1_affine[..domain_size]
+        .par_iter()
+        .enumerate()
+        .map(|(m, g1_affine)| g1_affine.mul(P::ScalarField::from((domain_size - 1 - m) as u64)))
+        .collect();
+    domain.ifft_in_place(&mut g1_list);
+    g1_list
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, g1)| *g1 *= domain.element((domain_size - i) % domain_size));
+
+    P::G1::normalize_batch(&g1_list)
+}
+
+// Computes [(L_i(tau) - 1) / (tau - w^i)]_1 for a single i with an MSM of
+// size n, using the coefficients of `lagrange_basis_quotients`.
+pub(crate) fn lagrange_basis_quotient<P: Pairing>(
+    srs_g1_affine: &[P::G1Affine],
+    domain: &Radix2EvaluationDomain<P::ScalarField>,
+    i: usize,
+) -> P::G1Affine {
+    let domain_size = domain.size();
+    let fr_w_inv_i = domain.element((domain_size - i % domain_size) % domain_size);
+    let mut fr_w_inv_pow = fr_w_inv_i * domain.size_inv;
+    let scalars: Vec<P::ScalarField> = (0..domain_size)
+        .map(|m| {
+            let scalar = P::ScalarField::from((domain_size - 1 - m) as u64) * fr_w_inv_pow;
+            fr_w_inv_pow *= fr_w_inv_i;
+
+            scalar
+        })
+        .collect();
+
+    P::G1::msm_unchecked(&srs_g1_affine[..domain_size], &scalars).into_affine()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,7 +156,7 @@ mod tests {
         domain: &Radix2EvaluationDomain<P::ScalarField>,
     ) -> Vec<DensePolynomial<P::ScalarField>> {
         let vanishing_poly: DensePolynomial<P::ScalarField> = domain.vanishing_polynomial().into();
-        let roots_of_unity = roots_of_unity::<P>(&domain);
+        let roots_of_unity = roots_of_unity::<P>(domain);
         let roots_of_unity_div_domain_size: Vec<P::ScalarField> = roots_of_unity
             .par_iter()
             .map(|&root| root / domain.size_as_field_element())
@@ -143,4 +214,54 @@ mod tests {
 
         assert_eq!(li_proofs_slow, li_proofs_fast);
     }
+
+    #[test]
+    fn test_lagrange_basis_quotients() {
+        let n = 32;
+        let domain = Radix2EvaluationDomain::<ScalarField>::new(n).unwrap();
+        let lagrange_basis = lagrange_basis::<Bn254>(&domain);
+
+        let mut rng = test_rng();
+
+        let (srs_g1, _, _, _) = unsafe_setup_from_rng::<Bn254, _>(n - 1, 0, &mut rng);
+        let quotients_slow: Vec<G1Affine> = lagrange_basis
+            .iter()
+            .zip(domain.elements())
+            .map(|(li, w_pow_i)| Kzg::<<Bn254 as Pairing>::G1>::open(&srs_g1, li, w_pow_i).1)
+            .collect();
+
+        let quotients_fast = lagrange_basis_quotients::<Bn254>(&srs_g1, &domain);
+
+        assert_eq!(quotients_slow, quotients_fast);
+        for i in [0, 1, 17, n - 1] {
+            assert_eq!(
+                lagrange_basis_quotient::<Bn254>(&srs_g1, &domain, i),
+                quotients_slow[i]
+            );
+        }
+    }
+
+    #[test]
+    fn test_shifted_zero_opening_proofs() {
+        let n = 32;
+        let shift = 5;
+        let domain = Radix2EvaluationDomain::<ScalarField>::new(n).unwrap();
+        let lagrange_basis = lagrange_basis::<Bn254>(&domain);
+
+        let mut rng = test_rng();
+
+        let (srs_g1, _, _, _) = unsafe_setup_from_rng::<Bn254, _>(n - 2 + shift, 0, &mut rng);
+        let proofs_slow: Vec<G1Affine> = lagrange_basis
+            .iter()
+            .map(|li| {
+                let poly_li_div_x = DensePolynomial::from_coefficients_slice(&li.coeffs[1..]);
+                Kzg::<<Bn254 as Pairing>::G1>::commit_with_offset(&srs_g1, &poly_li_div_x, shift)
+                    .into()
+            })
+            .collect();
+
+        let proofs_fast = shifted_zero_opening_proofs::<Bn254>(&srs_g1, &domain, shift);
+
+        assert_eq!(proofs_slow, proofs_fast);
+    }
 }
@@ -0,0 +1,197 @@
+use crate::error::Error;
+use ark_ec::AffineRepr;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use memmap2::Mmap;
+use rayon::prelude::*;
+use std::sync::{Arc, OnceLock};
+
+/// A list of points that is either held in memory or read on access from a
+/// memory-mapped file, in which it is stored as its uncompressed canonical
+/// serialization.
+///
+/// Mapped points are decoded without validation, so only the accessed entries
+/// are touched. [`LazyPoints::as_slice`] decodes a mapped list as a whole once,
+/// and keeps the decoded points in memory.
+#[derive(Debug)]
+pub struct LazyPoints<G: AffineRepr>(Repr<G>);
+
+#[derive(Debug)]
+enum Repr<G: AffineRepr> {
+    Memory(Vec<G>),
+    Mapped {
+        mmap: Arc<Mmap>,
+        // Offset of the first point in the file.
+        offset: usize,
+        len: usize,
+        decoded: OnceLock<Vec<G>>,
+    },
+}
+
+impl<G: AffineRepr> From<Vec<G>> for LazyPoints<G> {
+    fn from(points: Vec<G>) -> Self {
+        Self(Repr::Memory(points))
+    }
+}
+
+impl<G: AffineRepr> LazyPoints<G> {
+    // Maps the serialized list at `*offset` of the file, and advances the
+    // offset past it.
+    pub(crate) fn map(mmap: &Arc<Mmap>, offset: &mut usize) -> Result<Self, Error> {
+        let len_bytes = mmap
+            .get(*offset..*offset + 8)
+            .ok_or(Error::FailedToDeserializeElement)?;
+        let len = u64::deserialize_uncompressed_unchecked(len_bytes)
+            .map_err(|_| Error::FailedToDeserializeElement)? as usize;
+        let start = *offset + 8;
+        let end = start + len * element_size::<G>();
+        if end > mmap.len() {
+            return Err(Error::FailedToDeserializeElement);
+        }
+        *offset = end;
+
+        Ok(Self(Repr::Mapped {
+            mmap: mmap.clone(),
+            offset: start,
+            len,
+            decoded: OnceLock::new(),
+        }))
+    }
+
+    /// Returns the number of points without decoding them.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Memory(points) => points.len(),
+            Repr::Mapped { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_mapped(&self) -> bool {
+        matches!(self.0, Repr::Mapped { .. })
+    }
+
+    // Whether a mapped list has been decoded as a whole.
+    #[cfg(test)]
+    pub(crate) fn is_decoded(&self) -> bool {
+        match &self.0 {
+            Repr::Memory(_) => true,
+            Repr::Mapped { decoded, .. } => decoded.get().is_some(),
+        }
+    }
+
+    /// Returns the points as a slice. A mapped list is decoded as a whole on
+    /// the first call, and the decoded points are kept in memory. Fails if a
+    /// mapped point cannot be decoded.
+    pub fn as_slice(&self) -> Result<&[G], Error> {
+        match &self.0 {
+            Repr::Memory(points) => Ok(points),
+            Repr::Mapped { decoded, .. } => {
+                if let Some(points) = decoded.get() {
+                    return Ok(points);
+                }
+                let points = self.to_vec()?;
+
+                Ok(decoded.get_or_init(|| points))
+            },
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Result<G, Error> {
+        match &self.0 {
+            Repr::Memory(points) => points
+                .get(index)
+                .copied()
+                .ok_or(Error::InvalidSegmentElementIndex(index)),
+            Repr::Mapped {
+                mmap, offset, len, ..
+            } => {
+                if index >= *len {
+                    return Err(Error::InvalidSegmentElementIndex(index));
+                }
+                let size = element_size::<G>();
+                let start = offset + index * size;
+
+                G::deserialize_uncompressed_unchecked(&mmap[start..start + size])
+                    .map_err(|_| Error::FailedToDeserializeElement)
+            },
+        }
+    }
+
+    // Reads the points in start..start + len, decoding only those of a mapped
+    // list.
+    pub(crate) fn read(&self, start: usize, len: usize) -> Result<Vec<G>, Error> {
+        let end = start + len;
+        if end > self.len() {
+            return Err(Error::InvalidSegmentElementIndex(end));
+        }
+        match &self.0 {
+            Repr::Memory(points) => Ok(points[start..end].to_vec()),
+            Repr::Mapped { .. } => (start..end).into_par_iter().map(|i| self.get(i)).collect(),
+        }
+    }
+
+    pub(crate) fn to_vec(&self) -> Result<Vec<G>, Error> {
+        self.read(0, self.len())
+    }
+}
+
+fn element_size<G: AffineRepr>() -> usize {
+    G::zero().uncompressed_size()
+}
+
+// Serialized as a `Vec<G>`, so the in-memory and mapped lists have the same
+// encoding.
+impl<G: AffineRepr> Valid for LazyPoints<G> {
+    fn check(&self) -> Result<(), SerializationError> {
+        match &self.0 {
+            Repr::Memory(points) => G::batch_check(points.iter()),
+            Repr::Mapped { .. } => {
+                let points = self.to_vec().map_err(|_| SerializationError::InvalidData)?;
+                G::batch_check(points.iter())
+            },
+        }
+    }
+}
+
+impl<G: AffineRepr> CanonicalSerialize for LazyPoints<G> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match &self.0 {
+            Repr::Memory(points) => points.serialize_with_mode(writer, compress),
+            Repr::Mapped { len, .. } => {
+                (*len as u64).serialize_with_mode(&mut writer, compress)?;
+                for i in 0..*len {
+                    self.get(i)
+                        .map_err(|_| SerializationError::InvalidData)?
+                        .serialize_with_mode(&mut writer, compress)?;
+                }
+
+                Ok(())
+            },
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        8 + self.len() * G::zero().serialized_size(compress)
+    }
+}
+
+impl<G: AffineRepr> CanonicalDeserialize for LazyPoints<G> {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Vec::<G>::deserialize_with_mode(reader, compress, validate).map(Self::from)
+    }
+}
@@ -4,12 +4,17 @@ mod domain;
 mod error;
 pub mod kzg;
 mod lagrange_basis;
+pub mod lazy_points;
+pub mod multi_column;
 pub mod multi_unity;
 pub mod prover;
 pub mod public_parameters;
+pub mod srs;
+pub mod streaming;
 pub mod table;
+mod table_io;
 mod toeplitz;
-mod transcript;
+pub mod transcript;
 pub mod verifier;
 pub mod witness;
 
@@ -17,15 +22,30 @@ const COMPRESS_MOD: Compress = Compress::No;
 
 #[cfg(test)]
 mod tests {
-    use crate::prover::prove;
-    use crate::public_parameters::PublicParameters;
+    use crate::error::Error;
+    use crate::kzg::Kzg;
+    use crate::prover::{
+        prove, prove_dynamic_table, prove_dynamic_table_with_transcript, prove_with_transcript,
+        Proof, ProofOptions,
+    };
+    use crate::public_parameters::{PublicParameters, VerifierKey};
     use crate::table::{rand_segments, Table, TablePreprocessedParameters};
-    use crate::verifier::verify;
-    use crate::witness::Witness;
+    use crate::transcript::{PoseidonTranscript, Transcript};
+    use crate::verifier::{
+        challenges_with_transcript, verify, verify_debug, verify_dynamic_table,
+        verify_dynamic_table_with_transcript, verify_with_transcript, VerifyOptions,
+    };
+    use crate::witness::{PedersenBlinder, Statement, Witness};
     use ark_ec::pairing::Pairing;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::Field;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
     use ark_std::rand::RngCore;
     use ark_std::test_rng;
 
+    #[allow(clippy::type_complexity)]
     fn prepare_common_inputs<P: Pairing>(
         num_table_segments: usize,
         num_witness_segments: usize,
@@ -34,7 +54,7 @@ mod tests {
         PublicParameters<P>,
         Table<P>,
         Witness<P>,
-        P::G1Affine,
+        Statement<P>,
         TablePreprocessedParameters<P>,
     ) {
         let mut rng = test_rng();
@@ -49,14 +69,14 @@ mod tests {
         let t = Table::<P>::new(&pp, segments).expect("Failed to create table");
         let tpp = t.preprocess(&pp).unwrap();
 
-        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-            .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+        let queried_segment_indices: Vec<usize> = (0..pp.logical_num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
             .collect();
 
         let witness =
             Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
 
-        let statement = witness.generate_statement(&pp.g1_affine_srs);
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
 
         (pp, t, witness, statement, tpp)
     }
@@ -93,8 +113,828 @@ mod tests {
 
             let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
 
-            let result = verify(&pp, &tpp, statement, &proof, rng);
+            let result = verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                rng,
+            );
+            assert!(result.is_ok(), "Failed to verify proof: {:?} num_table_segments: {}, num_witness_segments: {}, segment_size: {}", result, num_table_segments, num_witness_segments, segment_size);
+        }
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_with_padding() {
+        let inputs = [
+            (1, 1, 1),
+            (3, 3, 3),
+            (5, 2, 3),
+            (6, 5, 1),
+            (7, 3, 5),
+            (2, 9, 2),
+        ];
+        for (num_table_segments, num_witness_segments, segment_size) in inputs.into_iter() {
+            let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(
+                num_table_segments,
+                num_witness_segments,
+                segment_size,
+            );
+            assert!(pp.num_table_segments.is_power_of_two());
+            assert!(pp.num_witness_segments.is_power_of_two());
+            assert!(pp.segment_size.is_power_of_two());
+
+            let rng = &mut test_rng();
+
+            let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
+
+            let result = verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                rng,
+            );
             assert!(result.is_ok(), "Failed to verify proof: {:?} num_table_segments: {}, num_witness_segments: {}, segment_size: {}", result, num_table_segments, num_witness_segments, segment_size);
         }
     }
+
+    #[test]
+    fn test_success_prove_and_verify_with_variable_number_of_queries() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+
+        let witness = Witness::new(&pp, &tpp.adjusted_table_values, &[5, 2]).unwrap();
+        assert_eq!(witness.segment_indices, vec![5, 2, 8, 8]);
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+
+        assert_eq!(statement.num_queries, 2);
+
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(
+            &pp.verifier_key().unwrap(),
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut rng
+        )
+        .is_ok());
+
+        // The number of queries is an input of the verifier, bound to the transcript.
+        for num_queries in [1, 3] {
+            let other_statement = Statement {
+                num_queries,
+                ..statement
+            };
+            assert!(verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                other_statement,
+                &proof,
+                &mut rng
+            )
+            .is_err());
+        }
+        let other_statement = Statement {
+            num_queries: 5,
+            ..statement
+        };
+        assert!(matches!(
+            verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                other_statement,
+                &proof,
+                &mut rng
+            ),
+            Err(Error::InvalidNumberOfQueries(5))
+        ));
+
+        assert!(Witness::new(&pp, &tpp.adjusted_table_values, &[]).is_err());
+        assert!(Witness::new(&pp, &tpp.adjusted_table_values, &[0; 5]).is_err());
+    }
+
+    // Builds a witness over arbitrary segment indices, as a dishonest prover could.
+    fn forge_witness<P: Pairing>(
+        pp: &PublicParameters<P>,
+        table_values: &[P::ScalarField],
+        segment_indices: Vec<usize>,
+        num_queries: usize,
+    ) -> Witness<P> {
+        let mut witness = Witness::new(pp, table_values, &[0]).unwrap();
+        witness.evaluations = segment_indices
+            .iter()
+            .flat_map(|&j| table_values[j * pp.segment_size..(j + 1) * pp.segment_size].to_vec())
+            .collect();
+        let domain_v =
+            Radix2EvaluationDomain::<P::ScalarField>::new(witness.evaluations.len()).unwrap();
+        witness.poly = DensePolynomial::from_coefficients_vec(domain_v.ifft(&witness.evaluations));
+        witness.segment_indices = segment_indices;
+        witness.num_queries = num_queries;
+
+        witness
+    }
+
+    #[test]
+    fn test_padding_segment_not_queryable() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let padding = pp.padding_segment_index();
+
+        // A real query on the padding segment hides a query from the statement.
+        let witness = forge_witness(
+            &pp,
+            &tpp.adjusted_table_values,
+            vec![padding, 2, padding, padding],
+            2,
+        );
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(matches!(
+            verify_debug(&vk, &tpp.verifier_key(), statement, &proof),
+            Err(Error::PaddingMultiplicityCheckFailed)
+        ));
+
+        // A real segment in the tail is not covered by the statement.
+        let witness = forge_witness(
+            &pp,
+            &tpp.adjusted_table_values,
+            vec![padding, 2, 1, padding],
+            2,
+        );
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(matches!(
+            verify_debug(&vk, &tpp.verifier_key(), statement, &proof),
+            Err(Error::PaddingPositionCheckFailed)
+        ));
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_with_context() {
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let vk = pp.verifier_key().unwrap();
+        let rng = &mut test_rng();
+
+        let context = b"chain_id=1;height=100";
+        let proof = prove_with_transcript(
+            &pp,
+            &tpp,
+            &witness,
+            statement,
+            &mut Transcript::new(),
+            &ProofOptions::default().context(context),
+            rng,
+        )
+        .unwrap();
+        let verify_with_context = |proof: &Proof<_>, context: &[u8]| {
+            verify_with_transcript(
+                &vk,
+                &tpp.verifier_key(),
+                statement,
+                proof,
+                &mut Transcript::new(),
+                &VerifyOptions::default().context(context),
+                &mut test_rng(),
+            )
+        };
+        assert!(verify_with_context(&proof, context).is_ok());
+
+        // The proof cannot be replayed under another context, or without one.
+        for other_context in [&b"chain_id=1;height=101"[..], b"chain_id=2;height=100", b""] {
+            assert!(verify_with_context(&proof, other_context).is_err());
+        }
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
+
+        // A proof without a context is a proof under the empty context.
+        let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
+        assert!(verify_with_context(&proof, b"").is_ok());
+        assert!(verify_with_context(&proof, context).is_err());
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_with_poseidon_transcript() {
+        type Fr = <ark_bn254::Bn254 as Pairing>::ScalarField;
+
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let vk = pp.verifier_key().unwrap();
+        let rng = &mut test_rng();
+
+        let proof = prove_with_transcript(
+            &pp,
+            &tpp,
+            &witness,
+            statement,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &ProofOptions::default().label(b"lookup"),
+            rng,
+        )
+        .unwrap();
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &VerifyOptions::default().label(b"lookup"),
+            rng
+        )
+        .is_ok());
+
+        // The challenges depend on the transcript.
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
+        let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &VerifyOptions::default().label(b"lookup"),
+            rng
+        )
+        .is_err());
+
+        // The plain functions run on a fresh default transcript.
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut Transcript::new(),
+            &VerifyOptions::default(),
+            rng
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_with_outer_transcript() {
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let vk = pp.verifier_key().unwrap();
+        let rng = &mut test_rng();
+
+        // The outer protocol appends its own messages before the lookup.
+        let outer_transcript = |message: &[u8]| {
+            let mut transcript = merlin::Transcript::new(b"outer protocol");
+            transcript.append_message(b"outer_message", message);
+            transcript
+        };
+
+        let mut prover_transcript = outer_transcript(b"commitments");
+        let proof = prove_with_transcript(
+            &pp,
+            &tpp,
+            &witness,
+            statement,
+            &mut prover_transcript,
+            &ProofOptions::default().label(b"lookup"),
+            rng,
+        )
+        .unwrap();
+        let mut verifier_transcript = outer_transcript(b"commitments");
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut verifier_transcript,
+            &VerifyOptions::default().label(b"lookup"),
+            rng
+        )
+        .is_ok());
+
+        // Both sides continue the outer protocol from the same state.
+        let mut prover_challenge = [0u8; 32];
+        prover_transcript.challenge_bytes(b"outer_challenge", &mut prover_challenge);
+        let mut verifier_challenge = [0u8; 32];
+        verifier_transcript.challenge_bytes(b"outer_challenge", &mut verifier_challenge);
+        assert_eq!(prover_challenge, verifier_challenge);
+
+        // The proof is bound to the outer transcript and to the label.
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut outer_transcript(b"other commitments"),
+            &VerifyOptions::default().label(b"lookup"),
+            rng
+        )
+        .is_err());
+        assert!(verify_with_transcript(
+            &vk,
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut outer_transcript(b"commitments"),
+            &VerifyOptions::default().label(b"other lookup"),
+            rng
+        )
+        .is_err());
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_dynamic_table() {
+        let inputs = [(4, 4, 4), (8, 2, 4), (5, 3, 3), (16, 8, 1)];
+        for (num_table_segments, num_witness_segments, segment_size) in inputs.into_iter() {
+            let mut rng = test_rng();
+            let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+                .num_table_segments(num_table_segments)
+                .num_witness_segments(num_witness_segments)
+                .segment_size(segment_size)
+                .build(&mut rng)
+                .expect("Failed to setup public parameters");
+            let vk = pp.verifier_key().unwrap();
+
+            // The prover builds the table at runtime and commits to it.
+            let segments = rand_segments::generate(&pp);
+            let t = Table::new(&pp, segments).expect("Failed to create table");
+            let table_statement = t.commit(&pp).unwrap();
+
+            let queried_segment_indices: Vec<usize> = (0..num_witness_segments)
+                .map(|_| rng.next_u32() as usize % num_table_segments)
+                .collect();
+            let witness = Witness::new(&pp, &t.values, &queried_segment_indices).unwrap();
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+
+            let proof =
+                prove_dynamic_table(&pp, &t, table_statement, &witness, statement, &mut rng)
+                    .unwrap();
+            let result = verify_dynamic_table(&vk, table_statement, statement, &proof, &mut rng);
+            assert!(result.is_ok(), "Failed to verify proof: {:?} num_table_segments: {}, num_witness_segments: {}, segment_size: {}", result, num_table_segments, num_witness_segments, segment_size);
+
+            // The proof does not hold for the commitment to another table.
+            let other_t = Table::new(
+                &pp,
+                rand_segments::generate(&pp).into_iter().rev().collect(),
+            )
+            .expect("Failed to create table");
+            let other_table_statement = other_t.commit(&pp).unwrap();
+            assert!(
+                verify_dynamic_table(&vk, other_table_statement, statement, &proof, &mut rng)
+                    .is_err()
+            );
+
+            // A witness that is not in the committed table is rejected.
+            let other_witness =
+                Witness::new(&pp, &other_t.values, &queried_segment_indices).unwrap();
+            let other_statement =
+                other_witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+            let proof = prove_dynamic_table(
+                &pp,
+                &t,
+                table_statement,
+                &other_witness,
+                other_statement,
+                &mut rng,
+            )
+            .unwrap();
+            assert!(
+                verify_dynamic_table(&vk, table_statement, other_statement, &proof, &mut rng)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_dynamic_table_with_poseidon_transcript() {
+        type Fr = <ark_bn254::Bn254 as Pairing>::ScalarField;
+
+        let mut rng = test_rng();
+        let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+
+        let t = Table::new(&pp, rand_segments::generate(&pp)).expect("Failed to create table");
+        let table_statement = t.commit(&pp).unwrap();
+        let witness = Witness::new(&pp, &t.values, &[1, 3, 3, 7]).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+
+        let proof = prove_dynamic_table_with_transcript(
+            &pp,
+            &t,
+            table_statement,
+            &witness,
+            statement,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &ProofOptions::default().label(b"lookup"),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(verify_dynamic_table_with_transcript(
+            &vk,
+            table_statement,
+            statement,
+            &proof,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &VerifyOptions::default().label(b"lookup"),
+            &mut rng
+        )
+        .is_ok());
+
+        // The challenges depend on the transcript.
+        assert!(verify_dynamic_table(&vk, table_statement, statement, &proof, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_deserialized_parameters() {
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let rng = &mut test_rng();
+        let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut pp_bytes = Vec::new();
+            pp.serialize_with_mode(&mut pp_bytes, compress).unwrap();
+            let mut tpp_bytes = Vec::new();
+            tpp.serialize_with_mode(&mut tpp_bytes, compress).unwrap();
+            let mut proof_bytes = Vec::new();
+            proof
+                .serialize_with_mode(&mut proof_bytes, compress)
+                .unwrap();
+
+            let pp = PublicParameters::<ark_bn254::Bn254>::deserialize_with_mode(
+                &pp_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+            let tpp = TablePreprocessedParameters::<ark_bn254::Bn254>::deserialize_with_mode(
+                &tpp_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+            let proof = Proof::<ark_bn254::Bn254>::deserialize_with_mode(
+                &proof_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+
+            assert!(verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                rng
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_verify_with_deserialized_verifier_key() {
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let rng = &mut test_rng();
+        let proof = prove(&pp, &tpp, &witness, statement, rng).unwrap();
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut vk_bytes = Vec::new();
+            pp.verifier_key()
+                .unwrap()
+                .serialize_with_mode(&mut vk_bytes, compress)
+                .unwrap();
+            let vk = VerifierKey::<ark_bn254::Bn254>::deserialize_with_mode(
+                &vk_bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+
+            assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_zero_knowledge() {
+        let inputs = [(4, 1, 1), (4, 4, 4), (4, 16, 4), (8, 8, 1), (16, 8, 4)];
+        for (num_table_segments, num_witness_segments, segment_size) in inputs.into_iter() {
+            let mut rng = test_rng();
+            let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+                .num_table_segments(num_table_segments)
+                .num_witness_segments(num_witness_segments)
+                .segment_size(segment_size)
+                .zero_knowledge(true)
+                .build(&mut rng)
+                .expect("Failed to setup public parameters");
+            let segments = rand_segments::generate(&pp);
+            let t = Table::new(&pp, segments).expect("Failed to create table");
+            let tpp = t.preprocess(&pp).unwrap();
+
+            let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+                .collect();
+            let witness = Witness::new_blinded(
+                &pp,
+                &tpp.adjusted_table_values,
+                &queried_segment_indices,
+                &mut rng,
+            )
+            .unwrap();
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+
+            let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+
+            let result = verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                &mut rng,
+            );
+            assert!(result.is_ok(), "Failed to verify proof: {:?} num_table_segments: {}, num_witness_segments: {}, segment_size: {}", result, num_table_segments, num_witness_segments, segment_size);
+        }
+    }
+
+    #[test]
+    fn test_success_prove_and_verify_with_pedersen_statement() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<ark_bn254::Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .pedersen_statements(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+
+        let queried_segment_indices = [3, 1, 3, 6];
+        let witness = Witness::new_blinded(
+            &pp,
+            &tpp.adjusted_table_values,
+            &queried_segment_indices,
+            &mut rng,
+        )
+        .unwrap();
+        let blinder = PedersenBlinder::rand(&mut rng);
+        let statement = witness
+            .generate_pedersen_statement(
+                pp.g1_affine_srs.as_slice().unwrap(),
+                &pp.g1_affine_srs_gamma,
+                &blinder,
+            )
+            .unwrap();
+        assert_ne!(
+            statement,
+            witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap())
+        );
+
+        let prove_pedersen = |blinder: &PedersenBlinder<_>, rng: &mut _| {
+            prove_with_transcript(
+                &pp,
+                &tpp,
+                &witness,
+                statement,
+                &mut Transcript::new(),
+                &ProofOptions::default().pedersen_blinder(blinder),
+                rng,
+            )
+        };
+        let proof = prove_pedersen(&blinder, &mut rng).unwrap();
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_ok());
+
+        // The proof does not verify with another blinder or without one.
+        let other_blinder = PedersenBlinder::rand(&mut rng);
+        let proof = prove_pedersen(&other_blinder, &mut rng).unwrap();
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_err());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_err());
+
+        // Plain statements still verify with these parameters.
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_ok());
+
+        // The witness of a Pedersen statement must be blinded.
+        let plain_witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = plain_witness
+            .generate_pedersen_statement(
+                pp.g1_affine_srs.as_slice().unwrap(),
+                &pp.g1_affine_srs_gamma,
+                &blinder,
+            )
+            .unwrap();
+        assert!(matches!(
+            prove_with_transcript(
+                &pp,
+                &tpp,
+                &plain_witness,
+                statement,
+                &mut Transcript::new(),
+                &ProofOptions::default().pedersen_blinder(&blinder),
+                &mut rng,
+            ),
+            Err(Error::WitnessNotBlinded)
+        ));
+    }
+
+    #[test]
+    fn test_pedersen_statements_not_enabled() {
+        let (pp, _, witness, statement, tpp) = prepare_common_inputs::<ark_bn254::Bn254>(8, 4, 4);
+        let rng = &mut test_rng();
+        let blinder = PedersenBlinder::rand(rng);
+
+        assert!(matches!(
+            witness.generate_pedersen_statement(
+                pp.g1_affine_srs.as_slice().unwrap(),
+                &pp.g1_affine_srs_gamma,
+                &blinder
+            ),
+            Err(Error::PedersenStatementsNotEnabled)
+        ));
+        assert!(matches!(
+            prove_with_transcript(
+                &pp,
+                &tpp,
+                &witness,
+                statement,
+                &mut Transcript::new(),
+                &ProofOptions::default().pedersen_blinder(&blinder),
+                rng
+            ),
+            Err(Error::PedersenStatementsNotEnabled)
+        ));
+    }
+
+    // Tests a guessed witness against a proof in every way that works without
+    // blinding: recomputes the statement, [M(tau)]_1, [A(tau)]_1, a_0, and
+    // [R_B(tau)]_1 from the guess, and evaluates F(X) and D(X) at the opened
+    // points. Returns whether each of them matches.
+    fn match_guessed_witness(
+        pp: &PublicParameters<ark_bn254::Bn254>,
+        tpp: &TablePreprocessedParameters<ark_bn254::Bn254>,
+        statement: Statement<ark_bn254::Bn254>,
+        proof: &Proof<ark_bn254::Bn254>,
+        guessed_segment_indices: &[usize],
+    ) -> Vec<bool> {
+        type Fr = <ark_bn254::Bn254 as Pairing>::ScalarField;
+        type G1 = <ark_bn254::Bn254 as Pairing>::G1;
+        let g1_affine_srs = pp.g1_affine_srs.as_slice().unwrap();
+        let mut transcript = Transcript::new();
+        VerifyOptions::default().append_to(&mut transcript).unwrap();
+        let challenges = challenges_with_transcript(
+            &pp.verifier_key().unwrap(),
+            &tpp.verifier_key(),
+            statement,
+            proof,
+            &mut transcript,
+        )
+        .unwrap();
+        let (beta, delta) = (challenges.beta, challenges.delta);
+        let witness =
+            Witness::new(pp, &tpp.adjusted_table_values, guessed_segment_indices).unwrap();
+
+        let table_element_indices = witness
+            .segment_indices
+            .iter()
+            .flat_map(|&i| i * pp.segment_size..(i + 1) * pp.segment_size)
+            .collect::<Vec<_>>();
+        let g1_lw = |i: usize| pp.g1_affine_list_lw.get(i).unwrap();
+        let fr_a = |i: usize| {
+            (beta + tpp.adjusted_table_values[i] + delta * pp.domain_w.element(i))
+                .inverse()
+                .unwrap()
+        };
+        let g1_m: G1 = table_element_indices
+            .iter()
+            .map(|&i| g1_lw(i).into_group())
+            .sum();
+        let g1_a: G1 = table_element_indices
+            .iter()
+            .map(|&i| g1_lw(i) * fr_a(i))
+            .sum();
+        let fr_a_at_zero = table_element_indices.iter().map(|&i| fr_a(i)).sum::<Fr>()
+            / Fr::from(pp.table_element_size as u64);
+        let poly_eval_list_b = table_element_indices
+            .iter()
+            .zip(&witness.evaluations)
+            .map(|(&i, &f)| {
+                (beta + f + delta * pp.domain_w.element(i))
+                    .inverse()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let poly_b0 =
+            DensePolynomial::from_coefficients_slice(&pp.domain_v.ifft(&poly_eval_list_b)[1..]);
+        let poly_d = DensePolynomial::from_coefficients_vec(
+            pp.domain_k.ifft(
+                &witness
+                    .segment_indices
+                    .iter()
+                    .map(|&i| pp.domain_w.element(i * pp.segment_size))
+                    .collect::<Vec<_>>(),
+            ),
+        );
+
+        vec![
+            witness.generate_statement(g1_affine_srs) == statement,
+            g1_m.into_affine() == proof.g1_affine_m,
+            g1_a.into_affine() == proof.g1_affine_a,
+            fr_a_at_zero == proof.fr_a_at_zero,
+            Kzg::<G1>::commit(g1_affine_srs, &poly_b0).into_affine() == proof.g1_affine_rb,
+            witness.poly.evaluate(&challenges.gamma) == proof.fr_f_at_gamma,
+            poly_d.evaluate(&challenges.gamma) == proof.fr_d_at_gamma,
+            poly_d.evaluate(&challenges.caulk_alpha) == proof.multi_unity_proof.fr_v1,
+        ]
+    }
+
+    #[test]
+    fn test_guessed_witness_does_not_match_proof() {
+        let mut rng = test_rng();
+        let queried_segment_indices = [3, 1, 3, 6];
+        let wrong_segment_indices = [3, 1, 3, 5];
+        let build = |zero_knowledge: bool, rng: &mut _| {
+            PublicParameters::<ark_bn254::Bn254>::builder()
+                .num_table_segments(8)
+                .num_witness_segments(4)
+                .segment_size(4)
+                .zero_knowledge(zero_knowledge)
+                .pedersen_statements(zero_knowledge)
+                .build(rng)
+                .expect("Failed to setup public parameters")
+        };
+
+        // Without zero knowledge, the right guess matches in every way, and
+        // the wrong one in none.
+        let pp = build(false, &mut rng);
+        let tpp = Table::new(&pp, rand_segments::generate(&pp))
+            .unwrap()
+            .preprocess(&pp)
+            .unwrap();
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        let matches = match_guessed_witness(&pp, &tpp, statement, &proof, &queried_segment_indices);
+        assert!(matches.iter().all(|&m| m), "{matches:?}");
+        let matches = match_guessed_witness(&pp, &tpp, statement, &proof, &wrong_segment_indices);
+        assert!(matches.iter().all(|&m| !m), "{matches:?}");
+
+        // With zero knowledge and a blinded or a Pedersen statement, neither
+        // guess matches in any way.
+        let pp = build(true, &mut rng);
+        let tpp = Table::new(&pp, rand_segments::generate(&pp))
+            .unwrap()
+            .preprocess(&pp)
+            .unwrap();
+        let witness = Witness::new_blinded(
+            &pp,
+            &tpp.adjusted_table_values,
+            &queried_segment_indices,
+            &mut rng,
+        )
+        .unwrap();
+        let g1_affine_srs = pp.g1_affine_srs.as_slice().unwrap();
+        let statement = witness.generate_statement(g1_affine_srs);
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        let blinder = PedersenBlinder::rand(&mut rng);
+        let pedersen_statement = witness
+            .generate_pedersen_statement(g1_affine_srs, &pp.g1_affine_srs_gamma, &blinder)
+            .unwrap();
+        let pedersen_proof = prove_with_transcript(
+            &pp,
+            &tpp,
+            &witness,
+            pedersen_statement,
+            &mut Transcript::new(),
+            &ProofOptions::default().pedersen_blinder(&blinder),
+            &mut rng,
+        )
+        .unwrap();
+        for (statement, proof) in [(statement, &proof), (pedersen_statement, &pedersen_proof)] {
+            for guessed_segment_indices in [queried_segment_indices, wrong_segment_indices] {
+                let matches =
+                    match_guessed_witness(&pp, &tpp, statement, proof, &guessed_segment_indices);
+                assert!(matches.iter().all(|&m| !m), "{matches:?}");
+            }
+        }
+    }
 }
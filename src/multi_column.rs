@@ -0,0 +1,723 @@
+use crate::error::Error;
+use crate::public_parameters::PublicParameters;
+use crate::table::{Table, TablePreprocessedParameters, TableVerifierKey, UniquenessEncoding};
+use crate::transcript::{Label, TranscriptProtocol};
+use crate::witness::{Statement, Witness};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_poly::univariate::DensePolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{One, Zero};
+use blake2::{Blake2b512, Digest};
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::ops::Mul;
+
+/// A table whose segment rows are tuples of values, one per column,
+/// stored as one single-column table per column.
+pub struct MultiColumnTable<P: Pairing> {
+    pub columns: Vec<Table<P>>,
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiColumnTablePreprocessedParameters<P: Pairing> {
+    // The make-unique adjustment is only applied to the first column, so that
+    // it is not scaled by the column challenge.
+    pub column_tpps: Vec<TablePreprocessedParameters<P>>,
+
+    pub(crate) hash_representation: Vec<u8>,
+}
+
+/// The subset of the multi-column table preprocessed parameters needed by
+/// the verifier.
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MultiColumnTableVerifierKey<P: Pairing> {
+    pub column_tvks: Vec<TableVerifierKey<P>>,
+
+    pub(crate) hash_representation: Vec<u8>,
+}
+
+/// A witness into a multi-column table, with one single-column witness per
+/// column over the same queried segments.
+pub struct MultiColumnWitness<P: Pairing> {
+    pub columns: Vec<Witness<P>>,
+}
+
+impl<P: Pairing> MultiColumnTable<P> {
+    /// Creates a table from n segments of s rows each, where each row is a
+    /// tuple of one value per column.
+    pub fn new(
+        pp: &PublicParameters<P>,
+        segment_rows: Vec<Vec<Vec<P::ScalarField>>>,
+    ) -> Result<Self, Error> {
+        let num_columns = segment_rows
+            .first()
+            .and_then(|rows| rows.first())
+            .map_or(0, |row| row.len());
+        if num_columns == 0 {
+            return Err(Error::InvalidNumberOfColumns(num_columns));
+        }
+        if let Some(row) = segment_rows
+            .iter()
+            .flatten()
+            .find(|row| row.len() != num_columns)
+        {
+            return Err(Error::InvalidNumberOfColumns(row.len()));
+        }
+
+        let columns = (0..num_columns)
+            .map(|c| {
+                let segment_values = segment_rows
+                    .iter()
+                    .map(|rows| rows.iter().map(|row| row[c]).collect())
+                    .collect();
+
+                Table::new(pp, segment_values)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self { columns })
+    }
+
+    /// Preprocesses the table with the default [`UniquenessEncoding`].
+    pub fn preprocess(
+        &self,
+        pp: &PublicParameters<P>,
+    ) -> Result<MultiColumnTablePreprocessedParameters<P>, Error> {
+        self.preprocess_with_encoding(pp, UniquenessEncoding::default())
+    }
+
+    /// Preprocesses each column. The encoding is applied to the first column.
+    pub fn preprocess_with_encoding(
+        &self,
+        pp: &PublicParameters<P>,
+        encoding: UniquenessEncoding,
+    ) -> Result<MultiColumnTablePreprocessedParameters<P>, Error> {
+        let column_tpps = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(c, column)| {
+                let column_encoding = if c == 0 {
+                    encoding
+                } else {
+                    UniquenessEncoding::None
+                };
+
+                column.preprocess_with_encoding(pp, column_encoding)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut hasher = Blake2b512::new();
+        hasher.update(column_tpps.len().to_le_bytes());
+        for column_tpp in &column_tpps {
+            hasher.update(&column_tpp.hash_representation);
+        }
+        let hash_representation = hasher.finalize().to_vec();
+
+        Ok(MultiColumnTablePreprocessedParameters {
+            column_tpps,
+            hash_representation,
+        })
+    }
+}
+
+impl<P: Pairing> MultiColumnTablePreprocessedParameters<P> {
+    pub fn num_columns(&self) -> usize {
+        self.column_tpps.len()
+    }
+
+    /// Extracts the verifier key of the table.
+    pub fn verifier_key(&self) -> MultiColumnTableVerifierKey<P> {
+        MultiColumnTableVerifierKey {
+            column_tvks: self
+                .column_tpps
+                .iter()
+                .map(|column_tpp| column_tpp.verifier_key())
+                .collect(),
+            hash_representation: self.hash_representation.clone(),
+        }
+    }
+
+    // The compressed table T(X) = sum_c zeta^c * T_c(X), with the quotient
+    // commitments q_{i, 1} compressed only for the elements of the queried
+    // segments, which are the only ones read by the prover.
+    pub(crate) fn compress_for_prover(
+        &self,
+        pp: &PublicParameters<P>,
+        fr_zeta_powers: &[P::ScalarField],
+        segment_indices: &[usize],
+    ) -> Result<TablePreprocessedParameters<P>, Error> {
+        let segment_size = pp.segment_size;
+        let table_element_size = pp.table_element_size;
+
+        let adjusted_table_values = (0..table_element_size)
+            .into_par_iter()
+            .map(|i| {
+                self.column_tpps
+                    .iter()
+                    .zip(fr_zeta_powers)
+                    .map(|(column_tpp, &fr_zeta_pow)| {
+                        column_tpp.adjusted_table_values[i] * fr_zeta_pow
+                    })
+                    .sum()
+            })
+            .collect::<Vec<_>>();
+
+        let element_indices: Vec<usize> = segment_indices
+            .iter()
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .flat_map(|segment_index| {
+                segment_index * segment_size..(segment_index + 1) * segment_size
+            })
+            .collect();
+        if let Some(&index) = element_indices.iter().find(|&&i| i >= table_element_size) {
+            return Err(Error::InvalidSegmentElementIndex(index));
+        }
+        let g1_list_q1: Vec<P::G1> = element_indices
+            .par_iter()
+            .map(|&i| {
+                self.column_tpps
+                    .iter()
+                    .zip(fr_zeta_powers)
+                    .map(|(column_tpp, &fr_zeta_pow)| {
+                        column_tpp.g1_affine_list_q1[i].mul(fr_zeta_pow)
+                    })
+                    .sum()
+            })
+            .collect();
+        let mut g1_affine_list_q1 = vec![P::G1Affine::zero(); table_element_size];
+        for (i, g1_affine_q1) in element_indices
+            .into_iter()
+            .zip(P::G1::normalize_batch(&g1_list_q1))
+        {
+            g1_affine_list_q1[i] = g1_affine_q1;
+        }
+
+        let g2_affine_adjusted_t = compress_g2_commitments::<P>(
+            self.column_tpps
+                .iter()
+                .map(|column_tpp| column_tpp.g2_affine_adjusted_t),
+            fr_zeta_powers,
+        );
+
+        let g1_affine_adjusted_t = self
+            .column_tpps
+            .iter()
+            .zip(fr_zeta_powers)
+            .map(|(column_tpp, &fr_zeta_pow)| column_tpp.g1_affine_adjusted_t.mul(fr_zeta_pow))
+            .sum::<P::G1>()
+            .into_affine();
+
+        let first_column_tpp = &self.column_tpps[0];
+        Ok(TablePreprocessedParameters {
+            g1_affine_list_q1,
+            g1_affine_d: first_column_tpp.g1_affine_d,
+            g1_affine_adjusted_t,
+            // The raw table commitment is not used by the prover.
+            g2_affine_t: P::G2Affine::zero(),
+            g2_affine_adjusted_t,
+            adjusted_table_values,
+            uniqueness_encoding: first_column_tpp.uniqueness_encoding,
+            fr_position_multiplier: first_column_tpp.fr_position_multiplier,
+            hash_representation: self.hash_representation.clone(),
+        })
+    }
+}
+
+impl<P: Pairing> MultiColumnTableVerifierKey<P> {
+    pub fn num_columns(&self) -> usize {
+        self.column_tvks.len()
+    }
+
+    // The commitment to the compressed table, which is all the verifier needs.
+    pub(crate) fn compress(&self, fr_zeta_powers: &[P::ScalarField]) -> TableVerifierKey<P> {
+        TableVerifierKey {
+            g2_affine_adjusted_t: compress_g2_commitments::<P>(
+                self.column_tvks
+                    .iter()
+                    .map(|column_tvk| column_tvk.g2_affine_adjusted_t),
+                fr_zeta_powers,
+            ),
+            hash_representation: self.hash_representation.clone(),
+        }
+    }
+}
+
+// [T(tau)]_2 = sum_c zeta^c * [T_c(tau)]_2.
+fn compress_g2_commitments<P: Pairing>(
+    g2_affine_list_t: impl Iterator<Item = P::G2Affine>,
+    fr_zeta_powers: &[P::ScalarField],
+) -> P::G2Affine {
+    g2_affine_list_t
+        .zip(fr_zeta_powers)
+        .map(|(g2_affine_t, &fr_zeta_pow)| g2_affine_t.mul(fr_zeta_pow))
+        .sum::<P::G2>()
+        .into_affine()
+}
+
+/// Several tables sharing one set of public parameters, combined into one
+/// two-column table. The first column is the tag of the table that each
+/// segment comes from, and the second column holds the table values.
+/// Unused logical segments are copies of the first segment of the first table.
+pub struct TaggedTables<P: Pairing> {
+    pub table: MultiColumnTable<P>,
+    // Index of the first combined segment of each table, followed by the
+    // number of segments of all tables.
+    pub(crate) segment_offsets: Vec<usize>,
+}
+
+impl<P: Pairing> TaggedTables<P> {
+    /// Combines the tables, each given as a list of segments of s values.
+    pub fn new(
+        pp: &PublicParameters<P>,
+        tables: Vec<Vec<Vec<P::ScalarField>>>,
+    ) -> Result<Self, Error> {
+        let mut segment_offsets = Vec::with_capacity(tables.len() + 1);
+        segment_offsets.push(0);
+        for table in &tables {
+            segment_offsets.push(segment_offsets[segment_offsets.len() - 1] + table.len());
+        }
+        let num_segments = segment_offsets[tables.len()];
+        if num_segments == 0 || num_segments > pp.logical_num_table_segments {
+            return Err(Error::InvalidNumberOfSegments(num_segments));
+        }
+
+        let mut segment_rows: Vec<Vec<Vec<P::ScalarField>>> = tables
+            .into_iter()
+            .enumerate()
+            .flat_map(|(tag, segments)| {
+                let fr_tag = P::ScalarField::from(tag as u64);
+                segments.into_iter().map(move |segment| {
+                    segment
+                        .into_iter()
+                        .map(|value| vec![fr_tag, value])
+                        .collect()
+                })
+            })
+            .collect();
+        segment_rows.resize(pp.logical_num_table_segments, segment_rows[0].clone());
+
+        Ok(Self {
+            table: MultiColumnTable::new(pp, segment_rows)?,
+            segment_offsets,
+        })
+    }
+
+    pub fn num_tables(&self) -> usize {
+        self.segment_offsets.len() - 1
+    }
+
+    /// Returns the index in the combined table of a segment of a table.
+    pub fn segment_index(&self, table_tag: usize, segment_index: usize) -> Result<usize, Error> {
+        if table_tag >= self.num_tables() {
+            return Err(Error::InvalidTableTag(table_tag));
+        }
+        self.segment_offsets[table_tag]
+            .checked_add(segment_index)
+            .filter(|&index| index < self.segment_offsets[table_tag + 1])
+            .ok_or(Error::InvalidSegmentIndex(segment_index))
+    }
+
+    pub fn preprocess(
+        &self,
+        pp: &PublicParameters<P>,
+    ) -> Result<MultiColumnTablePreprocessedParameters<P>, Error> {
+        self.table.preprocess(pp)
+    }
+}
+
+impl<P: Pairing> MultiColumnWitness<P> {
+    /// Creates a witness into tagged tables from up to k queries, each a pair
+    /// of the table tag and the segment index within that table.
+    /// The statement of the first column commits to the tags.
+    pub fn new_tagged(
+        pp: &PublicParameters<P>,
+        tables: &TaggedTables<P>,
+        tpp: &MultiColumnTablePreprocessedParameters<P>,
+        queries: &[(usize, usize)],
+    ) -> Result<Self, Error> {
+        let queried_segment_indices = queries
+            .iter()
+            .map(|&(table_tag, segment_index)| tables.segment_index(table_tag, segment_index))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Self::new(pp, tpp, &queried_segment_indices)
+    }
+
+    /// Creates a witness from up to k queried segment indices, reading the
+    /// adjusted values of each column from the preprocessed table.
+    pub fn new(
+        pp: &PublicParameters<P>,
+        tpp: &MultiColumnTablePreprocessedParameters<P>,
+        queried_segment_indices: &[usize],
+    ) -> Result<Self, Error> {
+        let columns = tpp
+            .column_tpps
+            .iter()
+            .map(|column_tpp| {
+                Witness::new(
+                    pp,
+                    &column_tpp.adjusted_table_values,
+                    queried_segment_indices,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self { columns })
+    }
+
+    /// Returns one statement per column.
+    pub fn generate_statements(&self, g1_srs: &[P::G1Affine]) -> Vec<Statement<P>> {
+        self.columns
+            .iter()
+            .map(|column| column.generate_statement(g1_srs))
+            .collect()
+    }
+
+    pub(crate) fn segment_indices(&self) -> Vec<usize> {
+        self.columns
+            .first()
+            .map_or_else(Vec::new, |column| column.segment_indices.clone())
+    }
+
+    // The compressed witness F(X) = sum_c zeta^c * F_c(X).
+    pub(crate) fn compress(&self, fr_zeta_powers: &[P::ScalarField]) -> Witness<P> {
+        let first_column = &self.columns[0];
+        let mut evaluations = vec![P::ScalarField::zero(); first_column.evaluations.len()];
+        let mut poly = DensePolynomial::zero();
+        let mut poly_blinder = DensePolynomial::zero();
+        for (column, &fr_zeta_pow) in self.columns.iter().zip(fr_zeta_powers) {
+            evaluations
+                .par_iter_mut()
+                .zip(column.evaluations.par_iter())
+                .for_each(|(e, &f_i)| *e += f_i * fr_zeta_pow);
+            poly += (fr_zeta_pow, &column.poly);
+            poly_blinder += (fr_zeta_pow, &column.poly_blinder);
+        }
+
+        Witness {
+            num_segments: first_column.num_segments,
+            segment_size: first_column.segment_size,
+            num_queries: first_column.num_queries,
+            segment_indices: first_column.segment_indices.clone(),
+            poly,
+            evaluations,
+            poly_blinder,
+        }
+    }
+}
+
+// Absorbs the table and the column statements into the transcript, and
+// returns the powers [1, zeta, ..., zeta^{c - 1}] of the column challenge.
+pub(crate) fn column_challenge_powers<P: Pairing, T: TranscriptProtocol<P::ScalarField>>(
+    transcript: &mut T,
+    pp_hash_representation: &[u8],
+    tpp_hash_representation: &[u8],
+    num_columns: usize,
+    statements: &[Statement<P>],
+) -> Result<Vec<P::ScalarField>, Error> {
+    if statements.len() != num_columns {
+        return Err(Error::InvalidNumberOfColumns(statements.len()));
+    }
+
+    transcript.append_bytes(Label::PublicParameters, pp_hash_representation)?;
+    transcript.append_bytes(Label::TablePreprocessedParameters, tpp_hash_representation)?;
+    transcript.append_scalar(
+        Label::ColumnStatements,
+        &P::ScalarField::from(statements.len() as u64),
+    )?;
+    let labels_and_statements: Vec<(Label, P::G1Affine)> = statements
+        .iter()
+        .map(|statement| (Label::ColumnStatements, statement.g1_affine_f))
+        .collect();
+    transcript.append_points(&labels_and_statements)?;
+    let fr_zeta = transcript.squeeze_challenge(Label::ChallengeColumn)?;
+
+    let mut fr_zeta_powers = Vec::with_capacity(statements.len());
+    let mut fr_zeta_pow = P::ScalarField::one();
+    for _ in 0..statements.len() {
+        fr_zeta_powers.push(fr_zeta_pow);
+        fr_zeta_pow *= fr_zeta;
+    }
+
+    Ok(fr_zeta_powers)
+}
+
+// [F(tau)]_1 = sum_c zeta^c * [F_c(tau)]_1, with the number of queries of
+// the columns, which must all be the same.
+pub(crate) fn compress_statements<P: Pairing>(
+    statements: &[Statement<P>],
+    fr_zeta_powers: &[P::ScalarField],
+) -> Result<Statement<P>, Error> {
+    let num_queries = statements
+        .first()
+        .map_or(0, |statement| statement.num_queries);
+    if let Some(statement) = statements
+        .iter()
+        .find(|statement| statement.num_queries != num_queries)
+    {
+        return Err(Error::InvalidNumberOfQueries(statement.num_queries));
+    }
+
+    let g1_affine_f = statements
+        .iter()
+        .zip(fr_zeta_powers)
+        .map(|(statement, &fr_zeta_pow)| statement.g1_affine_f.mul(fr_zeta_pow))
+        .sum::<P::G1>()
+        .into_affine();
+
+    Ok(Statement {
+        g1_affine_f,
+        num_queries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::rand::RngCore;
+    use ark_std::{test_rng, UniformRand};
+
+    use crate::prover::{prove_multi_column, prove_multi_column_with_transcript, ProofOptions};
+    use crate::transcript::PoseidonTranscript;
+    use crate::verifier::{
+        verify_multi_column, verify_multi_column_with_transcript, VerifyOptions,
+    };
+    use crate::witness::PedersenBlinder;
+
+    use super::*;
+
+    type Fr = <Bn254 as Pairing>::ScalarField;
+
+    fn generate_segment_rows(
+        pp: &PublicParameters<Bn254>,
+        num_columns: usize,
+    ) -> Vec<Vec<Vec<Fr>>> {
+        let mut rng = test_rng();
+        (0..pp.logical_num_table_segments)
+            .map(|_| {
+                (0..pp.logical_segment_size)
+                    .map(|_| (0..num_columns).map(|_| Fr::from(rng.next_u64())).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_column_prove_and_verify() {
+        let inputs = [(8, 4, 4, 1), (8, 4, 4, 3), (5, 3, 3, 2)];
+        for (num_table_segments, num_witness_segments, segment_size, num_columns) in inputs {
+            let mut rng = test_rng();
+            let pp = PublicParameters::<Bn254>::builder()
+                .num_table_segments(num_table_segments)
+                .num_witness_segments(num_witness_segments)
+                .segment_size(segment_size)
+                .build(&mut rng)
+                .expect("Failed to setup public parameters");
+            let segment_rows = generate_segment_rows(&pp, num_columns);
+
+            let t = MultiColumnTable::new(&pp, segment_rows).expect("Failed to create table");
+            let tpp = t.preprocess(&pp).unwrap();
+            assert_eq!(tpp.num_columns(), num_columns);
+
+            let queried_segment_indices: Vec<usize> = (0..num_witness_segments)
+                .map(|_| rng.next_u32() as usize % num_table_segments)
+                .collect();
+            let witness = MultiColumnWitness::new(&pp, &tpp, &queried_segment_indices).unwrap();
+            let statements = witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+
+            let proof = prove_multi_column(&pp, &tpp, &witness, &statements, &mut rng).unwrap();
+
+            let vk = pp.verifier_key().unwrap();
+            assert!(
+                verify_multi_column(&vk, &tpp.verifier_key(), &statements, &proof, &mut rng)
+                    .is_ok()
+            );
+            assert!(matches!(
+                verify_multi_column(&vk, &tpp.verifier_key(), &statements[1..], &proof, &mut rng),
+                Err(Error::InvalidNumberOfColumns(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_multi_column_prove_and_verify_with_poseidon_transcript() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let t = MultiColumnTable::new(&pp, generate_segment_rows(&pp, 2))
+            .expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let tvk = tpp.verifier_key();
+        let vk = pp.verifier_key().unwrap();
+
+        let witness = MultiColumnWitness::new(&pp, &tpp, &[0, 2, 2, 5]).unwrap();
+        let statements = witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove_multi_column_with_transcript(
+            &pp,
+            &tpp,
+            &witness,
+            &statements,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &ProofOptions::default().label(b"lookup"),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(verify_multi_column_with_transcript(
+            &vk,
+            &tvk,
+            &statements,
+            &proof,
+            &mut PoseidonTranscript::<Fr>::new(),
+            &VerifyOptions::default().label(b"lookup"),
+            &mut rng
+        )
+        .is_ok());
+
+        // The challenges depend on the transcript.
+        assert!(verify_multi_column(&vk, &tvk, &statements, &proof, &mut rng).is_err());
+
+        // The compressed statement cannot be a Pedersen statement.
+        let blinder = PedersenBlinder::rand(&mut rng);
+        assert!(matches!(
+            prove_multi_column_with_transcript(
+                &pp,
+                &tpp,
+                &witness,
+                &statements,
+                &mut PoseidonTranscript::<Fr>::new(),
+                &ProofOptions::default().pedersen_blinder(&blinder),
+                &mut rng,
+            ),
+            Err(Error::UnsupportedPedersenStatement)
+        ));
+    }
+
+    #[test]
+    fn test_tagged_tables_prove_and_verify() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(16)
+            .num_witness_segments(4)
+            .segment_size(2)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        // A range table, a byte XOR table, and a ROM, with 2-element segments.
+        let range_table: Vec<Vec<Fr>> = (0..4)
+            .map(|i| vec![Fr::from(2 * i as u64), Fr::from(2 * i as u64 + 1)])
+            .collect();
+        let xor_table: Vec<Vec<Fr>> = (0..6)
+            .map(|i| vec![Fr::from(i as u64), Fr::from((i ^ 5) as u64)])
+            .collect();
+        let rom_table: Vec<Vec<Fr>> = (0..3)
+            .map(|_| vec![Fr::rand(&mut rng), Fr::rand(&mut rng)])
+            .collect();
+
+        let tables = TaggedTables::new(&pp, vec![range_table, xor_table, rom_table]).unwrap();
+        assert_eq!(tables.num_tables(), 3);
+        let tpp = tables.preprocess(&pp).unwrap();
+
+        let queries = [(0, 3), (1, 5), (2, 0), (1, 0)];
+        let witness = MultiColumnWitness::new_tagged(&pp, &tables, &tpp, &queries).unwrap();
+        let statements = witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove_multi_column(&pp, &tpp, &witness, &statements, &mut rng).unwrap();
+        let vk = pp.verifier_key().unwrap();
+        assert!(
+            verify_multi_column(&vk, &tpp.verifier_key(), &statements, &proof, &mut rng).is_ok()
+        );
+
+        // The value column alone is a valid lookup, but not with the claimed
+        // table tags.
+        let mut tag_witness_segments = witness.columns[0].segment_indices.clone();
+        tag_witness_segments[1] = tables.segment_index(0, 0).unwrap();
+        let forged_witness = MultiColumnWitness {
+            columns: vec![
+                Witness::new(
+                    &pp,
+                    &tpp.column_tpps[0].adjusted_table_values,
+                    &tag_witness_segments,
+                )
+                .unwrap(),
+                Witness::new(
+                    &pp,
+                    &tpp.column_tpps[1].adjusted_table_values,
+                    &witness.columns[1].segment_indices,
+                )
+                .unwrap(),
+            ],
+        };
+        let statements = forged_witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove_multi_column(&pp, &tpp, &forged_witness, &statements, &mut rng).unwrap();
+        assert!(matches!(
+            verify_multi_column(&vk, &tpp.verifier_key(), &statements, &proof, &mut rng),
+            Err(Error::BatchedPairingFailed)
+        ));
+
+        assert!(matches!(
+            MultiColumnWitness::new_tagged(&pp, &tables, &tpp, &[(3, 0)]),
+            Err(Error::InvalidTableTag(3))
+        ));
+        assert!(matches!(
+            MultiColumnWitness::new_tagged(&pp, &tables, &tpp, &[(2, 3)]),
+            Err(Error::InvalidSegmentIndex(3))
+        ));
+        assert!(matches!(
+            tables.segment_index(1, usize::MAX),
+            Err(Error::InvalidSegmentIndex(usize::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_multi_column_mixed_rows_rejected() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(2)
+            .segment_size(2)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segment_rows = generate_segment_rows(&pp, 2);
+
+        let t = MultiColumnTable::new(&pp, segment_rows).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let vk = pp.verifier_key().unwrap();
+
+        // Each column on its own is a valid lookup, but the second column
+        // reads from different segments than the first one.
+        let witness = MultiColumnWitness {
+            columns: vec![
+                Witness::new(&pp, &tpp.column_tpps[0].adjusted_table_values, &[1, 2]).unwrap(),
+                Witness::new(&pp, &tpp.column_tpps[1].adjusted_table_values, &[3, 4]).unwrap(),
+            ],
+        };
+        let statements = witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+
+        // The prover follows the segments of the first column, and the
+        // compressed witness is not in the compressed table.
+        let proof = prove_multi_column(&pp, &tpp, &witness, &statements, &mut rng).unwrap();
+        assert!(matches!(
+            verify_multi_column(&vk, &tpp.verifier_key(), &statements, &proof, &mut rng),
+            Err(Error::BatchedPairingFailed)
+        ));
+
+        // The same columns read from the same segments are accepted.
+        let witness = MultiColumnWitness {
+            columns: vec![
+                Witness::new(&pp, &tpp.column_tpps[0].adjusted_table_values, &[1, 2]).unwrap(),
+                Witness::new(&pp, &tpp.column_tpps[1].adjusted_table_values, &[1, 2]).unwrap(),
+            ],
+        };
+        let statements = witness.generate_statements(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove_multi_column(&pp, &tpp, &witness, &statements, &mut rng).unwrap();
+        assert!(
+            verify_multi_column(&vk, &tpp.verifier_key(), &statements, &proof, &mut rng).is_ok()
+        );
+    }
+}
@@ -1,47 +1,98 @@
 use std::iter;
-use std::ops::{Mul, MulAssign};
+use std::ops::Mul;
 
 use crate::domain::divide_by_vanishing_poly_checked;
 use crate::error::Error;
 use crate::kzg::CaulkKzg;
-use crate::public_parameters::PublicParameters;
-use crate::transcript::{Label, Transcript};
+use crate::public_parameters::{PublicParameters, VerifierKey};
+#[cfg(test)]
+use crate::transcript::{Label, TranscriptProtocol};
 use ark_ec::pairing::Pairing;
 use ark_ec::{CurveGroup, VariableBaseMSM};
 use ark_ff::Field;
 use ark_poly::univariate::DensePolynomial;
-use ark_poly::{
-    DenseUVPolynomial, EvaluationDomain, Evaluations, Polynomial, Radix2EvaluationDomain,
-};
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Evaluations, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::Rng;
 use ark_std::{One, UniformRand, Zero};
 use rayon::prelude::*;
 
 /// Modified from https://github.com/caulk-crypto/caulk/blob/main/src/multi/unity.rs
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub(crate) struct MultiUnityProof<P: Pairing> {
     pub(crate) g1_u_bar: P::G1Affine,
     pub(crate) g1_h_1: P::G1Affine,
     pub(crate) g1_h_2: P::G1Affine,
-    g1_u_bar_alpha: P::G1Affine,
-    g1_h_2_alpha: P::G1Affine,
-    fr_v1: P::ScalarField,
-    fr_v2: P::ScalarField,
-    fr_v3: P::ScalarField,
-    g1_pi1: P::G1Affine,
-    g1_pi2: P::G1Affine,
-    g1_pi3: P::G1Affine,
-    g1_pi4: P::G1Affine,
-    g1_pi5: P::G1Affine,
+    pub(crate) g1_u_bar_alpha: P::G1Affine,
+    pub(crate) g1_h_2_alpha: P::G1Affine,
+    pub(crate) fr_v1: P::ScalarField,
+    pub(crate) fr_v2: P::ScalarField,
+    pub(crate) fr_v3: P::ScalarField,
+    pub(crate) g1_pi1: P::G1Affine,
+    pub(crate) g1_pi2: P::G1Affine,
+    pub(crate) g1_pi3: P::G1Affine,
+    pub(crate) g1_pi4: P::G1Affine,
+    pub(crate) g1_pi5: P::G1Affine,
 }
-pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
+
+// Standalone prover of the sub-protocol on a transcript. The lookup prover
+// runs its rounds one at a time instead.
+#[cfg(test)]
+pub(crate) fn multi_unity_prove<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
     pp: &PublicParameters<P>,
-    transcript: &mut Transcript<P::ScalarField>,
+    transcript: &mut T,
     poly_eval_list_d: &[P::ScalarField],
     poly_d: &DensePolynomial<P::ScalarField>,
     g1_d: &P::G1Affine,
     rng: &mut R,
 ) -> Result<MultiUnityProof<P>, Error> {
+    let round1 = multi_unity_round1(pp, poly_eval_list_d, poly_d, rng)?;
+
+    transcript.append_points(&[
+        (Label::CaulkG1D, *g1_d),
+        (Label::CaulkG1UBar, round1.g1_u_bar),
+        (Label::CaulkG1H2, round1.g1_h_2),
+    ])?;
+    let alpha = transcript.squeeze_challenge(Label::ChallengeCaulkAlpha)?;
+
+    let round2 = multi_unity_round2(pp, round1, alpha)?;
+
+    transcript.append_point(Label::CaulkG1H1, &round2.g1_h_1)?;
+    let beta = transcript.squeeze_challenge(Label::ChallengeCaulkBeta)?;
+
+    multi_unity_round3(pp, round2, beta)
+}
+
+// The prover's polynomials after its first message, [U_bar]_1 and [H_2]_1.
+pub(crate) struct MultiUnityRound1<P: Pairing> {
+    // D(X), U_1(X), ..., U_{log(n)-1}(X), and the identity polynomial.
+    poly_u_list: Vec<DensePolynomial<P::ScalarField>>,
+    poly_h_s_list: Vec<DensePolynomial<P::ScalarField>>,
+    partial_y_poly_list_u_bar: Vec<DensePolynomial<P::ScalarField>>,
+    partial_y_poly_list_h_2: Vec<DensePolynomial<P::ScalarField>>,
+    pub(crate) g1_u_bar: P::G1Affine,
+    pub(crate) g1_h_2: P::G1Affine,
+}
+
+// The prover's polynomials after its second message, [H_1]_1.
+pub(crate) struct MultiUnityRound2<P: Pairing> {
+    round1: MultiUnityRound1<P>,
+    alpha: P::ScalarField,
+    poly_u_alpha: DensePolynomial<P::ScalarField>,
+    poly_h_1: DensePolynomial<P::ScalarField>,
+    pub(crate) g1_h_1: P::G1Affine,
+}
+
+pub(crate) fn multi_unity_round1<P: Pairing, R: Rng + ?Sized>(
+    pp: &PublicParameters<P>,
+    poly_eval_list_d: &[P::ScalarField],
+    poly_d: &DensePolynomial<P::ScalarField>,
+    rng: &mut R,
+) -> Result<MultiUnityRound1<P>, Error> {
     // Round 1: The prover takes the input srs and U_0(X) amd samples log(n)
     // randomnesses to compute U_l(X) for l = 1, ..., log(n), U(X, Y), U_bar(X,
     // Y), and Q_2(X, Y). And send [U_bar(\tau^{log(n)}, \tau)]_1,
@@ -60,9 +111,12 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
 
     // Compute U_l(X) for l = 1, ..., log(n)-1
     // u_poly_list contains U_1(X), U_2(X), ..., U_{log(n)-1}(X)
+    // In zero-knowledge mode, U_l(X) is revealed at tau^d in [U_bar]_1 and at
+    // alpha, so its blinder Z_K(X) * r_l(X) has degree one in r_l(X).
     // Time complexity: (k * log(k) * log(n))
     let vanishing_poly_k: DensePolynomial<P::ScalarField> =
         pp.domain_k.vanishing_polynomial().into();
+    let blinder_degree = usize::from(pp.zero_knowledge);
     for _ in 1..log_num_table_segments {
         // Parallel in-place squaring of the evaluations of D(X)
         poly_eval_list_d.iter_mut().for_each(|eval| {
@@ -71,7 +125,7 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
 
         let poly_u = Evaluations::from_vec_and_domain(poly_eval_list_d.clone(), pp.domain_k)
             .interpolate()
-            + blinded_vanishing_poly::<P, _>(&vanishing_poly_k, rng);
+            + blinded_vanishing_poly::<P, _>(&vanishing_poly_k, blinder_degree, rng);
 
         poly_u_list.push(poly_u);
     }
@@ -80,13 +134,17 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
     // Store each term U_l(X) * \rho_l(Y) in a vector
     // Time complexity: (k * log(n) * log(log(n)))
     let partial_y_poly_list_u_bar: Vec<DensePolynomial<P::ScalarField>> = {
-        let num_coefficients = poly_u_list[0].len();
+        let num_coefficients = max_num_coefficients(&poly_u_list);
 
         (0..num_coefficients)
             .into_par_iter()
             .map(|coeff_index| {
                 let coeff_list: Vec<P::ScalarField> = iter::once(P::ScalarField::zero())
-                    .chain(poly_u_list.iter().map(|poly_u| poly_u[coeff_index]))
+                    .chain(
+                        poly_u_list
+                            .iter()
+                            .map(|poly_u| coefficient(poly_u, coeff_index)),
+                    )
                     .collect();
 
                 // Time complexity: (log(n) * log(log(n)))
@@ -98,7 +156,7 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
     // Add D(X) to the front and identity polynomial to the back.
     let identity_poly = pp.identity_poly_k.clone();
     poly_u_list = iter::once(poly_d.clone())
-        .chain(poly_u_list.into_iter())
+        .chain(poly_u_list)
         .chain(iter::once(identity_poly.clone()))
         .collect();
 
@@ -113,27 +171,19 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    // H_1(X) = (D(X)^2 - U_1(X)) / Z_K(X) has the largest degree in
+    // zero-knowledge mode, where D(X) is blinded with a multiple of Z_K(X) of
+    // degree k + 3.
     // Time complexity: (k * log(n) * log(log(n)))
     let partial_y_poly_list_h_2: Vec<DensePolynomial<P::ScalarField>> = {
-        let num_coefficients_first_poly = poly_h_s_list[0].len();
-        let num_coefficients = poly_h_s_list[1].len();
+        let num_coefficients = max_num_coefficients(&poly_h_s_list);
 
         (0..num_coefficients)
             .into_par_iter()
             .map(|coeff_index| {
-                let coeff0 = if coeff_index < num_coefficients_first_poly {
-                    poly_h_s_list[0][coeff_index]
-                } else {
-                    P::ScalarField::zero()
-                };
-
-                let coeff_list: Vec<P::ScalarField> = iter::once(coeff0)
-                    .chain(
-                        poly_h_s_list
-                            .iter()
-                            .skip(1)
-                            .map(|poly_h_s| poly_h_s[coeff_index]),
-                    )
+                let coeff_list: Vec<P::ScalarField> = poly_h_s_list
+                    .iter()
+                    .map(|poly_h_s| coefficient(poly_h_s, coeff_index))
                     .collect();
 
                 // Time complexity: (log(n) * log(log(n)))
@@ -154,13 +204,24 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
         log_num_table_segments,
     )?;
 
-    transcript.append_elements(&[
-        (Label::CaulkG1D, *g1_d),
-        (Label::CaulkG1UBar, g1_u_bar),
-        (Label::CaulkG1H2, g1_h_2),
-    ])?;
+    Ok(MultiUnityRound1 {
+        poly_u_list,
+        poly_h_s_list,
+        partial_y_poly_list_u_bar,
+        partial_y_poly_list_h_2,
+        g1_u_bar,
+        g1_h_2,
+    })
+}
 
-    let alpha = transcript.squeeze_challenge(Label::ChallengeCaulkAlpha)?;
+// The verifier sends alpha, and the prover sends [H_1]_1.
+pub(crate) fn multi_unity_round2<P: Pairing>(
+    pp: &PublicParameters<P>,
+    round1: MultiUnityRound1<P>,
+    alpha: P::ScalarField,
+) -> Result<MultiUnityRound2<P>, Error> {
+    let log_num_table_segments = pp.log_num_table_segments;
+    let poly_u_list = &round1.poly_u_list;
 
     // Compute H_1(Y)
     let bi_poly_u_at_alpha_list = poly_u_list
@@ -194,8 +255,41 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
 
     let g1_h_1 = P::G1::msm_unchecked(&pp.g1_affine_srs_caulk, &poly_h_1.coeffs).into_affine();
 
-    transcript.append_element(Label::CaulkG1H1, &g1_h_1)?;
-    let beta = transcript.squeeze_challenge(Label::ChallengeCaulkBeta)?;
+    Ok(MultiUnityRound2 {
+        round1,
+        alpha,
+        poly_u_alpha,
+        poly_h_1,
+        g1_h_1,
+    })
+}
+
+// The verifier sends beta, and the prover opens the polynomials.
+pub(crate) fn multi_unity_round3<P: Pairing>(
+    pp: &PublicParameters<P>,
+    round2: MultiUnityRound2<P>,
+    beta: P::ScalarField,
+) -> Result<MultiUnityProof<P>, Error> {
+    let MultiUnityRound2 {
+        round1:
+            MultiUnityRound1 {
+                poly_u_list,
+                poly_h_s_list,
+                partial_y_poly_list_u_bar,
+                partial_y_poly_list_h_2,
+                g1_u_bar,
+                g1_h_2,
+            },
+        alpha,
+        poly_u_alpha,
+        poly_h_1,
+        g1_h_1,
+    } = round2;
+    let log_num_table_segments = pp.log_num_table_segments;
+    let domain_log_n = &pp.domain_log_n;
+    let identity_poly = &pp.identity_poly_k;
+    let vanishing_poly_k: DensePolynomial<P::ScalarField> =
+        pp.domain_k.vanishing_polynomial().into();
 
     let u_alpha_beta = poly_u_alpha.evaluate(&beta);
     let mut poly_p = DensePolynomial::from_coefficients_slice(&[u_alpha_beta.square()]);
@@ -212,7 +306,7 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
             let u_s_alpha = ploy_u.evaluate(&alpha);
             u_s_alpha * lagrange_basis_at_beta_shift[s]
         })
-        .reduce(|| P::ScalarField::zero(), |acc, x| acc + x);
+        .reduce(P::ScalarField::zero, |acc, x| acc + x);
 
     let lagrange_basis_at_beta = domain_log_n.evaluate_all_lagrange_coefficients(beta);
     let temp = u_bar_alpha_shift_beta
@@ -287,48 +381,48 @@ pub(crate) fn multi_unity_prove<P: Pairing, R: Rng + ?Sized>(
     })
 }
 
+// Returns Z(X) * r(X) for a random r(X) of the given degree.
 fn blinded_vanishing_poly<P: Pairing, R: Rng + ?Sized>(
     vanishing_poly: &DensePolynomial<P::ScalarField>,
+    degree: usize,
     rng: &mut R,
 ) -> DensePolynomial<P::ScalarField> {
-    let fr_rand = P::ScalarField::rand(rng);
-    let vanishing_poly_coefficients: Vec<P::ScalarField> = vanishing_poly.coeffs.clone();
-    let rand_poly_coefficients = vanishing_poly_coefficients
-        .par_iter()
-        .map(|&s| s * fr_rand)
-        .collect();
+    let poly_rand = DensePolynomial::from_coefficients_vec(
+        (0..=degree).map(|_| P::ScalarField::rand(rng)).collect(),
+    );
+
+    vanishing_poly * &poly_rand
+}
+
+fn max_num_coefficients<F: Field>(polys: &[DensePolynomial<F>]) -> usize {
+    polys.iter().map(|poly| poly.len()).max().unwrap_or(0)
+}
 
-    DensePolynomial::from_coefficients_vec(rand_poly_coefficients)
+// The coefficient of X^i, which is zero beyond the degree.
+fn coefficient<F: Field>(poly: &DensePolynomial<F>, i: usize) -> F {
+    poly.coeffs.get(i).copied().unwrap_or_else(F::zero)
 }
 
+// Standalone verifier of the sub-protocol. The lookup verifier folds the
+// deferred pairing equations into its own multi-pairing instead.
+#[cfg(test)]
 pub(crate) fn multi_unity_verify<P: Pairing, R: Rng + ?Sized>(
-    pp: &PublicParameters<P>,
+    vk: &VerifierKey<P>,
     alpha: P::ScalarField,
     beta: P::ScalarField,
     g1_d: &P::G1Affine,
     proof: &MultiUnityProof<P>,
     rng: &mut R,
 ) -> Result<(), Error> {
-    let mut pairing_inputs = multi_unity_verify_defer_pairing(
-        alpha,
-        beta,
-        &pp.g1_affine_srs_caulk,
-        &pp.g2_affine_srs_caulk,
-        pp.identity_poly_k.clone(),
-        &pp.domain_k,
-        &pp.domain_log_n,
-        pp.log_num_table_segments,
-        g1_d,
-        proof,
-    )?;
-
-    // Scale the pairing inputs by powers of zeta
-    let mut zeta = P::ScalarField::rand(rng);
-    for (i, (g1, _)) in pairing_inputs.iter_mut().enumerate().skip(2) {
-        g1.mul_assign(zeta);
-        if i % 2 != 0 {
-            zeta.square_in_place();
-        }
+    let pairing_equations = multi_unity_verify_defer_pairing(vk, alpha, beta, g1_d, proof)?;
+
+    // Scale the pairing equations by powers of zeta
+    let zeta = P::ScalarField::rand(rng);
+    let mut zeta_pow = P::ScalarField::one();
+    let mut pairing_inputs = Vec::new();
+    for equation in pairing_equations {
+        pairing_inputs.extend(equation.into_iter().map(|(g1, g2)| (g1 * zeta_pow, g2)));
+        zeta_pow *= zeta;
     }
 
     // Extract G1 and G2 elements for the pairing
@@ -347,18 +441,23 @@ pub(crate) fn multi_unity_verify<P: Pairing, R: Rng + ?Sized>(
     Ok(())
 }
 
-fn multi_unity_verify_defer_pairing<P: Pairing>(
+// Returns five pairing equations.
+// Each equation holds if the product of its pairings is one.
+#[allow(clippy::type_complexity)]
+pub(crate) fn multi_unity_verify_defer_pairing<P: Pairing>(
+    vk: &VerifierKey<P>,
     alpha: P::ScalarField,
     beta: P::ScalarField,
-    g1_srs: &[P::G1Affine],
-    g2_srs: &[P::G2Affine],
-    identity_poly_k: DensePolynomial<P::ScalarField>,
-    domain_k: &Radix2EvaluationDomain<P::ScalarField>,
-    domain_log_n: &Radix2EvaluationDomain<P::ScalarField>,
-    log_num_segments: usize,
     g1_d: &P::G1Affine,
     proof: &MultiUnityProof<P>,
-) -> Result<Vec<(P::G1, P::G2)>, Error> {
+) -> Result<Vec<Vec<(P::G1, P::G2)>>, Error> {
+    let g1_srs = &vk.g1_affine_srs_caulk;
+    let g2_srs = &vk.g2_affine_srs_caulk;
+    let identity_poly_k = &vk.identity_poly_k;
+    let domain_k = &vk.domain_k;
+    let domain_log_n = &vk.domain_log_n;
+    let log_num_segments = vk.log_num_table_segments;
+
     let lagrange_basis_at_beta = domain_log_n.evaluate_all_lagrange_coefficients(beta);
     let u_alpha_beta = proof.fr_v1 * lagrange_basis_at_beta[0] + proof.fr_v2;
 
@@ -426,22 +525,14 @@ fn multi_unity_verify_defer_pairing<P: Pairing>(
         &proof.g1_pi5,
     );
 
-    let res = [
-        check1.as_slice(),
-        check2.as_slice(),
-        check3.as_slice(),
-        check4.as_slice(),
-        check5.as_slice(),
-    ]
-    .concat();
-
-    Ok(res)
+    Ok(vec![check1, check2, check3, check4, check5])
 }
 
 #[cfg(test)]
 mod tests {
     use crate::domain::roots_of_unity;
     use crate::kzg::Kzg;
+    use crate::transcript::Transcript;
     use ark_bn254::Bn254;
     use ark_std::rand::RngCore;
     use ark_std::test_rng;
@@ -465,7 +556,7 @@ mod tests {
             .expect("Failed to setup public parameters");
 
         let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-            .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
             .collect();
 
         let roots_of_unity_w: Vec<<Bn254 as Pairing>::ScalarField> =
@@ -505,7 +596,7 @@ mod tests {
             .build(&mut rng)
             .expect("Failed to setup public parameters");
         let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-            .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
             .collect();
         let roots_of_unity_w: Vec<<Bn254 as Pairing>::ScalarField> =
             roots_of_unity::<Bn254>(&pp.domain_w);
@@ -533,7 +624,7 @@ mod tests {
 
         let mut transcript = Transcript::new();
         transcript
-            .append_elements(&[
+            .append_points(&[
                 (Label::CaulkG1D, g1_affine_d),
                 (Label::CaulkG1UBar, multi_unity_proof.g1_u_bar),
                 (Label::CaulkG1H2, multi_unity_proof.g1_h_2),
@@ -543,15 +634,20 @@ mod tests {
             .squeeze_challenge(Label::ChallengeCaulkAlpha)
             .unwrap();
         transcript
-            .append_element(Label::CaulkG1H1, &multi_unity_proof.g1_h_1)
+            .append_point(Label::CaulkG1H1, &multi_unity_proof.g1_h_1)
             .unwrap();
         let beta = transcript
             .squeeze_challenge(Label::ChallengeCaulkBeta)
             .unwrap();
-        assert!(
-            multi_unity_verify(&pp, alpha, beta, &g1_affine_d, &multi_unity_proof, &mut rng)
-                .is_ok()
-        );
+        assert!(multi_unity_verify(
+            &pp.verifier_key().unwrap(),
+            alpha,
+            beta,
+            &g1_affine_d,
+            &multi_unity_proof,
+            &mut rng
+        )
+        .is_ok());
 
         let mut incorrect_poly_eval_list_d = poly_eval_list_d.clone();
         incorrect_poly_eval_list_d[0] = <Bn254 as Pairing>::ScalarField::from(456);
@@ -574,7 +670,7 @@ mod tests {
 
         let mut transcript = Transcript::new();
         transcript
-            .append_elements(&[
+            .append_points(&[
                 (Label::CaulkG1D, g1_affine_d),
                 (Label::CaulkG1UBar, multi_unity_proof.g1_u_bar),
                 (Label::CaulkG1H2, multi_unity_proof.g1_h_2),
@@ -584,23 +680,23 @@ mod tests {
             .squeeze_challenge(Label::ChallengeCaulkAlpha)
             .unwrap();
         transcript
-            .append_element(Label::CaulkG1H1, &multi_unity_proof.g1_h_1)
+            .append_point(Label::CaulkG1H1, &multi_unity_proof.g1_h_1)
             .unwrap();
         let beta = transcript
             .squeeze_challenge(Label::ChallengeCaulkBeta)
             .unwrap();
-        assert!(!multi_unity_verify(
-            &pp,
+        assert!(multi_unity_verify(
+            &pp.verifier_key().unwrap(),
             alpha,
             beta,
             &incorrect_g1_d,
             &multi_unity_proof,
             &mut rng
         )
-        .is_ok());
+        .is_err());
 
         let mut transcript = Transcript::new();
-        assert!(!multi_unity_prove(
+        assert!(multi_unity_prove(
             &pp,
             &mut transcript,
             &incorrect_poly_eval_list_d,
@@ -608,6 +704,6 @@ mod tests {
             &g1_affine_d,
             &mut rng,
         )
-        .is_ok());
+        .is_err());
     }
 }
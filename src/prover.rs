@@ -3,22 +3,32 @@ use crate::domain::{
 };
 use crate::error::Error;
 use crate::kzg::Kzg;
-use crate::multi_unity::{multi_unity_prove, MultiUnityProof};
+use crate::lazy_points::LazyPoints;
+use crate::multi_column::{
+    column_challenge_powers, compress_statements, MultiColumnTablePreprocessedParameters,
+    MultiColumnWitness,
+};
+use crate::multi_unity::{
+    multi_unity_round1, multi_unity_round2, multi_unity_round3, MultiUnityProof, MultiUnityRound1,
+    MultiUnityRound2,
+};
 use crate::public_parameters::PublicParameters;
-use crate::table::TablePreprocessedParameters;
-use crate::transcript::{Label, Transcript};
-use crate::witness::Witness;
+use crate::table::{Table, TablePreprocessedParameters, UniquenessEncoding};
+use crate::transcript::{Label, Transcript, TranscriptProtocol, DEFAULT_LABEL};
+use crate::witness::{PedersenBlinder, Statement, Witness};
 use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::Field;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::Rng;
-use ark_std::{One, Zero};
+use ark_std::{One, UniformRand, Zero};
 use dashmap::DashMap;
 use rayon::prelude::*;
 use std::ops::{AddAssign, Mul};
 
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct Proof<P: Pairing> {
     pub(crate) g1_affine_m: P::G1Affine,       // [M(tau)]_1
     pub(crate) g1_affine_m_div_w: P::G1Affine, // [M(tau / w)]_1
@@ -28,114 +38,549 @@ pub struct Proof<P: Pairing> {
     pub(crate) g1_affine_ql: P::G1Affine,      // [Q_L(tau)]_1
     pub(crate) g1_affine_d: P::G1Affine,       // [D(tau)]_1
     pub(crate) g1_affine_qd: P::G1Affine,      // [Q_D(tau)]_1
+    pub(crate) g1_affine_hm: P::G1Affine,      // [H_M(tau)]_1
+    pub(crate) g1_affine_qt: P::G1Affine,      // [Q_T(tau)]_1
+    pub(crate) g1_affine_ga: P::G1Affine,      // [G_A(tau)]_1, or zero
+    pub(crate) g1_affine_gb: P::G1Affine,      // [G_B(tau)]_1, or zero
     pub(crate) g1_affine_a: P::G1Affine,       // [A(tau)]_1
     pub(crate) g1_affine_qa: P::G1Affine,      // [Q_A(tau)]_1
     pub(crate) g1_affine_qb: P::G1Affine,      // [Q_B(tau)]_1
-    pub(crate) g1_affine_a0: P::G1Affine,      // [A_0(tau)]_1
-    pub(crate) g1_affine_b0: P::G1Affine,      // [B_0(tau)]_1
-    pub(crate) g1_affine_px: P::G1Affine,      // [P_A(tau)]_1 or [P_B(tau)]_1 or zero
+    pub(crate) g1_affine_a0: P::G1Affine,      // [R_A(tau)]_1, R_A(X) = A_0(X) + epsilon * s_A
+    pub(crate) g1_affine_b0: P::G1Affine,      // [B_0(tau)]_1, masked with epsilon * G_B(X)
+    pub(crate) g1_affine_pa: P::G1Affine,      // [P_A(tau)]_1 = [R_A(tau) * tau^{t_A}]_1, or zero
+    pub(crate) g1_affine_ha: P::G1Affine,      // [H_A(tau)]_1, or zero
+    pub(crate) g1_affine_rb: P::G1Affine,      // [R_B(tau)]_1, R_B(X) = B_0(X) mod Z_V(X)
+    pub(crate) g1_affine_hb: P::G1Affine,      // [H_B(tau)]_1, H_B(X) = B_0(X) div Z_V(X)
+    pub(crate) g1_affine_pb: P::G1Affine,      // [P_B(tau)]_1 = [R_B(tau) * tau^{t_B}]_1
     pub(crate) g1_affine_hp: P::G1Affine,      // [H_P(tau)]_1
 
     pub(crate) fr_b0_at_gamma: P::ScalarField, // b_{0,gamma} = B_0(gamma)
     pub(crate) fr_f_at_gamma: P::ScalarField,  // f_{gamma} = F(gamma)
     pub(crate) fr_l_at_gamma: P::ScalarField,  // l_{gamma} = L(gamma)
-    pub(crate) fr_a_at_zero: P::ScalarField,   // a_0 = A(0)
+    pub(crate) fr_a_at_zero: P::ScalarField,   // a_0' = A(0) + epsilon * G_A(0)
     pub(crate) fr_l_at_gamma_div_v: P::ScalarField, // l_{gamma,v} = L(gamma / v)
     pub(crate) fr_ql_at_gamma: P::ScalarField, // q_{gamma,L} = Q_L(gamma)
     pub(crate) fr_d_at_gamma: P::ScalarField,  // d_{gamma} = D(gamma)
     pub(crate) fr_qd_at_gamma: P::ScalarField, // q_{gamma, D} = Q_D(gamma)
+    pub(crate) fr_r_at_gamma: P::ScalarField,  // r_{gamma} = R(gamma), or zero
+    pub(crate) fr_gb_at_gamma: P::ScalarField, // g_{B,gamma} = G_B(gamma)
 
     pub(crate) multi_unity_proof: MultiUnityProof<P>, // Proof of the Caulk Sub-protocol
 }
 
+impl<P: Pairing> Proof<P> {
+    // Assembles the proof from the prover's messages.
+    pub(crate) fn from_messages(
+        round1: Round1Message<P>,
+        caulk_round1: CaulkRound1Message<P>,
+        caulk_round2: CaulkRound2Message<P>,
+        caulk_round3: CaulkRound3Message<P>,
+        round10: Round10Message<P>,
+        round12: Round12Message<P>,
+        round14: Round14Message<P>,
+    ) -> Self {
+        Self {
+            g1_affine_m: round1.g1_affine_m,
+            g1_affine_m_div_w: round1.g1_affine_m_div_w,
+            g1_affine_qm: round1.g1_affine_qm,
+            g1_affine_l: round1.g1_affine_l,
+            g1_affine_l_div_v: round1.g1_affine_l_div_v,
+            g1_affine_ql: round1.g1_affine_ql,
+            g1_affine_d: round1.g1_affine_d,
+            g1_affine_qd: round1.g1_affine_qd,
+            g1_affine_hm: round1.g1_affine_hm,
+            g1_affine_qt: round1.g1_affine_qt,
+            g1_affine_ga: round1.g1_affine_ga,
+            g1_affine_gb: round1.g1_affine_gb,
+            g1_affine_a: round10.g1_affine_a,
+            g1_affine_qa: round10.g1_affine_qa,
+            g1_affine_qb: round10.g1_affine_qb,
+            g1_affine_a0: round10.g1_affine_a0,
+            g1_affine_b0: round10.g1_affine_b0,
+            g1_affine_pa: round10.g1_affine_pa,
+            g1_affine_ha: round10.g1_affine_ha,
+            g1_affine_rb: round10.g1_affine_rb,
+            g1_affine_hb: round10.g1_affine_hb,
+            g1_affine_pb: round10.g1_affine_pb,
+            g1_affine_hp: round14.g1_affine_hp,
+
+            fr_b0_at_gamma: round12.fr_b0_at_gamma,
+            fr_f_at_gamma: round12.fr_f_at_gamma,
+            fr_l_at_gamma: round12.fr_l_at_gamma,
+            fr_a_at_zero: round12.fr_a_at_zero,
+            fr_l_at_gamma_div_v: round12.fr_l_at_gamma_div_v,
+            fr_ql_at_gamma: round12.fr_ql_at_gamma,
+            fr_d_at_gamma: round12.fr_d_at_gamma,
+            fr_qd_at_gamma: round12.fr_qd_at_gamma,
+            fr_r_at_gamma: round12.fr_r_at_gamma,
+            fr_gb_at_gamma: round12.fr_gb_at_gamma,
+
+            multi_unity_proof: MultiUnityProof {
+                g1_u_bar: caulk_round1.g1_u_bar,
+                g1_h_1: caulk_round2.g1_h_1,
+                g1_h_2: caulk_round1.g1_h_2,
+                g1_u_bar_alpha: caulk_round3.g1_u_bar_alpha,
+                g1_h_2_alpha: caulk_round3.g1_h_2_alpha,
+                fr_v1: caulk_round3.fr_v1,
+                fr_v2: caulk_round3.fr_v2,
+                fr_v3: caulk_round3.fr_v3,
+                g1_pi1: caulk_round3.g1_pi1,
+                g1_pi2: caulk_round3.g1_pi2,
+                g1_pi3: caulk_round3.g1_pi3,
+                g1_pi4: caulk_round3.g1_pi4,
+                g1_pi5: caulk_round3.g1_pi5,
+            },
+        }
+    }
+}
+
+/// A proof of a lookup into a dynamic table, with the prover's commitment to
+/// the table in G2, which the verifier checks against the G1 statement.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct DynamicTableProof<P: Pairing> {
+    pub(crate) g2_affine_t: P::G2Affine, // [T(tau)]_2
+    pub(crate) proof: Proof<P>,
+}
+
+/// The prover's Round 1 message: the commitments to the multiplicity and
+/// index polynomials and their quotients, the padding checks, and the masks
+/// of the sumcheck.
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Round1Message<P: Pairing> {
+    pub g1_affine_m: P::G1Affine,       // [M(tau)]_1
+    pub g1_affine_m_div_w: P::G1Affine, // [M(tau / w)]_1
+    pub g1_affine_qm: P::G1Affine,      // [Q_M(tau)]_1
+    pub g1_affine_l: P::G1Affine,       // [L(tau)]_1
+    pub g1_affine_l_div_v: P::G1Affine, // [L(tau / v)]_1
+    pub g1_affine_ql: P::G1Affine,      // [Q_L(tau)]_1
+    pub g1_affine_d: P::G1Affine,       // [D(tau)]_1
+    pub g1_affine_qd: P::G1Affine,      // [Q_D(tau)]_1
+    pub g1_affine_hm: P::G1Affine,      // [H_M(tau)]_1
+    pub g1_affine_qt: P::G1Affine,      // [Q_T(tau)]_1
+    pub g1_affine_ga: P::G1Affine,      // [G_A(tau)]_1, or zero
+    pub g1_affine_gb: P::G1Affine,      // [G_B(tau)]_1, or zero
+}
+
+impl<P: Pairing> Round1Message<P> {
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_points(&[
+            (Label::G1M, self.g1_affine_m),
+            (Label::G1MDivW, self.g1_affine_m_div_w),
+            (Label::G1Qm, self.g1_affine_qm),
+            (Label::G1L, self.g1_affine_l),
+            (Label::G1LDivV, self.g1_affine_l_div_v),
+            (Label::G1Ql, self.g1_affine_ql),
+            (Label::G1D, self.g1_affine_d),
+            (Label::G1Qd, self.g1_affine_qd),
+            (Label::G1Hm, self.g1_affine_hm),
+            (Label::G1Qt, self.g1_affine_qt),
+            (Label::G1Ga, self.g1_affine_ga),
+            (Label::G1Gb, self.g1_affine_gb),
+        ])
+    }
+}
+
+/// The prover's first message of the multi-unity check (Rounds 3-8).
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CaulkRound1Message<P: Pairing> {
+    pub g1_u_bar: P::G1Affine, // [U_bar(tau)]_1
+    pub g1_h_2: P::G1Affine,   // [H_2(tau)]_1
+}
+
+impl<P: Pairing> CaulkRound1Message<P> {
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_points(&[
+            (Label::CaulkG1UBar, self.g1_u_bar),
+            (Label::CaulkG1H2, self.g1_h_2),
+        ])
+    }
+}
+
+/// The prover's second message of the multi-unity check (Rounds 3-8).
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CaulkRound2Message<P: Pairing> {
+    pub g1_h_1: P::G1Affine, // [H_1(tau)]_1
+}
+
+impl<P: Pairing> CaulkRound2Message<P> {
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_point(Label::CaulkG1H1, &self.g1_h_1)
+    }
+}
+
+/// The prover's last message of the multi-unity check (Rounds 3-8): the
+/// evaluations at the challenges alpha and beta, and their openings. It is
+/// followed by the challenges of Round 9 without being absorbed.
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CaulkRound3Message<P: Pairing> {
+    pub g1_u_bar_alpha: P::G1Affine,
+    pub g1_h_2_alpha: P::G1Affine,
+    pub fr_v1: P::ScalarField,
+    pub fr_v2: P::ScalarField,
+    pub fr_v3: P::ScalarField,
+    pub g1_pi1: P::G1Affine,
+    pub g1_pi2: P::G1Affine,
+    pub g1_pi3: P::G1Affine,
+    pub g1_pi4: P::G1Affine,
+    pub g1_pi5: P::G1Affine,
+}
+
+impl<P: Pairing> From<MultiUnityProof<P>> for CaulkRound3Message<P> {
+    fn from(proof: MultiUnityProof<P>) -> Self {
+        Self {
+            g1_u_bar_alpha: proof.g1_u_bar_alpha,
+            g1_h_2_alpha: proof.g1_h_2_alpha,
+            fr_v1: proof.fr_v1,
+            fr_v2: proof.fr_v2,
+            fr_v3: proof.fr_v3,
+            g1_pi1: proof.g1_pi1,
+            g1_pi2: proof.g1_pi2,
+            g1_pi3: proof.g1_pi3,
+            g1_pi4: proof.g1_pi4,
+            g1_pi5: proof.g1_pi5,
+        }
+    }
+}
+
+/// The prover's Round 10 message: the commitments to A(X), B_0(X), their
+/// quotients, and the degree checks.
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Round10Message<P: Pairing> {
+    pub g1_affine_a: P::G1Affine,  // [A(tau)]_1
+    pub g1_affine_qa: P::G1Affine, // [Q_A(tau)]_1
+    pub g1_affine_qb: P::G1Affine, // [Q_B(tau)]_1
+    pub g1_affine_a0: P::G1Affine, // [R_A(tau)]_1, R_A(X) = A_0(X) + epsilon * s_A
+    pub g1_affine_b0: P::G1Affine, // [B_0(tau)]_1, masked with epsilon * G_B(X)
+    pub g1_affine_pa: P::G1Affine, // [P_A(tau)]_1 = [R_A(tau) * tau^{t_A}]_1, or zero
+    pub g1_affine_ha: P::G1Affine, // [H_A(tau)]_1, or zero
+    pub g1_affine_rb: P::G1Affine, // [R_B(tau)]_1, R_B(X) = B_0(X) mod Z_V(X)
+    pub g1_affine_hb: P::G1Affine, // [H_B(tau)]_1, H_B(X) = B_0(X) div Z_V(X)
+    pub g1_affine_pb: P::G1Affine, // [P_B(tau)]_1 = [R_B(tau) * tau^{t_B}]_1
+}
+
+impl<P: Pairing> Round10Message<P> {
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_points(&[
+            (Label::G1A, self.g1_affine_a),
+            (Label::G1Qa, self.g1_affine_qa),
+            (Label::G1Qb, self.g1_affine_qb),
+            (Label::G1A0, self.g1_affine_a0),
+            (Label::G1B0, self.g1_affine_b0),
+            (Label::G1Pa, self.g1_affine_pa),
+            (Label::G1Ha, self.g1_affine_ha),
+            (Label::G1Rb, self.g1_affine_rb),
+            (Label::G1Hb, self.g1_affine_hb),
+            (Label::G1Pb, self.g1_affine_pb),
+        ])
+    }
+}
+
+/// The prover's Round 12 message: the evaluations at gamma, and the masked
+/// evaluation of A(X) at zero.
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Round12Message<P: Pairing> {
+    pub fr_b0_at_gamma: P::ScalarField, // b_{0,gamma} = B_0(gamma)
+    pub fr_f_at_gamma: P::ScalarField,  // f_{gamma} = F(gamma)
+    pub fr_l_at_gamma: P::ScalarField,  // l_{gamma} = L(gamma)
+    pub fr_a_at_zero: P::ScalarField,   // a_0' = A(0) + epsilon * G_A(0)
+    pub fr_l_at_gamma_div_v: P::ScalarField, // l_{gamma,v} = L(gamma / v)
+    pub fr_ql_at_gamma: P::ScalarField, // q_{gamma,L} = Q_L(gamma)
+    pub fr_d_at_gamma: P::ScalarField,  // d_{gamma} = D(gamma)
+    pub fr_qd_at_gamma: P::ScalarField, // q_{gamma, D} = Q_D(gamma)
+    pub fr_r_at_gamma: P::ScalarField,  // r_{gamma} = R(gamma), or zero
+    pub fr_gb_at_gamma: P::ScalarField, // g_{B,gamma} = G_B(gamma)
+}
+
+impl<P: Pairing> Round12Message<P> {
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_scalars(&[
+            (Label::FrB0AtGamma, self.fr_b0_at_gamma),
+            (Label::FrFAtGamma, self.fr_f_at_gamma),
+            (Label::FrLAtGamma, self.fr_l_at_gamma),
+            (Label::FrAAtZero, self.fr_a_at_zero),
+            (Label::FrLAtGammaDivV, self.fr_l_at_gamma_div_v),
+            (Label::FrQlAtGamma, self.fr_ql_at_gamma),
+            (Label::FrDAtGamma, self.fr_d_at_gamma),
+            (Label::FrQdAtGamma, self.fr_qd_at_gamma),
+            (Label::FrRAtGamma, self.fr_r_at_gamma),
+            (Label::FrGbAtGamma, self.fr_gb_at_gamma),
+        ])
+    }
+}
+
+/// The prover's Round 14 message: the KZG batch opening at gamma.
+#[derive(Copy, Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Round14Message<P: Pairing> {
+    pub g1_affine_hp: P::G1Affine, // [H_P(tau)]_1
+}
+
+/// The options of a proof made on a transcript, which the verifier must
+/// match with [`crate::verifier::VerifyOptions`]. By default, the lookup is
+/// separated with [`DEFAULT_LABEL`], has an empty context, and proves a plain
+/// statement.
+pub struct ProofOptions<'a, P: Pairing> {
+    label: &'a [u8],
+    context: &'a [u8],
+    pedersen_blinder: Option<&'a PedersenBlinder<P>>,
+}
+
+impl<P: Pairing> Default for ProofOptions<'_, P> {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_LABEL,
+            context: &[],
+            pedersen_blinder: None,
+        }
+    }
+}
+
+impl<'a, P: Pairing> ProofOptions<'a, P> {
+    /// Sets the label that separates this lookup from the other
+    /// sub-arguments on the transcript of an outer protocol.
+    pub fn label(mut self, label: &'a [u8]) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// Sets the application context, such as a chain ID, a block height or a
+    /// session ID. The proof only verifies under the same context, so it
+    /// cannot be replayed in another one.
+    pub fn context(mut self, context: &'a [u8]) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Proves a lookup for the Pedersen statement [F(tau) + gamma * R(tau)]_1
+    /// from [`Witness::generate_pedersen_statement`], given its blinder R(X).
+    /// Multi-column statements cannot be Pedersen statements.
+    pub fn pedersen_blinder(mut self, pedersen_blinder: &'a PedersenBlinder<P>) -> Self {
+        self.pedersen_blinder = Some(pedersen_blinder);
+        self
+    }
+
+    pub(crate) fn append_to<T: TranscriptProtocol<P::ScalarField>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_bytes(Label::DomainSeparator, self.label)?;
+        transcript.append_bytes(Label::Context, self.context)
+    }
+}
+
 pub fn prove<P: Pairing, R: Rng + ?Sized>(
     pp: &PublicParameters<P>,
     tpp: &TablePreprocessedParameters<P>,
     witness: &Witness<P>,
-    statement: P::G1Affine,
+    statement: Statement<P>,
     rng: &mut R,
 ) -> Result<Proof<P>, Error> {
-    let mut transcript = Transcript::<P::ScalarField>::new();
-    transcript.append_elements(&[
-        (Label::PublicParameters, pp.hash_representation.clone()),
-        (
-            Label::TablePreprocessedParameters,
-            tpp.hash_representation.clone(),
-        ),
-    ])?;
-    transcript.append_element(Label::Statement, &statement)?;
-
-    // Round 1-1: Compute the multiplicity polynomial M of degree (ns - 1),
-    // and send [M(tau)]_1 and [M(tau / w)]_1 to the verifier.
-    // Round 1-2: Compute and send [Q_M(tau)]_1 using the SRS and Lemma 4.
-    let segment_multiplicities =
-        compute_segment_multiplicities(&witness.segment_indices, pp.num_table_segments)?;
-    let MultiplicityPolynomialsAndQuotient {
-        g1_affine_m,
-        g1_affine_m_div_w,
-        g1_affine_qm,
-    } = compute_multiplicity_polynomials_and_quotient::<P>(
-        &segment_multiplicities,
-        &pp.g1_affine_list_lw,
-        &pp.g1_affine_list_q3,
-        pp.segment_size,
-        pp.table_element_size,
-    );
-
-    transcript.append_elements(&[
-        (Label::G1M, g1_affine_m),
-        (Label::G1MDivW, g1_affine_m_div_w),
-        (Label::G1Qm, g1_affine_qm),
-    ])?;
-
-    // Round 1-3: Compute the indexing polynomial L(X) of degree (ks - 1),
-    // which maps the segment element indices from the witness to the table.
-    // Round 1-5: Compute another indexing polynomial D(X) of degree (k - 1).
-    // For each i \in [0, k - 1], D(v^{is}) = L(v^{is}) = w^{js}
-    // Round 1-4: Compute the quotient polynomial Q_L(X) s.t.
-    // (X^k - 1)*(L(Xv) - w*L(X)) = Z_V(X)*Q_L(X),
-    // and send [Q_L(tau)]_1 to the verifier.
-    // Inverse FFT costs O(ks log(ks)) operations
-    // Round 1-6: Compute Q_D s.t. L(X) - D(X) = Z_K(X)*Q_D(X),
-    // and send [Q_D(tau)]_1 to the verifier.
-    let roots_of_unity_w = roots_of_unity::<P>(&pp.domain_w);
-    let roots_of_unity_v = roots_of_unity::<P>(&pp.domain_v);
-    let domain_generator_w = pp.domain_w.group_gen;
-    let IndexPolynomialsAndQuotients {
-        g1_affine_l,
-        g1_affine_l_div_v,
-        g1_affine_d,
-        g1_affine_ql,
-        g1_affine_qd,
-        poly_l,
-        poly_ql,
-        poly_l_div_v,
-        poly_eval_list_l,
-        poly_eval_list_d,
-        poly_d,
-        poly_qd,
-    } = compute_index_polynomials_and_quotients::<P>(
-        &pp.domain_k,
-        &pp.domain_v,
-        &pp.domain_coset_v,
-        &roots_of_unity_w,
-        &roots_of_unity_v,
-        &pp.partial_inv_zk_at_coset_v_values,
-        &pp.g1_affine_list_lv,
-        &pp.g1_affine_srs,
-        &witness.segment_indices,
-        domain_generator_w,
-        pp.witness_element_size,
-        pp.segment_size,
+    prove_with_transcript(
+        pp,
+        tpp,
+        witness,
+        statement,
+        &mut Transcript::new(),
+        &ProofOptions::default(),
+        rng,
+    )
+}
+
+/// Proves a lookup on the given transcript, which may be a fresh one of any
+/// [`TranscriptProtocol`], e.g. [`crate::transcript::PoseidonTranscript`]
+/// for proofs that are verified inside a circuit, or the transcript of an
+/// outer protocol. The transcript first absorbs the label and the context of
+/// `options`, so the challenges are bound to everything appended before it.
+/// The transcript is left in the same state as after
+/// [`crate::verifier::verify_with_transcript`], for the outer protocol to
+/// continue on.
+pub fn prove_with_transcript<P: Pairing, T: TranscriptProtocol<P::ScalarField>, R: Rng + ?Sized>(
+    pp: &PublicParameters<P>,
+    tpp: &TablePreprocessedParameters<P>,
+    witness: &Witness<P>,
+    statement: Statement<P>,
+    transcript: &mut T,
+    options: &ProofOptions<P>,
+    rng: &mut R,
+) -> Result<Proof<P>, Error> {
+    options.append_to(transcript)?;
+
+    prove_rounds(pp, tpp, witness, statement, options, transcript, rng)
+}
+
+/// Proves a lookup into a multi-column table, given one statement per column.
+/// The columns of the table and the witness are compressed with a challenge
+/// derived from the transcript, and the compressed lookup is proven.
+pub fn prove_multi_column<P: Pairing, R: Rng + ?Sized>(
+    pp: &PublicParameters<P>,
+    tpp: &MultiColumnTablePreprocessedParameters<P>,
+    witness: &MultiColumnWitness<P>,
+    statements: &[Statement<P>],
+    rng: &mut R,
+) -> Result<Proof<P>, Error> {
+    prove_multi_column_with_transcript(
+        pp,
+        tpp,
+        witness,
+        statements,
+        &mut Transcript::new(),
+        &ProofOptions::default(),
+        rng,
+    )
+}
+
+/// Proves a lookup into a multi-column table on the given transcript, with
+/// `options` as in [`prove_with_transcript`]. The proof is verified with
+/// [`crate::verifier::verify_multi_column_with_transcript`].
+pub fn prove_multi_column_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
+    pp: &PublicParameters<P>,
+    tpp: &MultiColumnTablePreprocessedParameters<P>,
+    witness: &MultiColumnWitness<P>,
+    statements: &[Statement<P>],
+    transcript: &mut T,
+    options: &ProofOptions<P>,
+    rng: &mut R,
+) -> Result<Proof<P>, Error> {
+    if witness.columns.len() != tpp.num_columns() {
+        return Err(Error::InvalidNumberOfColumns(witness.columns.len()));
+    }
+    if options.pedersen_blinder.is_some() {
+        return Err(Error::UnsupportedPedersenStatement);
+    }
+
+    options.append_to(transcript)?;
+
+    let fr_zeta_powers = column_challenge_powers::<P, _>(
+        transcript,
+        &pp.hash_representation,
+        &tpp.hash_representation,
+        tpp.num_columns(),
+        statements,
     )?;
 
-    transcript.append_elements(&[
-        (Label::G1L, g1_affine_l),
-        (Label::G1LDivV, g1_affine_l_div_v),
-        (Label::G1Ql, g1_affine_ql),
-        (Label::G1D, g1_affine_d),
-        (Label::G1Qd, g1_affine_qd),
-    ])?;
+    let compressed_tpp =
+        tpp.compress_for_prover(pp, &fr_zeta_powers, &witness.segment_indices())?;
+    let compressed_witness = witness.compress(&fr_zeta_powers);
+    let compressed_statement = compress_statements::<P>(statements, &fr_zeta_powers)?;
+
+    prove_rounds(
+        pp,
+        &compressed_tpp,
+        &compressed_witness,
+        compressed_statement,
+        options,
+        transcript,
+        rng,
+    )
+}
+
+/// Proves a lookup into a dynamic table that the prover commits to at
+/// proving time, such as a memory image, given as `table_statement` =
+/// [T(tau)]_1 from [`Table::commit`]. The witness must be built from the raw
+/// table values, since dynamic tables have no uniqueness encoding.
+///
+/// Unlike a preprocessed table, the quotients q_{i, 1} and [T(tau)]_2 are
+/// computed in every proof, which adds O(ns log(ns)) group operations to the
+/// prover, making it linear in the table size. The proof has one more G2
+/// element, and the verifier checks it with two more Miller loops.
+pub fn prove_dynamic_table<P: Pairing, R: Rng + ?Sized>(
+    pp: &PublicParameters<P>,
+    table: &Table<P>,
+    table_statement: P::G1Affine,
+    witness: &Witness<P>,
+    statement: Statement<P>,
+    rng: &mut R,
+) -> Result<DynamicTableProof<P>, Error> {
+    prove_dynamic_table_with_transcript(
+        pp,
+        table,
+        table_statement,
+        witness,
+        statement,
+        &mut Transcript::new(),
+        &ProofOptions::default(),
+        rng,
+    )
+}
+
+/// Proves a lookup into a dynamic table on the given transcript, with
+/// `options` as in [`prove_with_transcript`]. The proof is verified with
+/// [`crate::verifier::verify_dynamic_table_with_transcript`].
+#[allow(clippy::too_many_arguments)]
+pub fn prove_dynamic_table_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
+    pp: &PublicParameters<P>,
+    table: &Table<P>,
+    table_statement: P::G1Affine,
+    witness: &Witness<P>,
+    statement: Statement<P>,
+    transcript: &mut T,
+    options: &ProofOptions<P>,
+    rng: &mut R,
+) -> Result<DynamicTableProof<P>, Error> {
+    let tpp = table.preprocess_with_encoding(pp, UniquenessEncoding::None)?;
+
+    options.append_to(transcript)?;
+    transcript.append_point(Label::DynamicTable, &table_statement)?;
+    let proof = prove_rounds(pp, &tpp, witness, statement, options, transcript, rng)?;
+
+    Ok(DynamicTableProof {
+        g2_affine_t: tpp.g2_affine_t,
+        proof,
+    })
+}
+
+// Runs rounds 1-15 of the protocol on the given transcript, from which the
+// verifier challenges are derived. The options are appended by the caller,
+// before any message of its own.
+pub(crate) fn prove_rounds<P: Pairing, T: TranscriptProtocol<P::ScalarField>, R: Rng + ?Sized>(
+    pp: &PublicParameters<P>,
+    tpp: &TablePreprocessedParameters<P>,
+    witness: &Witness<P>,
+    statement: Statement<P>,
+    options: &ProofOptions<P>,
+    transcript: &mut T,
+    rng: &mut R,
+) -> Result<Proof<P>, Error> {
+    transcript.append_bytes(Label::PublicParameters, &pp.hash_representation)?;
+    transcript.append_bytes(Label::TablePreprocessedParameters, &tpp.hash_representation)?;
+    if statement.num_queries != witness.num_queries {
+        return Err(Error::InvalidNumberOfQueries(statement.num_queries));
+    }
+    transcript.append_point(Label::Statement, &statement.g1_affine_f)?;
+    transcript.append_scalar(
+        Label::NumQueries,
+        &P::ScalarField::from(statement.num_queries as u64),
+    )?;
+
+    let mut prover = match options.pedersen_blinder {
+        Some(pedersen_blinder) => ProverState::new_pedersen(pp, tpp, witness, pedersen_blinder)?,
+        None => ProverState::new(pp, tpp, witness),
+    };
+
+    let round1 = prover.round1(rng)?;
+    round1.append_to(transcript)?;
 
     // Round 2 is performed by the verifier.
 
@@ -143,178 +588,919 @@ pub fn prove<P: Pairing, R: Rng + ?Sized>(
     // Using the instantiation of Lemma 5,
     // the prover and verifier engage in a protocol that polynomial L is
     // well-formed.
-    let multi_unity_proof = multi_unity_prove(
-        pp,
-        &mut transcript,
-        &poly_eval_list_d,
-        &poly_d,
-        &g1_affine_d,
-        rng,
-    )?;
+    let caulk_round1 = prover.caulk_round1(rng)?;
+    transcript.append_point(Label::CaulkG1D, &round1.g1_affine_d)?;
+    caulk_round1.append_to(transcript)?;
+    let caulk_alpha = transcript.squeeze_challenge(Label::ChallengeCaulkAlpha)?;
 
-    // Round 9: The verifier sends random scalar fields beta, delta to the prover.
+    let caulk_round2 = prover.caulk_round2(caulk_alpha)?;
+    caulk_round2.append_to(transcript)?;
+    let caulk_beta = transcript.squeeze_challenge(Label::ChallengeCaulkBeta)?;
+
+    let caulk_round3 = prover.caulk_round3(caulk_beta)?;
+
+    // Round 9: The verifier sends random scalar fields beta, delta, and
+    // epsilon to the prover.
     // Use Fiat-Shamir heuristic to make the protocol non-interactive.
     let beta = transcript.squeeze_challenge(Label::ChallengeBeta)?;
     let delta = transcript.squeeze_challenge(Label::ChallengeDelta)?;
+    let epsilon = transcript.squeeze_challenge(Label::ChallengeEpsilon)?;
 
-    // Round 10-1: The prover computes A(X) of degree ns-1 in sparse form,
-    // and sends [A(tau)]_1 to the verifier.
-    // Round 10-2: The prover computes [Q_A(tau)]_1 using the SRS and Lemma 4.
-    // Round 10-5: The prover computes A_0(X) = (A(X) - A(0)) / X,
-    // and sends [A_0(tau)]_1 to the verifier.
-    let PolynomialAAndQuotient {
-        g1_affine_a,
-        g1_affine_qa,
-        g1_affine_a0,
-        sparse_poly_eval_list_a,
-    }: PolynomialAAndQuotient<P> = compute_polynomial_a_and_quotient(
-        beta,
-        delta,
-        &tpp.adjusted_table_values,
-        &segment_multiplicities,
-        pp.segment_size,
-        &roots_of_unity_w,
-        &pp.g1_affine_list_lw,
-        &tpp.g1_affine_list_q1,
-        &pp.g1_affine_list_q2,
-        &pp.g1_affine_lw_opening_proofs_at_zero,
-    )?;
-
-    // Round 10-3: The prover computes B(X) of degree ks-1.
-    // Round 10-4: The prover computes [Q_B(tau)]_1 using the SRS and Lemma 4.
-    // Round 10-5: The prover computes B_0(X) = (B(X) - B(0)) / X,
-    // and sends [B_0(tau)]_1 to the verifier.
-    let PolynomialBAndQuotient {
-        poly_b,
-        poly_qb,
-        poly_b0,
-        g1_affine_qb,
-        g1_affine_b0,
-    } = compute_polynomial_b_and_quotient(
-        beta,
-        delta,
-        &witness,
-        pp.witness_element_size,
-        &pp.domain_v,
-        &pp.domain_coset_v,
-        &poly_eval_list_l,
-        &poly_l,
-        &pp.g1_affine_srs,
-    )?;
-
-    // Round 10-6: Degree check.
-    // This step is only necessary when k != n.
-    let g1_affine_px = compute_degree_check_g1_affine::<P>(
-        pp.num_table_segments,
-        pp.num_witness_segments,
-        pp.segment_size,
-        pp.table_element_size,
-        &poly_b0,
-        &pp.g1_affine_srs,
-        &sparse_poly_eval_list_a,
-        &pp.domain_w,
-    );
-
-    transcript.append_elements(&[
-        (Label::G1A, g1_affine_a),
-        (Label::G1Qa, g1_affine_qa),
-        (Label::G1Qb, g1_affine_qb),
-        (Label::G1A0, g1_affine_a0),
-        (Label::G1B0, g1_affine_b0),
-        (Label::G1Px, g1_affine_px),
-    ])?;
+    let round10 = prover.round10(beta, delta, epsilon)?;
+    round10.append_to(transcript)?;
 
     // Round 11-3: The verifier sends random scalar gamma to the prover.
     // Use Fiat-Shamir heuristic to make the protocol non-interactive.
     let gamma = transcript.squeeze_challenge(Label::ChallengeGamma)?;
 
-    // Round 12: The prover sends b_{0,gamma} = B_0(gamma), f_{gamma} = F(gamma),
-    // l_{gamma} = L(gamma), a_0 = A(0), l_{gamma,v} = L(v*gamma), q_{gamma,L}
-    // = Q_L(gamma), d_{gamma} = D(gamma), and q_{gamma, D} = Q_D(gamma)
-    // to the verifier.
-    let fr_b0_at_gamma = poly_b0.evaluate(&gamma);
-    let fr_f_at_gamma = witness.poly.evaluate(&gamma);
-    let fr_l_at_gamma = poly_l.evaluate(&gamma);
-    // Compute a_0 using sumcheck lemma.
-    let fr_a_at_zero = {
-        let fr_b_at_zero = poly_b.evaluate(&P::ScalarField::zero());
-        let table_elem_size = pp.num_table_segments * pp.segment_size;
-        let fr_inv_table_elem_size = P::ScalarField::from(table_elem_size as u64)
-            .inverse()
-            .ok_or(Error::FailedToInverseFieldElement)?;
-        let witness_elem_size = pp.num_witness_segments * pp.segment_size;
-        let fr_witness_elem_size = P::ScalarField::from(witness_elem_size as u64);
-
-        fr_b_at_zero * fr_witness_elem_size * fr_inv_table_elem_size
-    };
-    let fr_gamma_div_v = gamma / pp.domain_v.group_gen;
-    let fr_l_at_gamma_div_v = poly_l.evaluate(&fr_gamma_div_v);
-    let fr_ql_at_gamma = poly_ql.evaluate(&gamma);
-    let fr_d_at_gamma = poly_d.evaluate(&gamma);
-    let fr_qd_at_gamma = poly_qd.evaluate(&gamma);
-
-    transcript.append_elements(&[
-        (Label::FrB0AtGamma, fr_b0_at_gamma),
-        (Label::FrFAtGamma, fr_f_at_gamma),
-        (Label::FrLAtGamma, fr_l_at_gamma),
-        (Label::FrAAtZero, fr_a_at_zero),
-        (Label::FrLAtGammaDivV, fr_l_at_gamma_div_v),
-        (Label::FrQlAtGamma, fr_ql_at_gamma),
-        (Label::FrDAtGamma, fr_d_at_gamma),
-        (Label::FrQdAtGamma, fr_qd_at_gamma),
-    ])?;
+    let round12 = prover.round12(gamma)?;
+    round12.append_to(transcript)?;
 
     // Round 11-3: Use Fiat-Shamir transform to sample eta.
     let eta = transcript.squeeze_challenge(Label::ChallengeEta)?;
 
-    // Round 14: Compute the commitment of H_P(X)
-    // = (P(X) - p_{gamma}) / (X - gamma),
-    // which is a KZG batch opening proof of the polynomials to be
-    // checked, and send [H_P(tau)]_1 to the verifier.
-    let g1_affine_hp = Kzg::<P::G1>::batch_open(
-        &pp.g1_affine_srs,
-        &[
+    let round14 = prover.round14(eta)?;
+
+    Ok(Proof::from_messages(
+        round1,
+        caulk_round1,
+        caulk_round2,
+        caulk_round3,
+        round10,
+        round12,
+        round14,
+    ))
+}
+
+// The rounds in which the prover sends a message, in order.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Round {
+    Round1,
+    CaulkRound1,
+    CaulkRound2,
+    CaulkRound3,
+    Round10,
+    Round12,
+    Round14,
+    Done,
+}
+
+impl Round {
+    fn name(&self) -> &'static str {
+        match self {
+            Round::Round1 => "round 1",
+            Round::CaulkRound1 => "caulk round 1",
+            Round::CaulkRound2 => "caulk round 2",
+            Round::CaulkRound3 => "caulk round 3",
+            Round::Round10 => "round 10",
+            Round::Round12 => "round 12",
+            Round::Round14 => "round 14",
+            Round::Done => "done",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            Round::Round1 => Round::CaulkRound1,
+            Round::CaulkRound1 => Round::CaulkRound2,
+            Round::CaulkRound2 => Round::CaulkRound3,
+            Round::CaulkRound3 => Round::Round10,
+            Round::Round10 => Round::Round12,
+            Round::Round12 => Round::Round14,
+            Round::Round14 | Round::Done => Round::Done,
+        }
+    }
+
+    // Advances to the next round if the current one is `round`.
+    pub(crate) fn advance(&mut self, round: Round) -> Result<(), Error> {
+        if *self != round {
+            return Err(Error::UnexpectedRound(self.name()));
+        }
+        *self = self.next();
+
+        Ok(())
+    }
+}
+
+/// The prover of the interactive protocol, which advances one round at a
+/// time. Each round takes the verifier challenges sent after the previous
+/// message, and returns the next message. The rounds must be called in
+/// order; [`prove`] runs them with challenges derived from the transcript.
+pub struct ProverState<'a, P: Pairing> {
+    pp: &'a PublicParameters<P>,
+    tpp: &'a TablePreprocessedParameters<P>,
+    witness: &'a Witness<P>,
+    pedersen_blinder: Option<&'a PedersenBlinder<P>>,
+    round: Round,
+    round1: Option<Round1State<P>>,
+    multi_unity_round1: Option<MultiUnityRound1<P>>,
+    multi_unity_round2: Option<MultiUnityRound2<P>>,
+    round10: Option<Round10State<P>>,
+    gamma: P::ScalarField,
+}
+
+// The polynomials computed in Round 1.
+struct Round1State<P: Pairing> {
+    segment_multiplicities: DashMap<usize, usize>,
+    blinders: Option<Blinders<P>>,
+    poly_gb: DensePolynomial<P::ScalarField>,
+    // B(X) is computed from the unblinded L(X) in Round 10.
+    poly_l_unblinded: DensePolynomial<P::ScalarField>,
+    poly_l: DensePolynomial<P::ScalarField>,
+    poly_ql: DensePolynomial<P::ScalarField>,
+    poly_l_div_v: DensePolynomial<P::ScalarField>,
+    poly_eval_list_l: Vec<P::ScalarField>,
+    poly_eval_list_d: Vec<P::ScalarField>,
+    poly_d: DensePolynomial<P::ScalarField>,
+    poly_qd: DensePolynomial<P::ScalarField>,
+}
+
+// The polynomials computed in Round 10.
+struct Round10State<P: Pairing> {
+    epsilon: P::ScalarField,
+    poly_b: DensePolynomial<P::ScalarField>,
+    poly_b0: DensePolynomial<P::ScalarField>,
+    poly_qb: DensePolynomial<P::ScalarField>,
+    poly_f: DensePolynomial<P::ScalarField>,
+}
+
+impl<'a, P: Pairing> ProverState<'a, P> {
+    pub fn new(
+        pp: &'a PublicParameters<P>,
+        tpp: &'a TablePreprocessedParameters<P>,
+        witness: &'a Witness<P>,
+    ) -> Self {
+        Self {
+            pp,
+            tpp,
+            witness,
+            pedersen_blinder: None,
+            round: Round::Round1,
+            round1: None,
+            multi_unity_round1: None,
+            multi_unity_round2: None,
+            round10: None,
+            gamma: P::ScalarField::zero(),
+        }
+    }
+
+    /// Creates a prover for the Pedersen statement
+    /// [F(tau) + gamma * R(tau)]_1 from
+    /// [`Witness::generate_pedersen_statement`] with `pedersen_blinder`.
+    /// The witness must be blinded, since f_{gamma} = F(gamma) in the proof
+    /// would otherwise reveal it.
+    pub fn new_pedersen(
+        pp: &'a PublicParameters<P>,
+        tpp: &'a TablePreprocessedParameters<P>,
+        witness: &'a Witness<P>,
+        pedersen_blinder: &'a PedersenBlinder<P>,
+    ) -> Result<Self, Error> {
+        if pp.g1_affine_srs_gamma.is_empty() {
+            return Err(Error::PedersenStatementsNotEnabled);
+        }
+        if witness.poly_blinder.is_zero() {
+            return Err(Error::WitnessNotBlinded);
+        }
+
+        Ok(Self {
+            pedersen_blinder: Some(pedersen_blinder),
+            ..Self::new(pp, tpp, witness)
+        })
+    }
+
+    /// Round 1: commits to the multiplicity polynomial M(X), the index
+    /// polynomials L(X) and D(X), their quotients, and the masks G_A(X) and
+    /// G_B(X) of the sumcheck.
+    pub fn round1<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<Round1Message<P>, Error> {
+        self.round.advance(Round::Round1)?;
+        let pp = self.pp;
+        let blinders = pp.zero_knowledge.then(|| Blinders::<P>::rand(rng));
+
+        // Round 1-1: Compute the multiplicity polynomial M of degree (ns - 1),
+        // and send [M(tau)]_1 and [M(tau / w)]_1 to the verifier.
+        // Round 1-2: Compute and send [Q_M(tau)]_1 using the SRS and Lemma 4.
+        let segment_multiplicities =
+            compute_segment_multiplicities(&self.witness.segment_indices, pp.num_table_segments)?;
+        let mut multiplicity_polynomials_and_quotient =
+            compute_multiplicity_polynomials_and_quotient::<P>(
+                &segment_multiplicities,
+                &pp.g1_affine_list_lw,
+                &pp.g1_affine_list_q3,
+                pp.segment_size,
+                pp.table_element_size,
+            )?;
+
+        // Round 1-3: Compute the indexing polynomial L(X) of degree (ks - 1),
+        // which maps the segment element indices from the witness to the table.
+        // Round 1-5: Compute another indexing polynomial D(X) of degree (k - 1).
+        // For each i \in [0, k - 1], D(v^{is}) = L(v^{is}) = w^{js}
+        // Round 1-4: Compute the quotient polynomial Q_L(X) s.t.
+        // (X^k - 1)*(L(Xv) - w*L(X)) = Z_V(X)*Q_L(X),
+        // and send [Q_L(tau)]_1 to the verifier.
+        // Inverse FFT costs O(ks log(ks)) operations
+        // Round 1-6: Compute Q_D s.t. L(X) - D(X) = Z_K(X)*Q_D(X),
+        // and send [Q_D(tau)]_1 to the verifier.
+        let roots_of_unity_v = roots_of_unity::<P>(&pp.domain_v);
+        let domain_generator_w = pp.domain_w.group_gen;
+        let mut index_polynomials_and_quotients = compute_index_polynomials_and_quotients::<P>(
+            &pp.domain_k,
+            &pp.domain_v,
+            &pp.domain_coset_v,
+            &pp.domain_w,
+            &roots_of_unity_v,
+            &pp.partial_inv_zk_at_coset_v_values,
+            &pp.g1_affine_list_lv,
+            pp.g1_affine_srs.as_slice()?,
+            &self.witness.segment_indices,
+            domain_generator_w,
+            pp.witness_element_size,
+            pp.segment_size,
+        )?;
+        let poly_l_unblinded = index_polynomials_and_quotients.poly_l.clone();
+        // In zero-knowledge mode, blind L(X), L(X / v), D(X), Q_L(X), and Q_D(X).
+        if let Some(blinders) = &blinders {
+            blinders.blind_index_polynomials_and_quotients(
+                &mut index_polynomials_and_quotients,
+                &pp.domain_v,
+                &pp.domain_k,
+                domain_generator_w,
+                pp.g1_affine_srs.as_slice()?,
+            )?;
+        }
+        let IndexPolynomialsAndQuotients {
+            g1_affine_l,
+            g1_affine_l_div_v,
+            g1_affine_d,
+            g1_affine_ql,
+            g1_affine_qd,
+            poly_l,
+            poly_ql,
             poly_l_div_v,
+            poly_eval_list_l,
+            poly_eval_list_d,
+            poly_d,
+            poly_qd,
+        } = index_polynomials_and_quotients;
+
+        // Round 1-7: Open M(X) at w^{ps}, the first element of the padding
+        // segment p, and send [H_M(tau)]_1. The verifier checks that the
+        // multiplicity of the padding segment is k - q, so that no query uses it.
+        // Round 1-8: Compute Q_T(X) = (D(X) - w^{ps}) * S_q(X) / Z_K(X), where
+        // S_q(X) selects the padding queries of domain K, and send [Q_T(tau)]_1.
+        // The verifier checks that every padding query uses the padding segment.
+        let padding_segment_index = pp.padding_segment_index();
+        let fr_padding_root = pp.domain_w.element(padding_segment_index * pp.segment_size);
+        let mut g1_affine_hm = compute_padding_multiplicity_opening::<P>(
+            &segment_multiplicities,
+            &pp.g1_affine_list_lw,
+            pp.g1_affine_lw_padding_quotient,
+            &pp.domain_w,
+            padding_segment_index,
+            pp.segment_size,
+        )?;
+        // In zero-knowledge mode, blind M(X), M(X / w), Q_M(X), and H_M(X).
+        if let Some(blinders) = &blinders {
+            blinders.blind_multiplicity_polynomials_and_quotient(
+                &mut multiplicity_polynomials_and_quotient,
+                &mut g1_affine_hm,
+                pp.g1_affine_list_lw
+                    .get(padding_segment_index * pp.segment_size)?,
+                fr_padding_root,
+                &pp.domain_w,
+                pp.num_table_segments,
+                pp.g1_affine_srs.as_slice()?,
+            );
+        }
+        let MultiplicityPolynomialsAndQuotient {
+            g1_affine_m,
+            g1_affine_m_div_w,
+            g1_affine_qm,
+        } = multiplicity_polynomials_and_quotient;
+        let g1_affine_qt = compute_padding_position_quotient::<P>(
+            &poly_d,
+            &pp.domain_k,
+            fr_padding_root,
+            self.witness.num_queries,
+            pp.g1_affine_srs.as_slice()?,
+        );
+
+        // Round 1-9: In zero-knowledge mode, commit to the masks G_A(X) and
+        // G_B(X), with which the prover hides A(0) in Round 12.
+        let (g1_affine_ga, g1_affine_gb, poly_gb) = match &blinders {
+            Some(blinders) => {
+                blinders.commit_masks(&pp.domain_w, &pp.domain_v, pp.g1_affine_srs.as_slice()?)
+            },
+            None => (
+                P::G1Affine::zero(),
+                P::G1Affine::zero(),
+                DensePolynomial::zero(),
+            ),
+        };
+
+        self.round1 = Some(Round1State {
+            segment_multiplicities,
+            blinders,
+            poly_gb,
+            poly_l_unblinded,
             poly_l,
             poly_ql,
+            poly_l_div_v,
+            poly_eval_list_l,
+            poly_eval_list_d,
             poly_d,
             poly_qd,
+        });
+
+        Ok(Round1Message {
+            g1_affine_m,
+            g1_affine_m_div_w,
+            g1_affine_qm,
+            g1_affine_l,
+            g1_affine_l_div_v,
+            g1_affine_ql,
+            g1_affine_d,
+            g1_affine_qd,
+            g1_affine_hm,
+            g1_affine_qt,
+            g1_affine_ga,
+            g1_affine_gb,
+        })
+    }
+
+    /// Rounds 3-8: the first message of the multi-unity check of D(X).
+    pub fn caulk_round1<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<CaulkRound1Message<P>, Error> {
+        self.round.advance(Round::CaulkRound1)?;
+        let round1 = self
+            .round1
+            .as_ref()
+            .ok_or(Error::UnexpectedRound("round 1"))?;
+
+        let multi_unity_round1 =
+            multi_unity_round1(self.pp, &round1.poly_eval_list_d, &round1.poly_d, rng)?;
+        let message = CaulkRound1Message {
+            g1_u_bar: multi_unity_round1.g1_u_bar,
+            g1_h_2: multi_unity_round1.g1_h_2,
+        };
+        self.multi_unity_round1 = Some(multi_unity_round1);
+
+        Ok(message)
+    }
+
+    /// Rounds 3-8: the second message of the multi-unity check, after the
+    /// challenge alpha.
+    pub fn caulk_round2(
+        &mut self,
+        caulk_alpha: P::ScalarField,
+    ) -> Result<CaulkRound2Message<P>, Error> {
+        self.round.advance(Round::CaulkRound2)?;
+        let multi_unity_round1 = self
+            .multi_unity_round1
+            .take()
+            .ok_or(Error::UnexpectedRound("caulk round 1"))?;
+
+        let multi_unity_round2 = multi_unity_round2(self.pp, multi_unity_round1, caulk_alpha)?;
+        let message = CaulkRound2Message {
+            g1_h_1: multi_unity_round2.g1_h_1,
+        };
+        self.multi_unity_round2 = Some(multi_unity_round2);
+
+        Ok(message)
+    }
+
+    /// Rounds 3-8: the openings of the multi-unity check, after the
+    /// challenge beta.
+    pub fn caulk_round3(
+        &mut self,
+        caulk_beta: P::ScalarField,
+    ) -> Result<CaulkRound3Message<P>, Error> {
+        self.round.advance(Round::CaulkRound3)?;
+        let multi_unity_round2 = self
+            .multi_unity_round2
+            .take()
+            .ok_or(Error::UnexpectedRound("caulk round 2"))?;
+
+        let multi_unity_proof = multi_unity_round3(self.pp, multi_unity_round2, caulk_beta)?;
+
+        Ok(CaulkRound3Message::from(multi_unity_proof))
+    }
+
+    /// Round 10: commits to A(X), B_0(X) and their quotients, after the
+    /// challenges beta, delta, and epsilon of Round 9.
+    pub fn round10(
+        &mut self,
+        beta: P::ScalarField,
+        delta: P::ScalarField,
+        epsilon: P::ScalarField,
+    ) -> Result<Round10Message<P>, Error> {
+        self.round.advance(Round::Round10)?;
+        let (pp, tpp, witness) = (self.pp, self.tpp, self.witness);
+        let round1 = self
+            .round1
+            .as_ref()
+            .ok_or(Error::UnexpectedRound("round 1"))?;
+
+        // Round 10-1: The prover computes A(X) of degree ns-1 in sparse form,
+        // and sends [A(tau)]_1 to the verifier.
+        // Round 10-2: The prover computes [Q_A(tau)]_1 using the SRS and Lemma 4.
+        // Round 10-5: The prover computes A_0(X) = (A(X) - A(0)) / X,
+        // and sends [A_0(tau)]_1 to the verifier.
+        // Round 10-6: If the SRS does not bound the degree of A_0(X) by ns-2,
+        // the prover sends [P_A(tau)]_1 = [A_0(tau) * tau^{t_A}]_1 to the verifier.
+        let (shift_a0, shift_b0) = pp.degree_check_shifts();
+        let mut polynomial_a_and_quotient = compute_polynomial_a_and_quotient(
+            beta,
+            delta,
+            &tpp.adjusted_table_values,
+            &round1.segment_multiplicities,
+            pp.segment_size,
+            &pp.domain_w,
+            &pp.g1_affine_list_lw,
+            &tpp.g1_affine_list_q1,
+            pp.g1_affine_srs.get(0)?,
+            &pp.g1_affine_lw_opening_proofs_at_zero,
+            shift_a0.map(|_| &pp.g1_affine_lw_shifted_opening_proofs_at_zero),
+        )?;
+        // In zero-knowledge mode, blind A(X) and Q_A(X), and mask A_0(X) and
+        // P_A(X) with epsilon * G_A(X).
+        if let Some(blinders) = &round1.blinders {
+            blinders.blind_polynomial_a_and_quotient(
+                &mut polynomial_a_and_quotient,
+                beta,
+                delta,
+                epsilon,
+                tpp.g1_affine_adjusted_t,
+                pp.table_element_size,
+                shift_a0,
+                pp.g1_affine_srs.as_slice()?,
+            );
+        }
+        let PolynomialAAndQuotient {
+            g1_affine_a,
+            g1_affine_qa,
+            g1_affine_a0,
+            g1_affine_pa,
+            g1_affine_ha,
+        } = polynomial_a_and_quotient;
+
+        // Round 10-3: The prover computes B(X) of degree ks-1.
+        // Round 10-4: The prover computes [Q_B(tau)]_1 using the SRS and Lemma 4.
+        // Round 10-5: The prover computes B_0(X) = (B(X) - B(0)) / X,
+        // and sends [B_0(tau)]_1 to the verifier.
+        let mut polynomial_b_and_quotient = compute_polynomial_b_and_quotient(
+            beta,
+            delta,
+            witness,
+            pp.witness_element_size,
+            &pp.domain_v,
+            &pp.domain_coset_v,
+            &round1.poly_eval_list_l,
+            &round1.poly_l_unblinded,
+            pp.g1_affine_srs.as_slice()?,
+        )?;
+        // F(X) + Z_V(X) * r_F(X) committed in the statement.
+        let poly_f = witness.blinded_poly();
+        // In zero-knowledge mode, blind B_0(X) and Q_B(X), and mask B_0(X)
+        // with epsilon * G_B(X).
+        if let Some(blinders) = &round1.blinders {
+            blinders.blind_polynomial_b_and_quotient(
+                &mut polynomial_b_and_quotient,
+                beta,
+                delta,
+                epsilon,
+                &poly_f,
+                &witness.poly_blinder,
+                &round1.poly_l,
+                &pp.domain_v,
+                pp.g1_affine_srs.as_slice()?,
+            );
+        }
+        let PolynomialBAndQuotient {
+            poly_b,
+            poly_qb,
+            poly_b0,
+            g1_affine_qb,
+            g1_affine_b0,
+        } = polynomial_b_and_quotient;
+
+        // Round 10-7: The prover splits B_0(X) = R_B(X) + Z_V(X) * H_B(X), where
+        // H_B(X) is zero unless B_0(X) is blinded, and sends [R_B(tau)]_1,
+        // [H_B(tau)]_1, and [P_B(tau)]_1 = [R_B(tau) * tau^{t_B}]_1 to the
+        // verifier, which bound the degree of R_B(X) by ks-2.
+        let DegreeCheckB0 {
+            g1_affine_rb,
+            g1_affine_hb,
+            g1_affine_pb,
+        } = compute_degree_check_b0::<P>(
+            &poly_b0,
+            &pp.domain_v,
+            pp.g1_affine_srs.as_slice()?,
+            shift_b0,
+        );
+
+        self.round10 = Some(Round10State {
+            epsilon,
+            poly_b,
             poly_b0,
-            witness.poly.clone(),
             poly_qb,
-        ],
-        gamma,
-        eta,
-    );
-
-    Ok(Proof {
-        g1_affine_m,
-        g1_affine_m_div_w,
-        g1_affine_qm,
-        g1_affine_l,
-        g1_affine_l_div_v,
-        g1_affine_ql,
-        g1_affine_d,
-        g1_affine_qd,
-        g1_affine_a,
-        g1_affine_qa,
-        g1_affine_qb,
-        g1_affine_a0,
-        g1_affine_b0,
-        g1_affine_px,
-        g1_affine_hp,
-
-        fr_b0_at_gamma,
-        fr_f_at_gamma,
-        fr_l_at_gamma,
-        fr_a_at_zero,
-        fr_l_at_gamma_div_v,
-        fr_ql_at_gamma,
-        fr_d_at_gamma,
-        fr_qd_at_gamma,
-
-        multi_unity_proof,
-    })
+            poly_f,
+        });
+
+        Ok(Round10Message {
+            g1_affine_a,
+            g1_affine_qa,
+            g1_affine_qb,
+            g1_affine_a0,
+            g1_affine_b0,
+            g1_affine_pa,
+            g1_affine_ha,
+            g1_affine_rb,
+            g1_affine_hb,
+            g1_affine_pb,
+        })
+    }
+
+    /// Round 12: evaluates the polynomials at the challenge gamma of
+    /// Round 11.
+    pub fn round12(&mut self, gamma: P::ScalarField) -> Result<Round12Message<P>, Error> {
+        self.round.advance(Round::Round12)?;
+        let pp = self.pp;
+        let round1 = self
+            .round1
+            .as_ref()
+            .ok_or(Error::UnexpectedRound("round 1"))?;
+        let round10 = self
+            .round10
+            .as_ref()
+            .ok_or(Error::UnexpectedRound("round 10"))?;
+        self.gamma = gamma;
+
+        // Round 12: The prover sends b_{0,gamma} = B_0(gamma), f_{gamma} = F(gamma),
+        // l_{gamma} = L(gamma), a_0' = A(0) + epsilon * G_A(0), l_{gamma,v} =
+        // L(v*gamma), q_{gamma,L} = Q_L(gamma), d_{gamma} = D(gamma),
+        // q_{gamma, D} = Q_D(gamma), and g_{B,gamma} = G_B(gamma) to the verifier.
+        let fr_b0_at_gamma = round10.poly_b0.evaluate(&gamma);
+        let fr_f_at_gamma = round10.poly_f.evaluate(&gamma);
+        let fr_l_at_gamma = round1.poly_l.evaluate(&gamma);
+        // Compute a_0 using sumcheck lemma, and mask it with epsilon * g in
+        // zero-knowledge mode.
+        let mut fr_a_at_zero = {
+            let fr_b_at_zero = round10.poly_b.evaluate(&P::ScalarField::zero());
+            let table_elem_size = pp.num_table_segments * pp.segment_size;
+            let fr_inv_table_elem_size = P::ScalarField::from(table_elem_size as u64)
+                .inverse()
+                .ok_or(Error::FailedToInverseFieldElement)?;
+            let witness_elem_size = pp.num_witness_segments * pp.segment_size;
+            let fr_witness_elem_size = P::ScalarField::from(witness_elem_size as u64);
+
+            fr_b_at_zero * fr_witness_elem_size * fr_inv_table_elem_size
+        };
+        if let Some(blinders) = &round1.blinders {
+            fr_a_at_zero += round10.epsilon * blinders.fr_g;
+        }
+        let fr_gamma_div_v = gamma / pp.domain_v.group_gen;
+        let fr_l_at_gamma_div_v = round1.poly_l.evaluate(&fr_gamma_div_v);
+        let fr_ql_at_gamma = round1.poly_ql.evaluate(&gamma);
+        let fr_d_at_gamma = round1.poly_d.evaluate(&gamma);
+        let fr_qd_at_gamma = round1.poly_qd.evaluate(&gamma);
+        // For a Pedersen statement, the prover also sends r_{gamma} = R(gamma),
+        // with which the verifier removes the blinding from the statement.
+        let fr_r_at_gamma = self
+            .pedersen_blinder
+            .map_or(P::ScalarField::zero(), |blinder| {
+                blinder.poly.evaluate(&gamma)
+            });
+        let fr_gb_at_gamma = round1.poly_gb.evaluate(&gamma);
+
+        Ok(Round12Message {
+            fr_b0_at_gamma,
+            fr_f_at_gamma,
+            fr_l_at_gamma,
+            fr_a_at_zero,
+            fr_l_at_gamma_div_v,
+            fr_ql_at_gamma,
+            fr_d_at_gamma,
+            fr_qd_at_gamma,
+            fr_r_at_gamma,
+            fr_gb_at_gamma,
+        })
+    }
+
+    /// Round 14: the batch opening at gamma, after the challenge eta of
+    /// Round 13.
+    pub fn round14(&mut self, eta: P::ScalarField) -> Result<Round14Message<P>, Error> {
+        self.round.advance(Round::Round14)?;
+        let round1 = self
+            .round1
+            .take()
+            .ok_or(Error::UnexpectedRound("round 1"))?;
+        let round10 = self
+            .round10
+            .take()
+            .ok_or(Error::UnexpectedRound("round 10"))?;
+
+        // Round 14: Compute the commitment of H_P(X)
+        // = (P(X) - p_{gamma}) / (X - gamma),
+        // which is a KZG batch opening proof of the polynomials to be
+        // checked, and send [H_P(tau)]_1 to the verifier.
+        let mut poly_list = vec![
+            round1.poly_l_div_v,
+            round1.poly_l,
+            round1.poly_ql,
+            round1.poly_d,
+            round1.poly_qd,
+            round10.poly_b0,
+        ];
+        let poly_f_index = poly_list.len();
+        poly_list.push(round10.poly_f);
+        poly_list.push(round10.poly_qb);
+        poly_list.push(round1.poly_gb);
+        let (mut g1_affine_hp, fr_eta_powers) = Kzg::<P::G1>::batch_open(
+            self.pp.g1_affine_srs.as_slice()?,
+            &poly_list,
+            self.gamma,
+            eta,
+        );
+        // For a Pedersen statement, the verifier opens
+        // [F(tau) + gamma * R(tau)]_1 - r_{gamma} * [gamma]_1 instead of
+        // [F(tau)]_1, so [gamma * (R(tau) - r_{gamma}) / (tau - gamma)]_1 is
+        // added with the weight of F(X) in the batch.
+        if let Some(blinder) = self.pedersen_blinder {
+            let (_, g1_affine_hr) =
+                Kzg::<P::G1>::open(&self.pp.g1_affine_srs_gamma, &blinder.poly, self.gamma);
+            g1_affine_hp =
+                (g1_affine_hp + g1_affine_hr.mul(fr_eta_powers[poly_f_index])).into_affine();
+        }
+
+        Ok(Round14Message { g1_affine_hp })
+    }
+}
+
+// Random polynomials whose multiples of the vanishing polynomials blind the
+// prover polynomials in zero-knowledge mode without changing their
+// evaluations on domain W, V, or K. Each blinder has one coefficient per
+// point outside the domain at which the polynomial is revealed: M(X) at tau
+// and tau / w, L(X) at tau, tau / v, gamma and gamma / v, D(X) at tau and
+// gamma, and at tau^d and alpha in the multi-unity check. A(X) is revealed at
+// tau only, since A(0) is masked with G_A(X) (see `commit_masks`).
+struct Blinders<P: Pairing> {
+    fr_r_m0: P::ScalarField, // r_M(X) = r_{M,0} + r_{M,1} * X
+    fr_r_m1: P::ScalarField,
+    fr_r_a: P::ScalarField,                    // r_A, a constant
+    fr_g: P::ScalarField,                      // g = G_A(0)
+    fr_s_a: P::ScalarField,                    // s_A, a constant
+    fr_j_a: P::ScalarField,                    // j_A, a constant
+    fr_s_b: P::ScalarField,                    // s_B, a constant
+    poly_j_b: DensePolynomial<P::ScalarField>, // J_B(X) of degree 1
+    poly_r_l: DensePolynomial<P::ScalarField>, // r_L(X) of degree 3
+    poly_r_d: DensePolynomial<P::ScalarField>, // r_D(X) of degree 3
+    poly_r_b: DensePolynomial<P::ScalarField>, // r_B(X) of degree 1
+}
+
+impl<P: Pairing> Blinders<P> {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut rand_poly = |degree: usize| {
+            DensePolynomial::from_coefficients_vec(
+                (0..=degree).map(|_| P::ScalarField::rand(rng)).collect(),
+            )
+        };
+        let poly_j_b = rand_poly(1);
+        let poly_r_l = rand_poly(3);
+        let poly_r_d = rand_poly(3);
+        let poly_r_b = rand_poly(1);
+
+        Self {
+            fr_r_m0: P::ScalarField::rand(rng),
+            fr_r_m1: P::ScalarField::rand(rng),
+            fr_r_a: P::ScalarField::rand(rng),
+            fr_g: P::ScalarField::rand(rng),
+            fr_s_a: P::ScalarField::rand(rng),
+            fr_j_a: P::ScalarField::rand(rng),
+            fr_s_b: P::ScalarField::rand(rng),
+            poly_j_b,
+            poly_r_l,
+            poly_r_d,
+            poly_r_b,
+        }
+    }
+
+    // M'(X) = M(X) + Z_W(X) * r_M(X),
+    // M'(X / w) = M(X / w) + Z_W(X) * r_M(X / w), since Z_W(X / w) = Z_W(X),
+    // Q_M'(X) = Q_M(X) + r_{M,1} * (1 - w^{-1}) * X * (X^n - 1), and for the
+    // opening at z = w^a, with Z_W(X) / (X - z) = (N / z) * L^W_a(X),
+    // H_M'(X) = H_M(X) + (N * r_{M,0} / z + N * r_{M,1}) * L^W_a(X)
+    //   + r_{M,1} * Z_W(X).
+    // The commitments are sums of a few points of the SRS, so that the
+    // prover stays sublinear in the table size.
+    #[allow(clippy::too_many_arguments)]
+    fn blind_multiplicity_polynomials_and_quotient(
+        &self,
+        polys: &mut MultiplicityPolynomialsAndQuotient<P>,
+        g1_affine_hm: &mut P::G1Affine,
+        g1_affine_lw_padding: P::G1Affine,
+        fr_padding_root: P::ScalarField,
+        domain_w: &Radix2EvaluationDomain<P::ScalarField>,
+        num_table_segments: usize,
+        g1_affine_srs: &[P::G1Affine],
+    ) {
+        let table_element_size = domain_w.size();
+        let fr_table_element_size = P::ScalarField::from(table_element_size as u64);
+        let g1_zw = g1_affine_srs[table_element_size].into_group() - g1_affine_srs[0];
+        let g1_x_mul_zw = g1_affine_srs[table_element_size + 1].into_group() - g1_affine_srs[1];
+        let g1_x_mul_tau_pow_n_sub_one =
+            g1_affine_srs[num_table_segments + 1].into_group() - g1_affine_srs[1];
+        let fr_hm_lw_coeff =
+            fr_table_element_size * (self.fr_r_m0 / fr_padding_root + self.fr_r_m1);
+
+        let g1_list_blinding = P::G1::normalize_batch(&[
+            polys.g1_affine_m + g1_zw * self.fr_r_m0 + g1_x_mul_zw * self.fr_r_m1,
+            polys.g1_affine_m_div_w
+                + g1_zw * self.fr_r_m0
+                + g1_x_mul_zw * (self.fr_r_m1 * domain_w.group_gen_inv),
+            polys.g1_affine_qm
+                + g1_x_mul_tau_pow_n_sub_one
+                    * (self.fr_r_m1 * (P::ScalarField::one() - domain_w.group_gen_inv)),
+            *g1_affine_hm + g1_affine_lw_padding * fr_hm_lw_coeff + g1_zw * self.fr_r_m1,
+        ]);
+        polys.g1_affine_m = g1_list_blinding[0];
+        polys.g1_affine_m_div_w = g1_list_blinding[1];
+        polys.g1_affine_qm = g1_list_blinding[2];
+        *g1_affine_hm = g1_list_blinding[3];
+    }
+
+    // L'(X) = L(X) + Z_V(X) * r_L(X),
+    // L'(X / v) = L(X / v) + Z_V(X) * r_L(X / v), since Z_V(X / v) = Z_V(X),
+    // D'(X) = D(X) + Z_K(X) * r_D(X),
+    // Q_L'(X) = Q_L(X) + (X^k - 1) * (r_L(X) - w * r_L(X / v)),
+    // Q_D'(X) = Q_D(X) + (Z_V(X) / Z_K(X)) * r_L(X) - r_D(X).
+    fn blind_index_polynomials_and_quotients(
+        &self,
+        polys: &mut IndexPolynomialsAndQuotients<P>,
+        domain_v: &Radix2EvaluationDomain<P::ScalarField>,
+        domain_k: &Radix2EvaluationDomain<P::ScalarField>,
+        domain_generator_w: P::ScalarField,
+        g1_affine_srs: &[P::G1Affine],
+    ) -> Result<(), Error> {
+        let poly_zv_mul_r_l = self.poly_r_l.mul_by_vanishing_poly(*domain_v);
+        let poly_coeff_list_r_l_div_v: Vec<P::ScalarField> = self
+            .poly_r_l
+            .coeffs
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| c * domain_v.group_gen_inv.pow([i as u64]))
+            .collect();
+        let poly_r_l_div_v = DensePolynomial::from_coefficients_vec(poly_coeff_list_r_l_div_v);
+        let poly_zv_mul_r_l_div_v = poly_r_l_div_v.mul_by_vanishing_poly(*domain_v);
+        let poly_zk_mul_r_d = self.poly_r_d.mul_by_vanishing_poly(*domain_k);
+        let poly_ql_blinding = (&self.poly_r_l - &(&poly_r_l_div_v * domain_generator_w))
+            .mul_by_vanishing_poly(*domain_k);
+        let poly_qd_blinding =
+            &divide_by_vanishing_poly_checked::<P>(domain_k, &poly_zv_mul_r_l)? - &self.poly_r_d;
+
+        let g1_list_blinding = P::G1::normalize_batch(&[
+            polys.g1_affine_l + Kzg::<P::G1>::commit(g1_affine_srs, &poly_zv_mul_r_l),
+            polys.g1_affine_l_div_v + Kzg::<P::G1>::commit(g1_affine_srs, &poly_zv_mul_r_l_div_v),
+            polys.g1_affine_d + Kzg::<P::G1>::commit(g1_affine_srs, &poly_zk_mul_r_d),
+            polys.g1_affine_ql + Kzg::<P::G1>::commit(g1_affine_srs, &poly_ql_blinding),
+            polys.g1_affine_qd + Kzg::<P::G1>::commit(g1_affine_srs, &poly_qd_blinding),
+        ]);
+        polys.g1_affine_l = g1_list_blinding[0];
+        polys.g1_affine_l_div_v = g1_list_blinding[1];
+        polys.g1_affine_d = g1_list_blinding[2];
+        polys.g1_affine_ql = g1_list_blinding[3];
+        polys.g1_affine_qd = g1_list_blinding[4];
+
+        polys.poly_l += &poly_zv_mul_r_l;
+        polys.poly_l_div_v += &poly_zv_mul_r_l_div_v;
+        polys.poly_d += &poly_zk_mul_r_d;
+        polys.poly_ql += &poly_ql_blinding;
+        polys.poly_qd += &poly_qd_blinding;
+
+        Ok(())
+    }
+
+    // Returns [G_A(tau)]_1, [G_B(tau)]_1, and G_B(X) for the masks
+    // G_A(X) = g + s_A * X + j_A * X * Z_W(X) and
+    // G_B(X) = (N / ks) * g + s_B * X + X * Z_V(X) * J_B(X),
+    // which both sum to N * g over their domains. The verifier checks the
+    // sumcheck on A(X) + epsilon * G_A(X) and B(X) + epsilon * G_B(X), whose
+    // sums are still equal, so the prover only reveals a_0' = A(0) + epsilon * g.
+    // As the masks are committed before epsilon, a prover whose sums of A(X)
+    // and B(X) differ passes with probability 1 / |F|.
+    fn commit_masks(
+        &self,
+        domain_w: &Radix2EvaluationDomain<P::ScalarField>,
+        domain_v: &Radix2EvaluationDomain<P::ScalarField>,
+        g1_affine_srs: &[P::G1Affine],
+    ) -> (P::G1Affine, P::G1Affine, DensePolynomial<P::ScalarField>) {
+        let table_element_size = domain_w.size();
+        let g1_ga = g1_affine_srs[0] * self.fr_g
+            + g1_affine_srs[1] * (self.fr_s_a - self.fr_j_a)
+            + g1_affine_srs[table_element_size + 1] * self.fr_j_a;
+
+        let fr_gb_at_zero =
+            self.fr_g * P::ScalarField::from(table_element_size as u64) * domain_v.size_inv;
+        let poly_zv_mul_j_b = self.poly_j_b.mul_by_vanishing_poly(*domain_v);
+        let mut poly_gb = DensePolynomial::from_coefficients_vec(
+            [&[fr_gb_at_zero], poly_zv_mul_j_b.coeffs.as_slice()].concat(),
+        );
+        poly_gb.coeffs[1] += self.fr_s_b;
+        let g1_gb = Kzg::<P::G1>::commit(g1_affine_srs, &poly_gb);
+
+        let g1_affine_list = P::G1::normalize_batch(&[g1_ga, g1_gb]);
+
+        (g1_affine_list[0], g1_affine_list[1], poly_gb)
+    }
+
+    // A'(X) = A(X) + r_A * Z_W(X), and with M'(X) = M(X) + Z_W(X) * r_M(X),
+    // Q_A'(X) = Q_A(X) + r_A * (T'(X) + beta + delta * X) - r_M(X).
+    // A(0) is masked with G_A(X):
+    // A'(X) + epsilon * G_A(X) - a_0' = X * R_A(X) + Z_W(X) * H_A(X), with
+    // a_0' = A(0) + epsilon * g, R_A(X) = A_0(X) + epsilon * s_A, and
+    // H_A(X) = r_A + epsilon * j_A * X.
+    #[allow(clippy::too_many_arguments)]
+    fn blind_polynomial_a_and_quotient(
+        &self,
+        polys: &mut PolynomialAAndQuotient<P>,
+        beta: P::ScalarField,
+        delta: P::ScalarField,
+        epsilon: P::ScalarField,
+        g1_affine_adjusted_t: P::G1Affine,
+        table_element_size: usize,
+        shift_a0: Option<usize>,
+        g1_affine_srs: &[P::G1Affine],
+    ) {
+        let g1_zw = g1_affine_srs[table_element_size].into_group() - g1_affine_srs[0];
+        let fr_epsilon_mul_s_a = epsilon * self.fr_s_a;
+        let g1_pa_blinding = match shift_a0 {
+            Some(shift_a0) => g1_affine_srs[shift_a0] * fr_epsilon_mul_s_a,
+            None => P::G1::zero(),
+        };
+
+        let g1_list_blinding = P::G1::normalize_batch(&[
+            polys.g1_affine_a + g1_zw * self.fr_r_a,
+            polys.g1_affine_qa
+                + (g1_affine_adjusted_t.into_group()
+                    + g1_affine_srs[0] * beta
+                    + g1_affine_srs[1] * delta)
+                    * self.fr_r_a
+                - g1_affine_srs[0] * self.fr_r_m0
+                - g1_affine_srs[1] * self.fr_r_m1,
+            polys.g1_affine_a0 + g1_affine_srs[0] * fr_epsilon_mul_s_a,
+            polys.g1_affine_pa + g1_pa_blinding,
+            g1_affine_srs[0] * self.fr_r_a + g1_affine_srs[1] * (epsilon * self.fr_j_a),
+        ]);
+        polys.g1_affine_a = g1_list_blinding[0];
+        polys.g1_affine_qa = g1_list_blinding[1];
+        polys.g1_affine_a0 = g1_list_blinding[2];
+        polys.g1_affine_pa = g1_list_blinding[3];
+        polys.g1_affine_ha = g1_list_blinding[4];
+    }
+
+    // B'(X) = B(X) + Z_V(X) * X * r_B(X) keeps B'(0) = B(0) for the sumcheck.
+    // Given the blinded F'(X) and L'(X),
+    // Q_B'(X) = Q_B(X) + X * r_B(X) * (beta + F'(X) + delta * L'(X))
+    //   + B(X) * (r_F(X) + delta * r_L(X)).
+    // B_0(X) is masked with G_B(X), so that B'(X) = b_0' + X * B_0'(X)
+    // - epsilon * G_B(X) for b_0' = (N / ks) * a_0', with
+    // B_0'(X) = B_0(X) + epsilon * s_B + Z_V(X) * (r_B(X) + epsilon * J_B(X)).
+    #[allow(clippy::too_many_arguments)]
+    fn blind_polynomial_b_and_quotient(
+        &self,
+        polys: &mut PolynomialBAndQuotient<P>,
+        beta: P::ScalarField,
+        delta: P::ScalarField,
+        epsilon: P::ScalarField,
+        poly_f: &DensePolynomial<P::ScalarField>,
+        poly_r_f: &DensePolynomial<P::ScalarField>,
+        poly_l: &DensePolynomial<P::ScalarField>,
+        domain_v: &Radix2EvaluationDomain<P::ScalarField>,
+        g1_affine_srs: &[P::G1Affine],
+    ) {
+        let poly_b0_blinding = &(&self.poly_r_b + &(&self.poly_j_b * epsilon))
+            .mul_by_vanishing_poly(*domain_v)
+            + &DensePolynomial::from_coefficients_vec(vec![epsilon * self.fr_s_b]);
+        let poly_x_mul_r_b = DensePolynomial::from_coefficients_vec(
+            [&[P::ScalarField::zero()], self.poly_r_b.coeffs.as_slice()].concat(),
+        );
+        let poly_beta_add_f_add_delta_mul_l =
+            &(poly_f + &(poly_l * delta)) + &DensePolynomial::from_coefficients_vec(vec![beta]);
+        let poly_r_f_add_delta_mul_r_l = poly_r_f + &(&self.poly_r_l * delta);
+        let poly_qb_blinding = &(&poly_x_mul_r_b * &poly_beta_add_f_add_delta_mul_l)
+            + &(&polys.poly_b * &poly_r_f_add_delta_mul_r_l);
+
+        let g1_list_blinding = P::G1::normalize_batch(&[
+            polys.g1_affine_qb + Kzg::<P::G1>::commit(g1_affine_srs, &poly_qb_blinding),
+            polys.g1_affine_b0 + Kzg::<P::G1>::commit(g1_affine_srs, &poly_b0_blinding),
+        ]);
+        polys.g1_affine_qb = g1_list_blinding[0];
+        polys.g1_affine_b0 = g1_list_blinding[1];
+
+        polys.poly_qb += &poly_qb_blinding;
+        polys.poly_b0 += &poly_b0_blinding;
+    }
 }
 
 fn compute_segment_multiplicities(
@@ -349,11 +1535,11 @@ struct MultiplicityPolynomialsAndQuotient<P: Pairing> {
 
 fn compute_multiplicity_polynomials_and_quotient<P: Pairing>(
     segment_multiplicities: &DashMap<usize, usize>,
-    g1_affine_list_lw: &[P::G1Affine],
-    g1_affine_list_q3: &[P::G1Affine],
+    g1_affine_list_lw: &LazyPoints<P::G1Affine>,
+    g1_affine_list_q3: &LazyPoints<P::G1Affine>,
     segment_size: usize,
     table_element_size: usize,
-) -> MultiplicityPolynomialsAndQuotient<P> {
+) -> Result<MultiplicityPolynomialsAndQuotient<P>, Error> {
     // Compute partial [M(tau)]_1, [M(tau / w)]_1, and [Q_M(tau)]_1 in parallel.
     let (g1_m, g1_m_div_w, g1_qm) = segment_multiplicities
         .par_iter()
@@ -367,36 +1553,114 @@ fn compute_multiplicity_polynomials_and_quotient<P: Pairing>(
             let fr_mul = P::ScalarField::from(m as u64);
             for elem_index in segment_element_indices {
                 // Linear combination of [L^W_i(tau)]_1.
-                g1_m_partial.add_assign(g1_affine_list_lw[elem_index].mul(fr_mul));
+                g1_m_partial.add_assign(g1_affine_list_lw.get(elem_index)?.mul(fr_mul));
                 // Linear combination of [L^W_i(tau / w)]_1.
                 // L^W_i(tau / w) = L^W_{i+1}(tau).
                 let shifted_elem_index = (elem_index + 1) % table_element_size;
-                g1_m_div_w_partial.add_assign(g1_affine_list_lw[shifted_elem_index].mul(fr_mul));
+                g1_m_div_w_partial
+                    .add_assign(g1_affine_list_lw.get(shifted_elem_index)?.mul(fr_mul));
                 // Linear combination of q_{i, 3}.
-                g1_qm_partial.add_assign(g1_affine_list_q3[elem_index].mul(fr_mul));
+                g1_qm_partial.add_assign(g1_affine_list_q3.get(elem_index)?.mul(fr_mul));
                 // Linear combination of q_{i, 4}.
                 // q_{i, 4} is equivalent to shift q_{i, 3} to the left by 1.
                 let shifted_elem_index = (elem_index + 1) % table_element_size;
                 // Negate the coefficient of multiplicity.
-                g1_qm_partial.add_assign(g1_affine_list_q3[shifted_elem_index].mul(-fr_mul));
+                g1_qm_partial.add_assign(g1_affine_list_q3.get(shifted_elem_index)?.mul(-fr_mul));
             }
 
-            (g1_m_partial, g1_m_div_w_partial, g1_qm_partial)
+            Ok((g1_m_partial, g1_m_div_w_partial, g1_qm_partial))
         })
-        .reduce(
+        .try_reduce(
             || (P::G1::zero(), P::G1::zero(), P::G1::zero()),
             |(g1_m1, g1_m_div_w1, g1_qm1), (g1_m2, g1_m_div_w2, g1_qm2)| {
-                (g1_m1 + g1_m2, g1_m_div_w1 + g1_m_div_w2, g1_qm1 + g1_qm2)
+                Ok((g1_m1 + g1_m2, g1_m_div_w1 + g1_m_div_w2, g1_qm1 + g1_qm2))
             },
-        );
+        )?;
 
     let g1_affine_list = P::G1::normalize_batch(&[g1_m, g1_m_div_w, g1_qm]);
 
-    MultiplicityPolynomialsAndQuotient {
+    Ok(MultiplicityPolynomialsAndQuotient {
         g1_affine_m: g1_affine_list[0],
         g1_affine_m_div_w: g1_affine_list[1],
         g1_affine_qm: g1_affine_list[2],
-    }
+    })
+}
+
+// Computes [H_M(tau)]_1 = [(M(tau) - m_p) / (tau - w^a)]_1, the opening of
+// M(X) at the first element a = ps of the padding segment p, sparsely from
+// the multiplicities. For j != a,
+// L^W_j(X) / (X - w^a) = (L^W_j(X) - w^{j - a} * L^W_a(X)) / (w^j - w^a),
+// and for j = a, (L^W_a(X) - 1) / (X - w^a) is a point of the public
+// parameters.
+fn compute_padding_multiplicity_opening<P: Pairing>(
+    segment_multiplicities: &DashMap<usize, usize>,
+    g1_affine_list_lw: &LazyPoints<P::G1Affine>,
+    g1_affine_lw_padding_quotient: P::G1Affine,
+    domain_w: &Radix2EvaluationDomain<P::ScalarField>,
+    padding_segment_index: usize,
+    segment_size: usize,
+) -> Result<P::G1Affine, Error> {
+    let padding_elem_index = padding_segment_index * segment_size;
+    let fr_w_pow_a = domain_w.element(padding_elem_index);
+    let fr_inv_w_pow_a = fr_w_pow_a
+        .inverse()
+        .ok_or(Error::FailedToInverseFieldElement)?;
+
+    // Compute the partial sums of the terms with [L^W_j(tau)]_1 for j != a,
+    // and the coefficient of [L^W_a(tau)]_1, in parallel.
+    let (g1_hm, fr_la_coeff) = segment_multiplicities
+        .par_iter()
+        .map(|entry| {
+            let i = *entry.key();
+            let fr_mul = P::ScalarField::from(*entry.value() as u64);
+            let mut g1_hm_partial = P::G1::zero();
+            let mut fr_la_coeff_partial = P::ScalarField::zero();
+            for elem_index in i * segment_size..(i + 1) * segment_size {
+                if elem_index == padding_elem_index {
+                    g1_hm_partial.add_assign(g1_affine_lw_padding_quotient.mul(fr_mul));
+                    continue;
+                }
+                let fr_w_pow_j = domain_w.element(elem_index);
+                let fr_coeff = fr_mul
+                    * (fr_w_pow_j - fr_w_pow_a)
+                        .inverse()
+                        .ok_or(Error::FailedToInverseFieldElement)?;
+                g1_hm_partial.add_assign(g1_affine_list_lw.get(elem_index)?.mul(fr_coeff));
+                fr_la_coeff_partial += fr_coeff * fr_w_pow_j * fr_inv_w_pow_a;
+            }
+
+            Ok((g1_hm_partial, fr_la_coeff_partial))
+        })
+        .try_reduce(
+            || (P::G1::zero(), P::ScalarField::zero()),
+            |(g1_hm1, fr_la_coeff1), (g1_hm2, fr_la_coeff2)| {
+                Ok((g1_hm1 + g1_hm2, fr_la_coeff1 + fr_la_coeff2))
+            },
+        )?;
+    let g1_hm = g1_hm - g1_affine_list_lw.get(padding_elem_index)?.mul(fr_la_coeff);
+
+    Ok(g1_hm.into_affine())
+}
+
+// Computes [Q_T(tau)]_1 for Q_T(X) = (D(X) - w^{ps}) * S_q(X) / Z_K(X), where
+// S_q(X) is one on the padding queries v^{is}, i >= q, of domain K and zero
+// on the others. The division is exact if every padding query uses the
+// padding segment p.
+fn compute_padding_position_quotient<P: Pairing>(
+    poly_d: &DensePolynomial<P::ScalarField>,
+    domain_k: &Radix2EvaluationDomain<P::ScalarField>,
+    fr_padding_root: P::ScalarField,
+    num_queries: usize,
+    g1_affine_srs: &[P::G1Affine],
+) -> P::G1Affine {
+    let mut poly_eval_list_s = vec![P::ScalarField::zero(); domain_k.size()];
+    poly_eval_list_s[num_queries..].fill(P::ScalarField::one());
+    let poly_s = DensePolynomial::from_coefficients_vec(domain_k.ifft(&poly_eval_list_s));
+    let poly_d_sub_padding_root =
+        poly_d - &DensePolynomial::from_coefficients_vec(vec![fr_padding_root]);
+    let (poly_qt, _) = (&poly_d_sub_padding_root * &poly_s).divide_by_vanishing_poly(*domain_k);
+
+    Kzg::<P::G1>::commit(g1_affine_srs, &poly_qt).into_affine()
 }
 
 // Index polynomials and the quotients,
@@ -419,14 +1683,15 @@ struct IndexPolynomialsAndQuotients<P: Pairing> {
 
 // Compute the commitments of [L(tau)]_1, [L(tau*v)]_1, [D(tau)]_1,
 // [Q_L(tau)]_1, and [Q_D(tau)]_1.
+#[allow(clippy::too_many_arguments)]
 fn compute_index_polynomials_and_quotients<P: Pairing>(
     domain_k: &Radix2EvaluationDomain<P::ScalarField>,
     domain_v: &Radix2EvaluationDomain<P::ScalarField>,
     domain_coset_v: &Radix2EvaluationDomain<P::ScalarField>,
-    roots_of_unity_w: &[P::ScalarField],
+    domain_w: &Radix2EvaluationDomain<P::ScalarField>,
     roots_of_unity_v: &[P::ScalarField],
     partial_inv_zk_at_coset_v_values: &[P::ScalarField],
-    g1_affine_list_lv: &[P::G1Affine],
+    g1_affine_list_lv: &LazyPoints<P::G1Affine>,
     g1_affine_srs: &[P::G1Affine],
     queried_segment_indices: &[usize],
     domain_generator_w: P::ScalarField,
@@ -448,7 +1713,7 @@ fn compute_index_polynomials_and_quotients<P: Pairing>(
             let segment_element_indices = segment_element_start..segment_element_end;
 
             for (j, elem_index) in segment_element_indices.enumerate() {
-                let root_of_unity_w = roots_of_unity_w[elem_index];
+                let root_of_unity_w = domain_w.element(elem_index);
                 partial_poly_eval_list_l.push(root_of_unity_w);
 
                 // Compute witness_element_index based on segment position and element position.
@@ -456,7 +1721,7 @@ fn compute_index_polynomials_and_quotients<P: Pairing>(
 
                 // Linear combination of [L^V_i(tau)]_1.
                 partial_g1_l
-                    .add_assign(g1_affine_list_lv[witness_element_index].mul(root_of_unity_w));
+                    .add_assign(g1_affine_list_lv.get(witness_element_index)?.mul(root_of_unity_w));
 
                 // Linear combination of [L^V_i(tau / v)]_1.
                 // L^V_i(tau / v) = L^V_{i+1}(tau).
@@ -464,32 +1729,34 @@ fn compute_index_polynomials_and_quotients<P: Pairing>(
                 // to get [L^V_i(tau / v)]_1.
                 let shifted_witness_elem_index = (witness_element_index + 1) % witness_size;
                 partial_g1_l_div_v.add_assign(
-                    g1_affine_list_lv[shifted_witness_elem_index].mul(root_of_unity_w),
+                    g1_affine_list_lv
+                        .get(shifted_witness_elem_index)?
+                        .mul(root_of_unity_w),
                 );
             }
 
             // Push the first root of unity for this segment to poly_eval_list_d.
-            let root_of_unity_w = roots_of_unity_w[seg_index * segment_size];
+            let root_of_unity_w = domain_w.element(seg_index * segment_size);
             partial_poly_eval_list_d.push(root_of_unity_w);
 
             // Return the partial results.
-            (
+            Ok((
                 partial_poly_eval_list_l,
                 partial_g1_l,
                 partial_g1_l_div_v,
                 partial_poly_eval_list_d,
-            )
+            ))
         })
-        .reduce(
+        .try_reduce(
             || (Vec::new(), P::G1::zero(), P::G1::zero(), Vec::new()), // Initial accumulator
             |mut acc, (part_l, part_g1_l, part_g1_l_div_v, part_d)| {
                 acc.0.extend(part_l); // Combine poly_eval_list_l
                 acc.1.add_assign(part_g1_l); // Combine g1_l
                 acc.2.add_assign(part_g1_l_div_v); // Combine g1_l_div_v
                 acc.3.extend(part_d); // Combine poly_eval_list_d
-                acc
+                Ok(acc)
             },
-        );
+        )?;
 
     let poly_coeff_list_d = domain_k.ifft(&poly_eval_list_d);
     let poly_d = DensePolynomial::from_coefficients_vec(poly_coeff_list_d);
@@ -519,7 +1786,7 @@ fn compute_index_polynomials_and_quotients<P: Pairing>(
     let mut poly_ql = &poly_l - &poly_w_mul_l_div_v;
     poly_ql = poly_ql.mul(&poly_x_pow_k_sub_one);
     let poly_ql = divide_by_vanishing_poly_checked::<P>(domain_v, &poly_ql)?;
-    let g1_affine_ql = Kzg::<P::G1>::commit(&g1_affine_srs, &poly_ql).into_affine();
+    let g1_affine_ql = Kzg::<P::G1>::commit(g1_affine_srs, &poly_ql).into_affine();
 
     // Compute Q_D s.t. L(X) - D(X) = Z_K(X) * Q_D(X).
     let mut poly_qd = &poly_l - &poly_d;
@@ -536,7 +1803,7 @@ fn compute_index_polynomials_and_quotients<P: Pairing>(
         .collect::<Vec<_>>();
     let poly_coeff_list_qd = domain_coset_v.ifft(&poly_coset_eval_list_qd);
     let poly_qd = DensePolynomial::from_coefficients_vec(poly_coeff_list_qd);
-    let g1_affine_qd = Kzg::<P::G1>::commit(&g1_affine_srs, &poly_qd).into_affine();
+    let g1_affine_qd = Kzg::<P::G1>::commit(g1_affine_srs, &poly_qd).into_affine();
 
     Ok(IndexPolynomialsAndQuotients {
         g1_affine_l: g1_l.into_affine(),
@@ -558,81 +1825,101 @@ struct PolynomialAAndQuotient<P: Pairing> {
     g1_affine_a: P::G1Affine,
     g1_affine_qa: P::G1Affine,
     g1_affine_a0: P::G1Affine,
-    sparse_poly_eval_list_a: DashMap<usize, P::ScalarField>,
+    g1_affine_pa: P::G1Affine,
+    g1_affine_ha: P::G1Affine,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compute_polynomial_a_and_quotient<P: Pairing>(
     beta: P::ScalarField,
     delta: P::ScalarField,
     table_values: &[P::ScalarField],
     segment_multiplicities: &DashMap<usize, usize>,
     segment_size: usize,
-    roots_of_unity_w: &[P::ScalarField],
-    g1_affine_lw_list: &[P::G1Affine],
+    domain_w: &Radix2EvaluationDomain<P::ScalarField>,
+    g1_affine_lw_list: &LazyPoints<P::G1Affine>,
     g1_affine_q1_list: &[P::G1Affine],
-    g1_affine_q2_list: &[P::G1Affine],
-    g1_affine_lw_opening_proofs_at_zero: &[P::G1Affine],
+    g1_affine_generator: P::G1Affine,
+    g1_affine_lw_opening_proofs_at_zero: &LazyPoints<P::G1Affine>,
+    g1_affine_lw_shifted_opening_proofs_at_zero: Option<&LazyPoints<P::G1Affine>>,
 ) -> Result<PolynomialAAndQuotient<P>, Error> {
     let sparse_poly_eval_list_a = DashMap::<usize, P::ScalarField>::default();
 
-    // Parallel computation of g1_a and g1_qa
-    let (g1_a, g1_qa) = segment_multiplicities
+    // Parallel computation of g1_a and g1_qa.
+    // q_{i, 2} = [w^i / ns]_1 is a multiple of the generator, so instead of
+    // summing delta * a_i * q_{i, 2}, the scalars delta * a_i * w^i are summed
+    // and multiplied by [1 / ns]_1 once.
+    let (g1_a, mut g1_qa, fr_q2_scalar) = segment_multiplicities
         .par_iter()
         .try_fold(
-            || (P::G1::zero(), P::G1::zero()),
-            |(mut g1_a_partial, mut g1_qa_partial), entry| {
+            || (P::G1::zero(), P::G1::zero(), P::ScalarField::zero()),
+            |(mut g1_a_partial, mut g1_qa_partial, mut fr_q2_scalar_partial), entry| {
                 let segment_index = *entry.key();
                 let multiplicity = *entry.value();
 
                 let segment_element_indices =
                     segment_index * segment_size..(segment_index + 1) * segment_size;
                 for elem_index in segment_element_indices {
-                    let fr_a_i =
-                        (beta + table_values[elem_index] + delta * roots_of_unity_w[elem_index])
-                            .inverse()
-                            .ok_or(Error::FailedToInverseFieldElement)?
-                            * P::ScalarField::from(multiplicity as u64);
+                    let fr_w_i = domain_w.element(elem_index);
+                    let fr_a_i = (beta + table_values[elem_index] + delta * fr_w_i)
+                        .inverse()
+                        .ok_or(Error::FailedToInverseFieldElement)?
+                        * P::ScalarField::from(multiplicity as u64);
 
                     // Insert into DashMap (thread-safe)
                     sparse_poly_eval_list_a.insert(elem_index, fr_a_i);
 
                     // Accumulate partial G1 points
-                    g1_a_partial.add_assign(g1_affine_lw_list[elem_index].mul(fr_a_i));
+                    g1_a_partial.add_assign(g1_affine_lw_list.get(elem_index)?.mul(fr_a_i));
                     g1_qa_partial.add_assign(g1_affine_q1_list[elem_index].mul(fr_a_i));
-                    g1_qa_partial.add_assign(g1_affine_q2_list[elem_index].mul(delta.mul(fr_a_i)));
+                    fr_q2_scalar_partial += fr_a_i * fr_w_i;
                 }
 
-                Ok((g1_a_partial, g1_qa_partial))
+                Ok((g1_a_partial, g1_qa_partial, fr_q2_scalar_partial))
             },
         )
         .try_reduce(
-            || (P::G1::zero(), P::G1::zero()),
-            |(g1_a1, g1_qa1), (g1_a2, g1_qa2)| {
+            || (P::G1::zero(), P::G1::zero(), P::ScalarField::zero()),
+            |(g1_a1, g1_qa1, fr_q2_scalar1), (g1_a2, g1_qa2, fr_q2_scalar2)| {
                 let mut combined_g1_a = g1_a1;
                 combined_g1_a.add_assign(&g1_a2);
                 let mut combined_g1_qa = g1_qa1;
                 combined_g1_qa.add_assign(&g1_qa2);
-                Ok((combined_g1_a, combined_g1_qa))
+                Ok((combined_g1_a, combined_g1_qa, fr_q2_scalar1 + fr_q2_scalar2))
             },
         )?;
+    g1_qa.add_assign(g1_affine_generator.mul(delta * fr_q2_scalar * domain_w.size_inv));
 
-    // Parallel computation of g1_a0
-    let g1_a0 = sparse_poly_eval_list_a
+    // Parallel computation of g1_a0, and of g1_pa if the degree check of
+    // A_0(X) is needed.
+    let (g1_a0, g1_pa) = sparse_poly_eval_list_a
         .par_iter()
         .map(|entry| {
             let i = *entry.key();
             let a_i = *entry.value();
 
-            g1_affine_lw_opening_proofs_at_zero[i].mul(a_i)
+            let g1_a0_i = g1_affine_lw_opening_proofs_at_zero.get(i)?.mul(a_i);
+            let g1_pa_i = match g1_affine_lw_shifted_opening_proofs_at_zero {
+                Some(g1_affine_list) => g1_affine_list.get(i)?.mul(a_i),
+                None => P::G1::zero(),
+            };
+
+            Ok((g1_a0_i, g1_pa_i))
         })
-        .reduce(|| P::G1::zero(), |acc, x| acc + x)
-        .into_affine();
+        .try_reduce(
+            || (P::G1::zero(), P::G1::zero()),
+            |(g1_a0_1, g1_pa_1), (g1_a0_2, g1_pa_2)| Ok((g1_a0_1 + g1_a0_2, g1_pa_1 + g1_pa_2)),
+        )?;
+
+    let g1_affine_list = P::G1::normalize_batch(&[g1_a, g1_qa, g1_a0, g1_pa]);
 
     Ok(PolynomialAAndQuotient {
-        g1_affine_a: g1_a.into_affine(),
-        g1_affine_qa: g1_qa.into_affine(),
-        g1_affine_a0: g1_a0,
-        sparse_poly_eval_list_a,
+        g1_affine_a: g1_affine_list[0],
+        g1_affine_qa: g1_affine_list[1],
+        g1_affine_a0: g1_affine_list[2],
+        g1_affine_pa: g1_affine_list[3],
+        // H_A(X) is zero unless A(X) is blinded.
+        g1_affine_ha: P::G1Affine::zero(),
     })
 }
 
@@ -644,6 +1931,7 @@ struct PolynomialBAndQuotient<P: Pairing> {
     g1_affine_b0: P::G1Affine,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compute_polynomial_b_and_quotient<P: Pairing>(
     beta: P::ScalarField,
     delta: P::ScalarField,
@@ -669,7 +1957,7 @@ fn compute_polynomial_b_and_quotient<P: Pairing>(
     let poly_b = DensePolynomial::from_coefficients_vec(poly_coeff_list_b);
 
     // Round 10-4: The prover computes [Q_B(tau)]_1 using the SRS and Lemma 4.
-    let poly_coset_eval_list_l = domain_coset_v.fft(&poly_l);
+    let poly_coset_eval_list_l = domain_coset_v.fft(poly_l);
     let poly_coset_eval_list_b = domain_coset_v.fft(&poly_b);
     let poly_coset_eval_list_f = domain_coset_v.fft(&witness.poly);
     let fr_one = P::ScalarField::one();
@@ -683,7 +1971,7 @@ fn compute_polynomial_b_and_quotient<P: Pairing>(
     let poly_coeff_list_qb = poly_coset_eval_list_qb;
 
     let mut poly_qb = DensePolynomial::from_coefficients_vec(poly_coeff_list_qb);
-    divide_by_vanishing_poly_on_coset_in_place::<P::G1>(&domain_v, &mut poly_qb.coeffs)?;
+    divide_by_vanishing_poly_on_coset_in_place::<P::G1>(domain_v, &mut poly_qb.coeffs)?;
     let g1_qb = Kzg::<P::G1>::commit(g1_affine_srs, &poly_qb);
 
     let poly_b0 = DensePolynomial::from_coefficients_slice(&poly_b.coeffs[1..]);
@@ -700,49 +1988,29 @@ fn compute_polynomial_b_and_quotient<P: Pairing>(
     })
 }
 
-fn compute_degree_check_g1_affine<P: Pairing>(
-    num_table_segments: usize,
-    num_witness_segments: usize,
-    segment_size: usize,
-    table_element_size: usize,
+// Commitments of the degree check of B_0(X).
+struct DegreeCheckB0<P: Pairing> {
+    g1_affine_rb: P::G1Affine,
+    g1_affine_hb: P::G1Affine,
+    g1_affine_pb: P::G1Affine,
+}
+
+fn compute_degree_check_b0<P: Pairing>(
     poly_b0: &DensePolynomial<P::ScalarField>,
+    domain_v: &Radix2EvaluationDomain<P::ScalarField>,
     g1_affine_srs: &[P::G1Affine],
-    sparse_poly_eval_list_a: &DashMap<usize, P::ScalarField>,
-    domain_w: &Radix2EvaluationDomain<P::ScalarField>,
-) -> P::G1Affine {
-    if num_table_segments > num_witness_segments {
-        // If n > k, the prover computes P_B(X) and sends [P_B(tau)]_1 to the verifier.
-        let coeff_shift = (num_table_segments - num_witness_segments) * segment_size - 1;
-
-        Kzg::<P::G1>::commit_with_offset(&g1_affine_srs, &poly_b0, coeff_shift).into_affine()
-    } else if num_table_segments < num_witness_segments {
-        // If n < k, the prover computes P_A(X) and sends [P_A(tau)]_1 to the verifier.
-        // We can use Inverse FFT to compute the polynomial A(X),
-        // since the runtime does not exceed O(ks log ks) as n < k.
-        let mut poly_eval_list_a = vec![P::ScalarField::zero(); table_element_size];
-
-        // Step 1: Collect all (index, value) pairs in parallel
-        let entries: Vec<(usize, P::ScalarField)> = sparse_poly_eval_list_a
-            .par_iter()
-            .map(|entry| (*entry.key(), *entry.value()))
-            .collect();
-
-        // Step 2: Assign the collected values sequentially
-        for (i, a_i) in entries {
-            poly_eval_list_a[i] = a_i;
-        }
-
-        // Proceed with Inverse FFT and commitment
-        domain_w.ifft_in_place(&mut poly_eval_list_a);
-        let poly_coeff_list_a = poly_eval_list_a;
-        let poly_coeff_list_a0 = poly_coeff_list_a[1..].to_vec();
-        let poly_a0 = DensePolynomial::from_coefficients_slice(&poly_coeff_list_a0);
-
-        let coeff_shift = (num_witness_segments - num_table_segments) * segment_size - 1;
-
-        Kzg::<P::G1>::commit_with_offset(&g1_affine_srs, &poly_a0, coeff_shift).into_affine()
-    } else {
-        P::G1Affine::zero()
+    shift_b0: usize,
+) -> DegreeCheckB0<P> {
+    let (poly_hb, poly_rb) = poly_b0.divide_by_vanishing_poly(*domain_v);
+    let g1_rb = Kzg::<P::G1>::commit(g1_affine_srs, &poly_rb);
+    let g1_hb = Kzg::<P::G1>::commit(g1_affine_srs, &poly_hb);
+    let g1_pb = Kzg::<P::G1>::commit_with_offset(g1_affine_srs, &poly_rb, shift_b0);
+    let g1_affine_list = P::G1::normalize_batch(&[g1_rb, g1_hb, g1_pb]);
+
+    DegreeCheckB0 {
+        g1_affine_rb: g1_affine_list[0],
+        g1_affine_hb: g1_affine_list[1],
+        g1_affine_pb: g1_affine_list[2],
     }
 }
 
@@ -752,7 +2020,9 @@ mod tests {
     use crate::table::{rand_segments, Table};
     use ark_bn254::Bn254;
     use ark_ec::PrimeGroup;
-    use ark_std::rand::RngCore;
+    use ark_serialize::{Compress, Validate};
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::{RngCore, SeedableRng};
     use ark_std::{test_rng, UniformRand};
     use std::ops::{Neg, Sub};
 
@@ -818,16 +2088,17 @@ mod tests {
         });
         let poly_coeff_list_m = pp.domain_w.ifft(&poly_eval_m_list);
         let poly_m = DensePolynomial::from_coefficients_vec(poly_coeff_list_m.clone());
-        let g1_affine_m_expected = Kzg::<G1>::commit(&pp.g1_affine_srs, &poly_m).into_affine();
+        let g1_affine_m_expected =
+            Kzg::<G1>::commit(pp.g1_affine_srs.as_slice().unwrap(), &poly_m).into_affine();
         let inv_generator_w = pp.domain_w.group_gen_inv;
         let poly_coeff_list_m_div_w: Vec<Fr> = poly_coeff_list_m
             .par_iter()
             .enumerate()
-            .map(|(i, &c)| c * inv_generator_w.pow(&[i as u64]))
+            .map(|(i, &c)| c * inv_generator_w.pow([i as u64]))
             .collect();
         let poly_m_div_w = DensePolynomial::from_coefficients_vec(poly_coeff_list_m_div_w);
         let g1_affine_m_div_w_expected =
-            Kzg::<G1>::commit(&pp.g1_affine_srs, &poly_m_div_w).into_affine();
+            Kzg::<G1>::commit(pp.g1_affine_srs.as_slice().unwrap(), &poly_m_div_w).into_affine();
 
         let mut poly_coeff_list_x_pow_n_sub_one = vec![Fr::zero(); pp.table_element_size];
         poly_coeff_list_x_pow_n_sub_one[pp.num_table_segments] = Fr::one();
@@ -838,7 +2109,8 @@ mod tests {
         poly_qm = poly_qm.sub(&poly_m_div_w);
         poly_qm = poly_qm.naive_mul(&poly_x_pow_n_sub_one);
         let poly_qm = divide_by_vanishing_poly_checked::<Bn254>(&pp.domain_w, &poly_qm).unwrap();
-        let g1_affine_qm_expected = Kzg::<G1>::commit(&pp.g1_affine_srs, &poly_qm).into_affine();
+        let g1_affine_qm_expected =
+            Kzg::<G1>::commit(pp.g1_affine_srs.as_slice().unwrap(), &poly_qm).into_affine();
 
         let MultiplicityPolynomialsAndQuotient {
             g1_affine_m: g1_affine_m_got,
@@ -850,13 +2122,219 @@ mod tests {
             &pp.g1_affine_list_q3,
             segment_size,
             pp.table_element_size,
-        );
+        )
+        .unwrap();
 
         assert_eq!(g1_affine_m_expected, g1_affine_m_got);
         assert_eq!(g1_affine_m_div_w_expected, g1_affine_m_div_w_got);
         assert_eq!(g1_affine_qm_expected, g1_affine_qm_got);
     }
 
+    #[test]
+    fn test_compute_polynomial_a_and_quotient() {
+        let mut rng = test_rng();
+        // In zero-knowledge mode, the SRS does not bound the degree of A_0(X),
+        // so [P_A(tau)]_1 is also computed.
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let t = Table::new(&pp, rand_segments::generate(&pp)).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let multiplicities = compute_segment_multiplicities(&[1, 3, 3, 6], 8).unwrap();
+        let beta = Fr::rand(&mut rng);
+        let delta = Fr::rand(&mut rng);
+        let (shift_a0, _) = pp.degree_check_shifts();
+        let shift_a0 = shift_a0.unwrap();
+
+        let PolynomialAAndQuotient {
+            g1_affine_qa: g1_affine_qa_got,
+            g1_affine_a0: g1_affine_a0_got,
+            g1_affine_pa: g1_affine_pa_got,
+            ..
+        } = compute_polynomial_a_and_quotient::<Bn254>(
+            beta,
+            delta,
+            &tpp.adjusted_table_values,
+            &multiplicities,
+            pp.segment_size,
+            &pp.domain_w,
+            &pp.g1_affine_list_lw,
+            &tpp.g1_affine_list_q1,
+            pp.g1_affine_srs.as_slice().unwrap()[0],
+            &pp.g1_affine_lw_opening_proofs_at_zero,
+            Some(&pp.g1_affine_lw_shifted_opening_proofs_at_zero),
+        )
+        .unwrap();
+
+        // a_i = m_i / (beta + t_i + delta * w^i) on the queried segments.
+        let poly_eval_list_a: Vec<Fr> = (0..pp.table_element_size)
+            .map(|i| match multiplicities.get(&(i / pp.segment_size)) {
+                Some(multiplicity) => {
+                    Fr::from(*multiplicity as u64)
+                        / (beta + tpp.adjusted_table_values[i] + delta * pp.domain_w.element(i))
+                },
+                None => Fr::zero(),
+            })
+            .collect();
+
+        // [Q_A(tau)]_1 = sum_i a_i * (q_{i, 1} + delta * q_{i, 2}),
+        // with q_{i, 2} = [w^i / ns]_1 computed per element.
+        let fr_inv_ns = Fr::from(pp.table_element_size as u64).inverse().unwrap();
+        let g1_affine_qa_expected = poly_eval_list_a
+            .iter()
+            .enumerate()
+            .map(|(i, &a_i)| {
+                let g1_q2_i =
+                    pp.g1_affine_srs.as_slice().unwrap()[0].mul(pp.domain_w.element(i) * fr_inv_ns);
+                (tpp.g1_affine_list_q1[i] + g1_q2_i.mul(delta)).mul(a_i)
+            })
+            .sum::<G1>()
+            .into_affine();
+
+        assert_eq!(g1_affine_qa_expected, g1_affine_qa_got);
+
+        // [A_0(tau)]_1 and [P_A(tau)]_1 = [A_0(tau) * tau^{t_A}]_1 from the
+        // dense A_0(X) of degree ns-2.
+        let poly_a = DensePolynomial::from_coefficients_vec(pp.domain_w.ifft(&poly_eval_list_a));
+        let poly_a0 = DensePolynomial::from_coefficients_slice(&poly_a.coeffs[1..]);
+        assert_eq!(
+            Kzg::<G1>::commit(pp.g1_affine_srs.as_slice().unwrap(), &poly_a0).into_affine(),
+            g1_affine_a0_got
+        );
+        assert_eq!(
+            Kzg::<G1>::commit_with_offset(pp.g1_affine_srs.as_slice().unwrap(), &poly_a0, shift_a0)
+                .into_affine(),
+            g1_affine_pa_got
+        );
+        assert_eq!(
+            shift_a0 + pp.table_element_size - 2,
+            pp.g1_affine_srs.len() - 1
+        );
+    }
+
+    // Earlier versions stored q_{i, 2} = [w^i / ns]_1 in the public parameters.
+    // A prover that computes [Q_A(tau)]_1 from the stored list produces the
+    // same proof, byte for byte.
+    #[test]
+    fn test_prove_matches_stored_q2_list() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(7)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let t = Table::new(&pp, rand_segments::generate(&pp)).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let witness = Witness::new(&pp, &tpp.adjusted_table_values, &[1, 3, 3, 6]).unwrap();
+        let g1_affine_srs = pp.g1_affine_srs.as_slice().unwrap();
+        let statement = witness.generate_statement(g1_affine_srs);
+        let seed = rng.next_u64();
+        let proof = prove(
+            &pp,
+            &tpp,
+            &witness,
+            statement,
+            &mut StdRng::seed_from_u64(seed),
+        )
+        .unwrap();
+
+        // Step 4-a of the setup of earlier versions.
+        let fr_inv_ns = Fr::from(pp.table_element_size as u64).inverse().unwrap();
+        let g1_affine_list_q2 = G1::normalize_batch(
+            &(0..pp.table_element_size)
+                .map(|i| g1_affine_srs[0].mul(pp.domain_w.element(i) * fr_inv_ns))
+                .collect::<Vec<_>>(),
+        );
+
+        // The rounds of `prove`, with Round 10-2 computed as
+        // [Q_A(tau)]_1 = sum_i a_i * q_{i, 1} + delta * a_i * q_{i, 2}.
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let transcript = &mut Transcript::<Fr>::new();
+        ProofOptions::<Bn254>::default()
+            .append_to(transcript)
+            .unwrap();
+        transcript
+            .append_bytes(Label::PublicParameters, &pp.hash_representation)
+            .unwrap();
+        transcript
+            .append_bytes(Label::TablePreprocessedParameters, &tpp.hash_representation)
+            .unwrap();
+        transcript
+            .append_point(Label::Statement, &statement.g1_affine_f)
+            .unwrap();
+        transcript
+            .append_scalar(Label::NumQueries, &Fr::from(statement.num_queries as u64))
+            .unwrap();
+        let mut prover = ProverState::new(&pp, &tpp, &witness);
+        let round1 = prover.round1(rng).unwrap();
+        round1.append_to(transcript).unwrap();
+        let caulk_round1 = prover.caulk_round1(rng).unwrap();
+        transcript
+            .append_point(Label::CaulkG1D, &round1.g1_affine_d)
+            .unwrap();
+        caulk_round1.append_to(transcript).unwrap();
+        let caulk_alpha = transcript
+            .squeeze_challenge(Label::ChallengeCaulkAlpha)
+            .unwrap();
+        let caulk_round2 = prover.caulk_round2(caulk_alpha).unwrap();
+        caulk_round2.append_to(transcript).unwrap();
+        let caulk_beta = transcript
+            .squeeze_challenge(Label::ChallengeCaulkBeta)
+            .unwrap();
+        let caulk_round3 = prover.caulk_round3(caulk_beta).unwrap();
+        let beta = transcript.squeeze_challenge(Label::ChallengeBeta).unwrap();
+        let delta = transcript.squeeze_challenge(Label::ChallengeDelta).unwrap();
+        let epsilon = transcript
+            .squeeze_challenge(Label::ChallengeEpsilon)
+            .unwrap();
+        let mut round10 = prover.round10(beta, delta, epsilon).unwrap();
+        let multiplicities =
+            compute_segment_multiplicities(&witness.segment_indices, pp.num_table_segments)
+                .unwrap();
+        round10.g1_affine_qa = multiplicities
+            .iter()
+            .flat_map(|entry| {
+                let (segment_index, multiplicity) = (*entry.key(), *entry.value());
+                (segment_index * pp.segment_size..(segment_index + 1) * pp.segment_size)
+                    .map(move |i| (i, multiplicity))
+            })
+            .map(|(i, multiplicity)| {
+                let fr_a_i = Fr::from(multiplicity as u64)
+                    / (beta + tpp.adjusted_table_values[i] + delta * pp.domain_w.element(i));
+                tpp.g1_affine_list_q1[i].mul(fr_a_i) + g1_affine_list_q2[i].mul(delta * fr_a_i)
+            })
+            .sum::<G1>()
+            .into_affine();
+        round10.append_to(transcript).unwrap();
+        let gamma = transcript.squeeze_challenge(Label::ChallengeGamma).unwrap();
+        let round12 = prover.round12(gamma).unwrap();
+        round12.append_to(transcript).unwrap();
+        let eta = transcript.squeeze_challenge(Label::ChallengeEta).unwrap();
+        let round14 = prover.round14(eta).unwrap();
+        let proof_stored_q2 = Proof::from_messages(
+            round1,
+            caulk_round1,
+            caulk_round2,
+            caulk_round3,
+            round10,
+            round12,
+            round14,
+        );
+
+        let mut proof_bytes = Vec::new();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        let mut proof_bytes_stored_q2 = Vec::new();
+        proof_stored_q2
+            .serialize_compressed(&mut proof_bytes_stored_q2)
+            .unwrap();
+        assert_eq!(proof_bytes, proof_bytes_stored_q2);
+    }
+
     #[test]
     fn test_successful_prove() {
         let mut rng = test_rng();
@@ -873,11 +2351,11 @@ mod tests {
             let t = Table::new(&pp, segments).unwrap();
 
             let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-                .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
                 .collect();
 
             let witness = Witness::new(&pp, &t.values, &queried_segment_indices).unwrap();
-            let statement = witness.generate_statement(&pp.g1_affine_srs);
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
 
             let tpp = t.preprocess(&pp).unwrap();
 
@@ -886,4 +2364,318 @@ mod tests {
             prove(&pp, &tpp, &witness, statement, rng).unwrap();
         }
     }
+
+    #[test]
+    fn test_prover_state_rounds_out_of_order() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let witness = Witness::new(&pp, &t.values, &[0, 1, 2, 3]).unwrap();
+
+        let mut prover = ProverState::new(&pp, &tpp, &witness);
+        assert!(matches!(
+            prover.caulk_round1(&mut rng),
+            Err(Error::UnexpectedRound("round 1"))
+        ));
+
+        prover.round1(&mut rng).unwrap();
+        assert!(matches!(
+            prover.round1(&mut rng),
+            Err(Error::UnexpectedRound("caulk round 1"))
+        ));
+        assert!(matches!(
+            prover.round12(Fr::rand(&mut rng)),
+            Err(Error::UnexpectedRound("caulk round 1"))
+        ));
+        prover.caulk_round1(&mut rng).unwrap();
+    }
+
+    #[test]
+    fn test_proof_serialization() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut bytes = Vec::new();
+            proof.serialize_with_mode(&mut bytes, compress).unwrap();
+            assert_eq!(bytes.len(), proof.serialized_size(compress));
+
+            let proof_deserialized =
+                Proof::<Bn254>::deserialize_with_mode(&bytes[..], compress, Validate::Yes).unwrap();
+            let mut bytes_deserialized = Vec::new();
+            proof_deserialized
+                .serialize_with_mode(&mut bytes_deserialized, compress)
+                .unwrap();
+            assert_eq!(bytes, bytes_deserialized);
+        }
+
+        // Tampering with the y-coordinate of [M(tau)]_1 moves it off the curve.
+        let mut bytes = Vec::new();
+        proof.serialize_uncompressed(&mut bytes).unwrap();
+        bytes[32] ^= 1;
+        assert!(Proof::<Bn254>::deserialize_uncompressed(&bytes[..]).is_err());
+        assert!(Proof::<Bn254>::deserialize_uncompressed_unchecked(&bytes[..]).is_ok());
+    }
+
+    #[test]
+    fn test_zero_knowledge_simulated_evaluations() {
+        // Given the challenges, a simulator without the witness samples the
+        // opened evaluations uniformly at random. For any simulated evaluations,
+        // there are blinders under which the real prover opens the same values.
+        // The blinders enter the evaluations through surjective affine maps,
+        // so the real evaluations are uniformly distributed as well.
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+        let mut witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let g1_affine_srs = pp.g1_affine_srs.as_slice().unwrap();
+
+        let roots_of_unity_v = roots_of_unity::<Bn254>(&pp.domain_v);
+        let domain_generator_w = pp.domain_w.group_gen;
+        let mut index_polys = compute_index_polynomials_and_quotients::<Bn254>(
+            &pp.domain_k,
+            &pp.domain_v,
+            &pp.domain_coset_v,
+            &pp.domain_w,
+            &roots_of_unity_v,
+            &pp.partial_inv_zk_at_coset_v_values,
+            &pp.g1_affine_list_lv,
+            g1_affine_srs,
+            &witness.segment_indices,
+            domain_generator_w,
+            pp.witness_element_size,
+            pp.segment_size,
+        )
+        .unwrap();
+        let beta = Fr::rand(&mut rng);
+        let delta = Fr::rand(&mut rng);
+        let epsilon = Fr::rand(&mut rng);
+        let gamma = Fr::rand(&mut rng);
+        let mut b_polys = compute_polynomial_b_and_quotient(
+            beta,
+            delta,
+            &witness,
+            pp.witness_element_size,
+            &pp.domain_v,
+            &pp.domain_coset_v,
+            &index_polys.poly_eval_list_l,
+            &index_polys.poly_l,
+            g1_affine_srs,
+        )
+        .unwrap();
+        // a_0 = (ks / N) * B(0) by the sumcheck lemma.
+        let fr_n_div_ks =
+            Fr::from(pp.table_element_size as u64) / Fr::from(pp.witness_element_size as u64);
+        let fr_a_at_zero = b_polys.poly_b.evaluate(&Fr::zero()) / fr_n_div_ks;
+
+        // Simulated l_{gamma}, l_{gamma,v}, d_{gamma}, b_{0,gamma}, f_{gamma},
+        // a_0', and g_{B,gamma}.
+        let fr_sim_l_at_gamma = Fr::rand(&mut rng);
+        let fr_sim_l_at_gamma_div_v = Fr::rand(&mut rng);
+        let fr_sim_d_at_gamma = Fr::rand(&mut rng);
+        let fr_sim_b0_at_gamma = Fr::rand(&mut rng);
+        let fr_sim_f_at_gamma = Fr::rand(&mut rng);
+        let fr_sim_a_at_zero = Fr::rand(&mut rng);
+        let fr_sim_gb_at_gamma = Fr::rand(&mut rng);
+
+        // Solve for the blinders that produce the simulated evaluations, keeping
+        // the others random.
+        let fr_zv_at_gamma = pp.domain_v.evaluate_vanishing_polynomial(gamma);
+        let fr_zk_at_gamma = pp.domain_k.evaluate_vanishing_polynomial(gamma);
+        let fr_gamma_div_v = gamma * pp.domain_v.group_gen_inv;
+        let mut blinders = Blinders::<Bn254>::rand(&mut rng);
+        // r_L(X) = r_{L,0} + r_{L,1} * X + X^2 * (r_{L,2} + r_{L,3} * X).
+        blinders.poly_r_l.coeffs[0] = Fr::zero();
+        blinders.poly_r_l.coeffs[1] = Fr::zero();
+        let fr_r_l_at_gamma = (fr_sim_l_at_gamma - index_polys.poly_l.evaluate(&gamma))
+            / fr_zv_at_gamma
+            - blinders.poly_r_l.evaluate(&gamma);
+        let fr_r_l_at_gamma_div_v =
+            (fr_sim_l_at_gamma_div_v - index_polys.poly_l_div_v.evaluate(&gamma)) / fr_zv_at_gamma
+                - blinders.poly_r_l.evaluate(&fr_gamma_div_v);
+        blinders.poly_r_l.coeffs[1] =
+            (fr_r_l_at_gamma - fr_r_l_at_gamma_div_v) / (gamma - fr_gamma_div_v);
+        blinders.poly_r_l.coeffs[0] = fr_r_l_at_gamma - blinders.poly_r_l.coeffs[1] * gamma;
+        let fr_r_d_at_gamma =
+            (fr_sim_d_at_gamma - index_polys.poly_d.evaluate(&gamma)) / fr_zk_at_gamma;
+        let fr_r_d_offset = fr_r_d_at_gamma - blinders.poly_r_d.evaluate(&gamma);
+        blinders.poly_r_d.coeffs[0] += fr_r_d_offset;
+        blinders.fr_g = (fr_sim_a_at_zero - fr_a_at_zero) / epsilon;
+        let (_, _, poly_gb) = blinders.commit_masks(&pp.domain_w, &pp.domain_v, g1_affine_srs);
+        blinders.poly_j_b.coeffs[0] +=
+            (fr_sim_gb_at_gamma - poly_gb.evaluate(&gamma)) / (gamma * fr_zv_at_gamma);
+        let fr_r_b_at_gamma =
+            (fr_sim_b0_at_gamma - b_polys.poly_b0.evaluate(&gamma) - epsilon * blinders.fr_s_b)
+                / fr_zv_at_gamma
+                - epsilon * blinders.poly_j_b.evaluate(&gamma);
+        let fr_r_b_offset = fr_r_b_at_gamma - blinders.poly_r_b.evaluate(&gamma);
+        blinders.poly_r_b.coeffs[0] += fr_r_b_offset;
+        witness.poly_blinder = DensePolynomial::from_coefficients_vec(vec![
+            (fr_sim_f_at_gamma - witness.poly.evaluate(&gamma)) / fr_zv_at_gamma,
+        ]);
+
+        blinders
+            .blind_index_polynomials_and_quotients(
+                &mut index_polys,
+                &pp.domain_v,
+                &pp.domain_k,
+                domain_generator_w,
+                g1_affine_srs,
+            )
+            .unwrap();
+        let (_, _, poly_gb) = blinders.commit_masks(&pp.domain_w, &pp.domain_v, g1_affine_srs);
+        let poly_f = witness.blinded_poly();
+        blinders.blind_polynomial_b_and_quotient(
+            &mut b_polys,
+            beta,
+            delta,
+            epsilon,
+            &poly_f,
+            &witness.poly_blinder,
+            &index_polys.poly_l,
+            &pp.domain_v,
+            g1_affine_srs,
+        );
+
+        let fr_l_at_gamma = index_polys.poly_l.evaluate(&gamma);
+        let fr_l_at_gamma_div_v = index_polys.poly_l.evaluate(&fr_gamma_div_v);
+        let fr_d_at_gamma = index_polys.poly_d.evaluate(&gamma);
+        let fr_b0_at_gamma = b_polys.poly_b0.evaluate(&gamma);
+        let fr_f_at_gamma = poly_f.evaluate(&gamma);
+        let fr_masked_a_at_zero = fr_a_at_zero + epsilon * blinders.fr_g;
+        let fr_gb_at_gamma = poly_gb.evaluate(&gamma);
+        assert_eq!(fr_l_at_gamma, fr_sim_l_at_gamma);
+        assert_eq!(
+            index_polys.poly_l_div_v.evaluate(&gamma),
+            fr_l_at_gamma_div_v
+        );
+        assert_eq!(fr_l_at_gamma_div_v, fr_sim_l_at_gamma_div_v);
+        assert_eq!(fr_d_at_gamma, fr_sim_d_at_gamma);
+        assert_eq!(fr_b0_at_gamma, fr_sim_b0_at_gamma);
+        assert_eq!(fr_f_at_gamma, fr_sim_f_at_gamma);
+        assert_eq!(fr_masked_a_at_zero, fr_sim_a_at_zero);
+        assert_eq!(fr_gb_at_gamma, fr_sim_gb_at_gamma);
+
+        // The remaining evaluations are determined by the simulated ones through
+        // the identities checked by the verifier.
+        let fr_gamma_pow_k_sub_one = gamma.pow([pp.num_witness_segments as u64]) - Fr::one();
+        assert_eq!(
+            fr_gamma_pow_k_sub_one * (fr_l_at_gamma - domain_generator_w * fr_l_at_gamma_div_v),
+            fr_zv_at_gamma * index_polys.poly_ql.evaluate(&gamma)
+        );
+        assert_eq!(
+            fr_l_at_gamma - fr_d_at_gamma,
+            fr_zk_at_gamma * index_polys.poly_qd.evaluate(&gamma)
+        );
+        let fr_b_at_gamma =
+            fr_b0_at_gamma * gamma + fr_masked_a_at_zero * fr_n_div_ks - epsilon * fr_gb_at_gamma;
+        assert_eq!(
+            (beta + fr_f_at_gamma + delta * fr_l_at_gamma) * fr_b_at_gamma - Fr::one(),
+            fr_zv_at_gamma * b_polys.poly_qb.evaluate(&gamma)
+        );
+    }
+
+    #[test]
+    fn test_zero_knowledge_commitments() {
+        // Zero-knowledge mode blinds every commitment of the proof, so that two
+        // proofs for the same multiset of queried segments, or for a permutation
+        // of it, share no commitment.
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let beta = Fr::rand(&mut rng);
+        let delta = Fr::rand(&mut rng);
+        let epsilon = Fr::rand(&mut rng);
+
+        // Runs the prover up to Round 10 under fixed challenges.
+        let mut run = |queried_segment_indices: &[usize]| {
+            let witness = Witness::new_blinded(
+                &pp,
+                &tpp.adjusted_table_values,
+                queried_segment_indices,
+                &mut rng,
+            )
+            .unwrap();
+            let mut prover = ProverState::new(&pp, &tpp, &witness);
+            let round1 = prover.round1(&mut rng).unwrap();
+            prover.caulk_round1(&mut rng).unwrap();
+            prover.caulk_round2(Fr::rand(&mut rng)).unwrap();
+            prover.caulk_round3(Fr::rand(&mut rng)).unwrap();
+            let round10 = prover.round10(beta, delta, epsilon).unwrap();
+
+            (round1, round10)
+        };
+        let (round1, round10) = run(&[1, 3, 3, 6]);
+        let (same_round1, same_round10) = run(&[1, 3, 3, 6]);
+        let (permuted_round1, permuted_round10) = run(&[3, 6, 1, 3]);
+
+        for (other_round1, other_round10) in [
+            (&same_round1, &same_round10),
+            (&permuted_round1, &permuted_round10),
+        ] {
+            assert_ne!(round1.g1_affine_m, other_round1.g1_affine_m);
+            assert_ne!(round1.g1_affine_m_div_w, other_round1.g1_affine_m_div_w);
+            assert_ne!(round1.g1_affine_qm, other_round1.g1_affine_qm);
+            assert_ne!(round1.g1_affine_l, other_round1.g1_affine_l);
+            assert_ne!(round1.g1_affine_l_div_v, other_round1.g1_affine_l_div_v);
+            assert_ne!(round1.g1_affine_ql, other_round1.g1_affine_ql);
+            assert_ne!(round1.g1_affine_d, other_round1.g1_affine_d);
+            assert_ne!(round1.g1_affine_qd, other_round1.g1_affine_qd);
+            assert_ne!(round1.g1_affine_hm, other_round1.g1_affine_hm);
+            assert_ne!(round1.g1_affine_ga, other_round1.g1_affine_ga);
+            assert_ne!(round1.g1_affine_gb, other_round1.g1_affine_gb);
+            assert_ne!(round10.g1_affine_a, other_round10.g1_affine_a);
+            assert_ne!(round10.g1_affine_qa, other_round10.g1_affine_qa);
+            assert_ne!(round10.g1_affine_a0, other_round10.g1_affine_a0);
+            assert_ne!(round10.g1_affine_pa, other_round10.g1_affine_pa);
+            assert_ne!(round10.g1_affine_ha, other_round10.g1_affine_ha);
+            assert_ne!(round10.g1_affine_b0, other_round10.g1_affine_b0);
+            assert_ne!(round10.g1_affine_qb, other_round10.g1_affine_qb);
+            assert_ne!(round10.g1_affine_rb, other_round10.g1_affine_rb);
+            assert_ne!(round10.g1_affine_hb, other_round10.g1_affine_hb);
+            assert_ne!(round10.g1_affine_pb, other_round10.g1_affine_pb);
+        }
+    }
 }
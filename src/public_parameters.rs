@@ -4,22 +4,46 @@ use crate::domain::{
 };
 use crate::error::Error;
 use crate::kzg::unsafe_setup_from_tau;
-use crate::lagrange_basis::{lagrange_basis_g1, zero_opening_proofs};
+use crate::lagrange_basis::{
+    lagrange_basis_g1, lagrange_basis_quotient, lagrange_basis_quotients,
+    shifted_zero_opening_proofs, zero_opening_proofs,
+};
+use crate::lazy_points::LazyPoints;
+use crate::srs::PowersOfTau;
 use crate::COMPRESS_MOD;
-use ark_ec::{pairing::Pairing, CurveGroup};
-use ark_ff::{FftField, Field};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, FftField, Field, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
-use ark_serialize::CanonicalSerialize;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
 use ark_std::rand::rngs::StdRng;
 use ark_std::rand::Rng;
 use ark_std::{One, UniformRand, Zero};
 use blake2::{Blake2b512, Digest};
+use memmap2::Mmap;
 use rayon::prelude::*;
 use std::cmp::max;
-use std::ops::{Mul, MulAssign};
+use std::fs::File;
+use std::ops::MulAssign;
+use std::path::Path;
+use std::sync::Arc;
+
+// Extra powers of tau in G1 for zero-knowledge mode, so that the blinded
+// prover polynomials of degree up to k * s + 5 (Q_B(X)) can be committed.
+const ZK_EXTRA_POWERS_OF_TAU: usize = 6;
+
+// Extra coefficients in X of the multi-unity check in zero-knowledge mode,
+// where H_1(X) = (D(X)^2 - U_1(X)) / Z_K(X) has degree k + 6 instead of k.
+const ZK_EXTRA_CAULK_COEFFICIENTS: usize = 6;
+
+// Number of powers of [gamma * tau^i]_1 for Pedersen statements. The blinding
+// polynomial R(X) has degree one, since F(X) is only opened at gamma.
+pub(crate) const PEDERSEN_SRS_SIZE: usize = 2;
 
-#[derive(Debug)]
+/// The public parameters of the lookup. The prover takes them in full, while
+/// the verifier only needs the compact [`VerifierKey`] derived with
+/// [`PublicParameters::verifier_key`].
+#[derive(Debug, CanonicalSerialize, CanonicalDeserialize)]
 pub struct PublicParameters<P: Pairing> {
     // Number of total segments in the table (n).
     pub num_table_segments: usize,
@@ -27,32 +51,54 @@ pub struct PublicParameters<P: Pairing> {
     pub num_witness_segments: usize,
     // Segment size (s).
     pub segment_size: usize,
+    // Number of table segments requested by the caller, before padding n to a
+    // power of two.
+    pub logical_num_table_segments: usize,
+    // Number of witness segments requested by the caller, before padding k to
+    // a power of two.
+    pub logical_num_witness_segments: usize,
+    // Segment size requested by the caller, before padding s to a power of two.
+    pub logical_segment_size: usize,
+    // Whether the prover blinds its polynomials to hide the witness.
+    pub zero_knowledge: bool,
     // Table size (n * s).
     pub(crate) table_element_size: usize,
     // Witness size (k * s).
     pub(crate) witness_element_size: usize,
-    // [tau^i]_1 for i in 0..max*s.
-    pub g1_affine_srs: Vec<P::G1Affine>,
+    // [tau^i]_1 for i in 0..max*s, extended in zero-knowledge mode.
+    pub g1_affine_srs: LazyPoints<P::G1Affine>,
+    // [gamma * tau^i]_1 for i in 0..2 with a secret gamma derived from the
+    // setup, or empty if Pedersen statements are not enabled.
+    pub g1_affine_srs_gamma: Vec<P::G1Affine>,
     // [tau^i]_2 for i in 0..max*s.
-    pub g2_affine_srs: Vec<P::G2Affine>,
+    pub g2_affine_srs: LazyPoints<P::G2Affine>,
     // [Z_W(tau)]_2.
     pub g2_affine_zw: P::G2Affine,
     // [Z_V(tau)]_2.
     pub g2_affine_zv: P::G2Affine,
     // [Z_K(tau)]_2.
     pub g2_affine_zk: P::G2Affine,
-    // q_{i, 2} for i in 1..n*s.
-    // The commitment of quotient polynomials Q_{i, 2} s.t.
-    // L^W_i(X) * X = omega^i * L^W_i(X) + Z_W(X) * Q_{i, 2}(X).
-    pub(crate) g1_affine_list_q2: Vec<P::G1Affine>,
+    // [S_q(tau)]_2 = [sum_{i >= q} L^K_i(tau)]_2 for q in 0..=k, which is one
+    // on the padding queries after q queries and zero on the others.
+    pub(crate) g2_affine_list_padding_selectors: Vec<P::G2Affine>,
     // q_{i, 3} for i in 1..n*s.
-    pub(crate) g1_affine_list_q3: Vec<P::G1Affine>,
+    pub(crate) g1_affine_list_q3: LazyPoints<P::G1Affine>,
     // [L^W_i(tau)]_1 for i in 1..n*s.
-    pub(crate) g1_affine_list_lw: Vec<P::G1Affine>,
+    pub(crate) g1_affine_list_lw: LazyPoints<P::G1Affine>,
     // [(L^W_i(tau) - L^W_i(0)) / tau]_1 for i in 1..n*s.
-    pub(crate) g1_affine_lw_opening_proofs_at_zero: Vec<P::G1Affine>,
+    pub(crate) g1_affine_lw_opening_proofs_at_zero: LazyPoints<P::G1Affine>,
+    // [tau^{t_A} * (L^W_i(tau) - L^W_i(0)) / tau]_1 for i in 1..n*s, for the
+    // degree check of A_0(X), or empty if the SRS already bounds its degree.
+    pub(crate) g1_affine_lw_shifted_opening_proofs_at_zero: LazyPoints<P::G1Affine>,
+    // [(L^W_i(tau) - 1) / (tau - w^i)]_1 for i in 1..n*s, used to update the
+    // preprocessed table and by the streaming preprocessor, or empty if they
+    // are not enabled.
+    pub(crate) g1_affine_lw_quotients: LazyPoints<P::G1Affine>,
+    // [(L^W_a(tau) - 1) / (tau - w^a)]_1 for the first element a = p * s of
+    // the padding segment p, to open M(X) at w^a.
+    pub(crate) g1_affine_lw_padding_quotient: P::G1Affine,
     // [L^V_i(tau)]_1 for i in 1..k*s.
-    pub(crate) g1_affine_list_lv: Vec<P::G1Affine>,
+    pub(crate) g1_affine_list_lv: LazyPoints<P::G1Affine>,
 
     // Domain W, V, and K.
     pub domain_w: Radix2EvaluationDomain<P::ScalarField>,
@@ -77,13 +123,225 @@ impl<P: Pairing> PublicParameters<P> {
     pub fn builder() -> PublicParametersBuilder<P> {
         PublicParametersBuilder::<P>::default()
     }
+
+    /// Returns the index of the padding segment, which follows the logical
+    /// table. Witnesses with fewer than k queries are padded with it, and no
+    /// query may use it.
+    pub fn padding_segment_index(&self) -> usize {
+        self.logical_num_table_segments
+    }
+
+    /// Returns the hash of the parameters that is absorbed by the transcript.
+    pub fn hash_representation(&self) -> &[u8] {
+        &self.hash_representation
+    }
+
+    /// Extracts the verifier key, whose size does not depend on the number of
+    /// table segments except for the Caulk SRS (O(k log n)). It also holds
+    /// k + 1 padding selectors in G2. Fails if a power of the SRS cannot be
+    /// decoded from parameters opened with [`PublicParameters::open_mapped`].
+    pub fn verifier_key(&self) -> Result<VerifierKey<P>, Error> {
+        let (shift_a0, shift_b0) = self.degree_check_shifts();
+        // Read single powers, so that a mapped SRS is not decoded as a whole.
+        let g2_affine_tau_pow = |i: usize| self.g2_affine_srs.get(i);
+
+        Ok(VerifierKey {
+            num_table_segments: self.num_table_segments,
+            num_witness_segments: self.num_witness_segments,
+            segment_size: self.segment_size,
+            logical_num_table_segments: self.logical_num_table_segments,
+            logical_num_witness_segments: self.logical_num_witness_segments,
+            logical_segment_size: self.logical_segment_size,
+
+            g1_affine_one: self.g1_affine_srs.get(0)?,
+            g2_affine_one: g2_affine_tau_pow(0)?,
+            g2_affine_tau: g2_affine_tau_pow(1)?,
+            g2_affine_tau_pow_n: g2_affine_tau_pow(self.num_table_segments)?,
+            g2_affine_degree_check_a0: shift_a0.map(g2_affine_tau_pow).transpose()?,
+            g2_affine_degree_check_b0: g2_affine_tau_pow(shift_b0)?,
+            g2_affine_zw: self.g2_affine_zw,
+            g2_affine_zv: self.g2_affine_zv,
+            g2_affine_zk: self.g2_affine_zk,
+            g2_affine_list_padding_selectors: self.g2_affine_list_padding_selectors.clone(),
+            g1_affine_gamma: self
+                .g1_affine_srs_gamma
+                .first()
+                .copied()
+                .unwrap_or(P::G1Affine::zero()),
+
+            domain_w: self.domain_w,
+            domain_v: self.domain_v,
+            domain_k: self.domain_k,
+
+            g1_affine_srs_caulk: self.g1_affine_srs_caulk.clone(),
+            g2_affine_srs_caulk: self.g2_affine_srs_caulk.clone(),
+            log_num_table_segments: self.log_num_table_segments,
+            domain_log_n: self.domain_log_n,
+            identity_poly_k: self.identity_poly_k.clone(),
+
+            hash_representation: self.hash_representation.clone(),
+        })
+    }
+
+    // Shifts of the degree checks of A_0(X) and B_0(X) (see
+    // `degree_check_shifts`).
+    pub(crate) fn degree_check_shifts(&self) -> (Option<usize>, usize) {
+        degree_check_shifts(
+            self.g1_affine_srs.len() - 1,
+            self.table_element_size,
+            self.witness_element_size,
+        )
+    }
+
+    /// Opens public parameters serialized without compression (e.g. with
+    /// [`CanonicalSerialize::serialize_uncompressed`]) by memory-mapping the
+    /// file. Opening takes time and memory independent of the table size:
+    /// - The per-element lists of O(ns) points are not read, and the prover
+    ///   only decodes the entries of the queried segments.
+    /// - The G1 and G2 SRS are not read either. The verifier key and the
+    ///   [`StreamingPreprocessor`](crate::streaming::StreamingPreprocessor)
+    ///   read only the powers they need. Proving and in-memory table
+    ///   preprocessing use the SRS with [`LazyPoints::as_slice`], which
+    ///   decodes all of it once and keeps it in memory (O(ns) points).
+    /// - The other fields are read eagerly. Their size depends only on k, s
+    ///   and log n.
+    ///
+    /// The file is trusted: the points are neither checked to be on the
+    /// curve nor in the subgroup, and the file must not be modified while the
+    /// parameters are in use. Mapped points that cannot be decoded are
+    /// reported as [`Error::FailedToDeserializeElement`] when accessed.
+    pub fn open_mapped(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|err| Error::FailedToMapFile(err.to_string()))?;
+        // SAFETY: The caller guarantees that the file is not modified while
+        // it is mapped.
+        let mmap = Arc::new(
+            unsafe { Mmap::map(&file) }.map_err(|err| Error::FailedToMapFile(err.to_string()))?,
+        );
+        let offset = &mut 0;
+
+        // The fields are read in the order of the derived serialization.
+        Ok(Self {
+            num_table_segments: read_mapped(&mmap, offset)?,
+            num_witness_segments: read_mapped(&mmap, offset)?,
+            segment_size: read_mapped(&mmap, offset)?,
+            logical_num_table_segments: read_mapped(&mmap, offset)?,
+            logical_num_witness_segments: read_mapped(&mmap, offset)?,
+            logical_segment_size: read_mapped(&mmap, offset)?,
+            zero_knowledge: read_mapped(&mmap, offset)?,
+            table_element_size: read_mapped(&mmap, offset)?,
+            witness_element_size: read_mapped(&mmap, offset)?,
+            g1_affine_srs: LazyPoints::map(&mmap, offset)?,
+            g1_affine_srs_gamma: read_mapped(&mmap, offset)?,
+            g2_affine_srs: LazyPoints::map(&mmap, offset)?,
+            g2_affine_zw: read_mapped(&mmap, offset)?,
+            g2_affine_zv: read_mapped(&mmap, offset)?,
+            g2_affine_zk: read_mapped(&mmap, offset)?,
+            g2_affine_list_padding_selectors: read_mapped(&mmap, offset)?,
+            g1_affine_list_q3: LazyPoints::map(&mmap, offset)?,
+            g1_affine_list_lw: LazyPoints::map(&mmap, offset)?,
+            g1_affine_lw_opening_proofs_at_zero: LazyPoints::map(&mmap, offset)?,
+            g1_affine_lw_shifted_opening_proofs_at_zero: LazyPoints::map(&mmap, offset)?,
+            g1_affine_lw_quotients: LazyPoints::map(&mmap, offset)?,
+            g1_affine_lw_padding_quotient: read_mapped(&mmap, offset)?,
+            g1_affine_list_lv: LazyPoints::map(&mmap, offset)?,
+            domain_w: read_mapped(&mmap, offset)?,
+            domain_v: read_mapped(&mmap, offset)?,
+            domain_k: read_mapped(&mmap, offset)?,
+            domain_coset_v: read_mapped(&mmap, offset)?,
+            partial_inv_zk_at_coset_v_values: read_mapped(&mmap, offset)?,
+            g1_affine_srs_caulk: read_mapped(&mmap, offset)?,
+            g2_affine_srs_caulk: read_mapped(&mmap, offset)?,
+            log_num_table_segments: read_mapped(&mmap, offset)?,
+            domain_log_n: read_mapped(&mmap, offset)?,
+            identity_poly_k: read_mapped(&mmap, offset)?,
+            hash_representation: read_mapped(&mmap, offset)?,
+        })
+    }
+}
+
+// Reads an uncompressed field of the public parameters at `*offset`, and
+// advances the offset past it.
+fn read_mapped<T: CanonicalDeserialize>(bytes: &[u8], offset: &mut usize) -> Result<T, Error> {
+    let mut reader = bytes
+        .get(*offset..)
+        .ok_or(Error::FailedToDeserializeElement)?;
+    let value = T::deserialize_with_mode(&mut reader, Compress::No, Validate::No)
+        .map_err(|_| Error::FailedToDeserializeElement)?;
+    *offset = bytes.len() - reader.len();
+
+    Ok(value)
+}
+
+/// The subset of the public parameters needed by the verifier.
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<P: Pairing> {
+    // Number of total segments in the table (n).
+    pub num_table_segments: usize,
+    // Number of segments in the witness (k).
+    pub num_witness_segments: usize,
+    // Segment size (s).
+    pub segment_size: usize,
+    // Sizes requested by the caller, before padding to powers of two.
+    pub logical_num_table_segments: usize,
+    pub logical_num_witness_segments: usize,
+    pub logical_segment_size: usize,
+
+    // [1]_1.
+    pub(crate) g1_affine_one: P::G1Affine,
+    // [1]_2.
+    pub(crate) g2_affine_one: P::G2Affine,
+    // [tau]_2.
+    pub(crate) g2_affine_tau: P::G2Affine,
+    // [tau^n]_2.
+    pub(crate) g2_affine_tau_pow_n: P::G2Affine,
+    // [tau^{t_A}]_2 for the degree check of A_0(X), or None if the SRS
+    // already bounds its degree.
+    pub(crate) g2_affine_degree_check_a0: Option<P::G2Affine>,
+    // [tau^{t_B}]_2 for the degree check of B_0(X).
+    pub(crate) g2_affine_degree_check_b0: P::G2Affine,
+    // [Z_W(tau)]_2.
+    pub(crate) g2_affine_zw: P::G2Affine,
+    // [Z_V(tau)]_2.
+    pub(crate) g2_affine_zv: P::G2Affine,
+    // [Z_K(tau)]_2.
+    pub(crate) g2_affine_zk: P::G2Affine,
+    // [S_q(tau)]_2 for q in 0..=k, selecting the padding queries.
+    pub(crate) g2_affine_list_padding_selectors: Vec<P::G2Affine>,
+    // [gamma]_1 of the Pedersen statements, or zero if they are not enabled.
+    pub(crate) g1_affine_gamma: P::G1Affine,
+
+    // Domain W, V, and K.
+    pub domain_w: Radix2EvaluationDomain<P::ScalarField>,
+    pub domain_v: Radix2EvaluationDomain<P::ScalarField>,
+    pub domain_k: Radix2EvaluationDomain<P::ScalarField>,
+
+    // Caulk Sub-protocol parameters.
+    pub(crate) g1_affine_srs_caulk: Vec<P::G1Affine>,
+    pub(crate) g2_affine_srs_caulk: Vec<P::G2Affine>,
+    pub(crate) log_num_table_segments: usize,
+    pub(crate) domain_log_n: Radix2EvaluationDomain<P::ScalarField>,
+    pub(crate) identity_poly_k: DensePolynomial<P::ScalarField>,
+
+    pub(crate) hash_representation: Vec<u8>,
+}
+
+impl<P: Pairing> VerifierKey<P> {
+    /// Returns the hash of the public parameters that is absorbed by the
+    /// transcript.
+    pub fn hash_representation(&self) -> &[u8] {
+        &self.hash_representation
+    }
 }
 
 pub struct PublicParametersBuilder<P: Pairing> {
     num_table_segments: Option<usize>,
     num_witness_segments: Option<usize>,
     segment_size: Option<usize>,
+    zero_knowledge: bool,
+    pedersen_statements: bool,
+    lagrange_quotients: bool,
     tau: Option<P::ScalarField>,
+    powers_of_tau: Option<PowersOfTau<P>>,
     domain_generator_w: Option<P::ScalarField>,
     domain_generator_v: Option<P::ScalarField>,
 }
@@ -94,36 +352,94 @@ impl<P: Pairing> PublicParametersBuilder<P> {
             num_table_segments: None,
             num_witness_segments: None,
             segment_size: None,
+            zero_knowledge: false,
+            pedersen_statements: false,
+            lagrange_quotients: false,
             tau: None,
+            powers_of_tau: None,
             domain_generator_w: None,
             domain_generator_v: None,
         }
     }
 
-    /// Sets the number of table segments.
+    /// Sets the number of table segments. The table is extended with one
+    /// padding segment and padded to the next power of two with copies of
+    /// the first segment.
     pub fn num_table_segments(mut self, num: usize) -> Self {
         self.num_table_segments = Some(num);
         self
     }
 
-    /// Sets the number of witness segments.
+    /// Sets the number of witness segments. It is padded to the next power of
+    /// two with queries of the padding segment.
     pub fn num_witness_segments(mut self, num: usize) -> Self {
         self.num_witness_segments = Some(num);
         self
     }
 
-    /// Sets the segment size.
+    /// Sets the segment size. Each segment is padded with zeros to the next
+    /// power of two.
     pub fn segment_size(mut self, size: usize) -> Self {
         self.segment_size = Some(size);
         self
     }
 
+    /// Enables the zero-knowledge mode, in which the prover blinds the
+    /// witness polynomials with multiples of the vanishing polynomials, and
+    /// masks the sumcheck of A(X) and B(X) with random polynomials committed
+    /// in Round 1. The SRS is extended to commit to the blinded polynomials.
+    ///
+    /// The blinding covers every commitment and evaluation of the proof,
+    /// including M(X), A(X), and the degree checks, so the proof reveals
+    /// neither the queried segments nor their multiplicities: a simulator
+    /// with the trapdoor tau produces proofs of the same distribution from
+    /// the statement alone. The statement [F(tau)]_1 is not part of the
+    /// proof, and a guessed witness can be tested against it unless it is
+    /// blinded as well, with [`crate::witness::Witness::new_blinded`] or as a
+    /// Pedersen statement (see [`Self::pedersen_statements`]).
+    pub fn zero_knowledge(mut self, enabled: bool) -> Self {
+        self.zero_knowledge = enabled;
+        self
+    }
+
+    /// Enables Pedersen statements, which are commitments
+    /// [F(tau) + gamma * R(tau)]_1 to a blinded witness with a second
+    /// blinder R(X) (see
+    /// [`crate::witness::Witness::generate_pedersen_statement`]), and adds
+    /// [gamma * tau^i]_1 to the SRS. gamma is derived from the setup: it is
+    /// the ceremony's alpha of [`PowersOfTau`], or a hash of tau otherwise,
+    /// so it is as secret as tau.
+    ///
+    /// Requires zero-knowledge mode, since without it the evaluations in the
+    /// proof reveal the segments of F(X), and the statement hides nothing.
+    pub fn pedersen_statements(mut self, enabled: bool) -> Self {
+        self.pedersen_statements = enabled;
+        self
+    }
+
+    /// Enables the quotients [(L^W_i(tau) - 1) / (tau - w^i)]_1 of the
+    /// Lagrange basis, which add n * s points to the public parameters.
+    /// They are needed by
+    /// [`crate::table::TablePreprocessedParameters::update_segment`] and
+    /// [`crate::streaming::StreamingPreprocessor`], but not by the prover.
+    pub fn lagrange_quotients(mut self, enabled: bool) -> Self {
+        self.lagrange_quotients = enabled;
+        self
+    }
+
     /// Sets a specific tau value.
     pub fn tau(mut self, tau: P::ScalarField) -> Self {
         self.tau = Some(tau);
         self
     }
 
+    /// Uses the powers of tau from an external trusted setup instead of
+    /// sampling tau. This takes precedence over [`Self::tau`].
+    pub fn powers_of_tau(mut self, powers_of_tau: PowersOfTau<P>) -> Self {
+        self.powers_of_tau = Some(powers_of_tau);
+        self
+    }
+
     /// Sets a specific generator for domain W.
     pub fn domain_generator_w(mut self, gen: P::ScalarField) -> Self {
         self.domain_generator_w = Some(gen);
@@ -138,28 +454,114 @@ impl<P: Pairing> PublicParametersBuilder<P> {
 
     pub fn build<R: Rng + ?Sized>(self, rng: &mut R) -> Result<PublicParameters<P>, Error> {
         // Extract parameters or set defaults.
-        let num_table_segments = self
+        let logical_num_table_segments = self
             .num_table_segments
             .ok_or(Error::MissingParameter("num_table_segments"))?;
-        let num_witness_segments = self
+        let logical_num_witness_segments = self
             .num_witness_segments
             .ok_or(Error::MissingParameter("num_witness_segments"))?;
-        let segment_size = self
+        let logical_segment_size = self
             .segment_size
             .ok_or(Error::MissingParameter("segment_size"))?;
-        let tau = self.tau.unwrap_or_else(|| P::ScalarField::rand(rng));
-
+        if logical_num_table_segments == 0 {
+            return Err(Error::InvalidNumberOfSegments(logical_num_table_segments));
+        }
+        if logical_num_witness_segments == 0 {
+            return Err(Error::InvalidNumberOfQueries(logical_num_witness_segments));
+        }
+        if logical_segment_size == 0 {
+            return Err(Error::InvalidSegmentSize(logical_segment_size));
+        }
+        if self.pedersen_statements && !self.zero_knowledge {
+            return Err(Error::ZeroKnowledgeNotEnabled);
+        }
+        // Pad the sizes to powers of two, so that domains W, V and K have
+        // exactly n * s, k * s and k elements. The table reserves one segment
+        // after the logical table to pad witnesses with fewer than k queries.
+        let num_table_segments = (logical_num_table_segments + 1).next_power_of_two();
+        let num_witness_segments = logical_num_witness_segments.next_power_of_two();
+        let segment_size = logical_segment_size.next_power_of_two();
         // Compute the other sizes.
         let table_element_size = num_table_segments * segment_size;
         let witness_element_size = num_witness_segments * segment_size;
         let log_num_table_segments = max(num_table_segments.trailing_zeros() as usize, 2);
 
         // Step 1: Compute SRS from tau.
-        let max_pow_of_tau_g1 = max(num_table_segments, num_witness_segments) * segment_size - 1;
+        let mut max_pow_of_tau_g1 =
+            max(num_table_segments, num_witness_segments) * segment_size - 1;
+        if self.zero_knowledge {
+            max_pow_of_tau_g1 += ZK_EXTRA_POWERS_OF_TAU;
+        }
+        if self.pedersen_statements {
+            max_pow_of_tau_g1 = max(max_pow_of_tau_g1, PEDERSEN_SRS_SIZE - 1);
+        }
+        let mut caulk_num_coefficients = num_witness_segments + 1;
+        if self.zero_knowledge {
+            caulk_num_coefficients += ZK_EXTRA_CAULK_COEFFICIENTS;
+        }
         let caulk_max_pow_of_tau_g1 =
-            (num_witness_segments + 1) * log_num_table_segments.next_power_of_two();
-        let (g1_affine_srs, g2_affine_srs, g1_affine_srs_caulk, g2_affine_srs_caulk) =
-            unsafe_setup_from_tau::<P, StdRng>(max_pow_of_tau_g1, caulk_max_pow_of_tau_g1, tau);
+            caulk_num_coefficients * log_num_table_segments.next_power_of_two();
+        // For Pedersen statements, [gamma * tau^i]_1 comes from the setup too:
+        // gamma is the alpha of an external ceremony, or a hash of tau.
+        let (
+            g1_affine_srs,
+            g2_affine_srs,
+            g1_affine_srs_caulk,
+            g2_affine_srs_caulk,
+            g1_affine_srs_gamma,
+        ) = match &self.powers_of_tau {
+            Some(powers_of_tau) => {
+                if !powers_of_tau.verify(rng) {
+                    return Err(Error::InvalidStructuredReferenceStrings);
+                }
+                let (g1_affine_srs, g2_affine_srs, g1_affine_srs_caulk, g2_affine_srs_caulk) =
+                    powers_of_tau.split(max_pow_of_tau_g1, caulk_max_pow_of_tau_g1)?;
+                let g1_affine_srs_gamma = if self.pedersen_statements {
+                    if powers_of_tau.g1_affine_alpha_powers.len() < PEDERSEN_SRS_SIZE {
+                        return Err(Error::InvalidStructuredReferenceStrings);
+                    }
+                    powers_of_tau.g1_affine_alpha_powers[..PEDERSEN_SRS_SIZE].to_vec()
+                } else {
+                    Vec::new()
+                };
+
+                (
+                    g1_affine_srs,
+                    g2_affine_srs,
+                    g1_affine_srs_caulk,
+                    g2_affine_srs_caulk,
+                    g1_affine_srs_gamma,
+                )
+            },
+            None => {
+                let tau = self.tau.unwrap_or_else(|| P::ScalarField::rand(rng));
+                let (g1_affine_srs, g2_affine_srs, g1_affine_srs_caulk, g2_affine_srs_caulk) =
+                    unsafe_setup_from_tau::<P, StdRng>(
+                        max_pow_of_tau_g1,
+                        caulk_max_pow_of_tau_g1,
+                        tau,
+                    );
+                let g1_affine_srs_gamma = if self.pedersen_statements {
+                    let gamma = pedersen_gamma_from_tau(tau);
+                    let g1_srs_gamma: Vec<P::G1> = g1_affine_srs[..PEDERSEN_SRS_SIZE]
+                        .iter()
+                        .map(|&g1| g1 * gamma)
+                        .collect();
+
+                    P::G1::normalize_batch(&g1_srs_gamma)
+                } else {
+                    Vec::new()
+                };
+
+                (
+                    g1_affine_srs,
+                    g2_affine_srs,
+                    g1_affine_srs_caulk,
+                    g2_affine_srs_caulk,
+                    g1_affine_srs_gamma,
+                )
+            },
+        };
 
         // Step 2: Define domains.
         // Compute [Z_W(tau)]_2.
@@ -188,6 +590,15 @@ impl<P: Pairing> PublicParametersBuilder<P> {
         let domain_k = create_sub_domain::<P>(&domain_v, order_k, segment_size)?;
         let g2_affine_zk = vanishing_poly_commitment_affine::<P::G2>(&g2_affine_srs, &domain_k);
 
+        // Compute [S_q(tau)]_2 = [sum_{i >= q} L^K_i(tau)]_2 for q in 0..=k as
+        // the suffix sums of [L^K_i(tau)]_2.
+        let g2_affine_list_lk = lagrange_basis_g1::<P::G2>(&g2_affine_srs, &domain_k)?;
+        let mut g2_list_padding_selectors = vec![P::G2::zero(); order_k + 1];
+        for i in (0..order_k).rev() {
+            g2_list_padding_selectors[i] = g2_list_padding_selectors[i + 1] + g2_affine_list_lk[i];
+        }
+        let g2_affine_list_padding_selectors = P::G2::normalize_batch(&g2_list_padding_selectors);
+
         let domain_coset_v = domain_v
             .get_coset(P::ScalarField::GENERATOR)
             .ok_or(Error::FailedToCreateEvaluationDomain)?;
@@ -210,60 +621,49 @@ impl<P: Pairing> PublicParametersBuilder<P> {
                 .collect();
         let partial_inv_zk_at_coset_v_values = partial_inv_zk_at_coset_v_values?;
 
-        // Step 4-a: Compute q_{i, 2} = [Q_{i,2}(tau)]_1 for i in 1..n*s.
-        // Q_{i,2}(X) = w^i / (ns).
-        let roots_of_unity_w: Vec<P::ScalarField> = roots_of_unity::<P>(&domain_w);
-        let quotient_values: Vec<P::ScalarField> = roots_of_unity_w
-            .par_iter()
-            .map(|&x| x / P::ScalarField::from(order_w as u64))
-            .collect();
-        let g1_affine_list_q2 = quotient_values
-            .par_iter()
-            .map(|&x| g1_affine_srs[0].mul(x).into())
-            .collect();
-
-        // Step 4-b: Compute [L^W_i(tau)]_1 for i in 1..n*s.
+        // Step 4-a: Compute [L^W_i(tau)]_1 for i in 1..n*s.
         let g1_affine_list_lw = lagrange_basis_g1::<P::G1>(&g1_affine_srs, &domain_w)?;
 
-        // Step 4-c: Compute [(L^W_i(tau) - L^W_i(0)) / tau]_1 for i in 1..n*s.
+        // Step 4-b: Compute [(L^W_i(tau) - L^W_i(0)) / tau]_1 for i in 1..n*s.
         // a.k.a. zero openings of the Lagrange basis.
         let g1_affine_lw_opening_proofs_at_zero =
             zero_opening_proofs::<P>(&g1_affine_srs, &domain_w, &g1_affine_list_lw)?;
 
+        // Step 4-b': Compute [tau^{t_A} * (L^W_i(tau) - L^W_i(0)) / tau]_1 for
+        // i in 1..n*s, so that the prover commits to P_A(X) = A_0(X) * X^{t_A}
+        // for the degree check in sparse form.
+        let (shift_a0, _) =
+            degree_check_shifts(max_pow_of_tau_g1, table_element_size, witness_element_size);
+        let g1_affine_lw_shifted_opening_proofs_at_zero = shift_a0.map_or_else(Vec::new, |shift| {
+            shifted_zero_opening_proofs::<P>(&g1_affine_srs, &domain_w, shift)
+        });
+
+        // Step 4-c: Compute [(L^W_i(tau) - 1) / (tau - w^i)]_1 for i in 1..n*s
+        // if enabled, and for the first element of the padding segment.
+        let g1_affine_lw_quotients = if self.lagrange_quotients {
+            lagrange_basis_quotients::<P>(&g1_affine_srs, &domain_w)
+        } else {
+            Vec::new()
+        };
+        let g1_affine_lw_padding_quotient = lagrange_basis_quotient::<P>(
+            &g1_affine_srs,
+            &domain_w,
+            logical_num_table_segments * segment_size,
+        );
+
         // Step 5: Compute [L^V_i(tau)]_1 for i in 1..k*s.
         let g1_affine_list_lv = lagrange_basis_g1::<P::G1>(&g1_affine_srs, &domain_v)?;
 
         // Step 6: Compute quotient polynomial commitments q_{i, 3} and q_{i, 4} for i
         // in 1..n*s. q_{i, 3} = [(w^i / ns) * (tau^n - w^{in}) / (tau -
-        // w^i)]_1.
-        let fr_inv_ns = domain_w
-            .size_as_field_element()
-            .inverse()
-            .ok_or(Error::FailedToInverseFieldElement)?;
-        let inv_tau_sub_w_pow_i_list: Vec<P::ScalarField> = roots_of_unity_w
-            .par_iter()
-            .map(|x| {
-                (tau - x)
-                    .inverse()
-                    .unwrap_or_else(|| P::ScalarField::zero())
-            })
-            .collect();
-        let fr_tau_pow_n = tau.pow([num_table_segments as u64]);
-        let tau_pow_n_sub_w_pow_in_list: Vec<P::ScalarField> = (0..order_w)
-            .into_par_iter()
-            .map(|i| fr_tau_pow_n - roots_of_unity_w[i].pow([num_table_segments as u64]))
-            .collect();
-        let g1_affine_list_q3: Vec<P::G1Affine> = (0..order_w)
-            .into_par_iter()
-            .map(|i| {
-                let mut q3 = g1_affine_srs[0].mul(roots_of_unity_w[i]);
-                q3.mul_assign(fr_inv_ns);
-                q3.mul_assign(tau_pow_n_sub_w_pow_in_list[i]);
-                q3.mul_assign(inv_tau_sub_w_pow_i_list[i]);
-
-                q3.into_affine()
-            })
-            .collect();
+        // w^i)]_1, derived from the SRS alone without knowing tau.
+        let roots_of_unity_w: Vec<P::ScalarField> = roots_of_unity::<P>(&domain_w);
+        let g1_affine_list_q3 = compute_quotients_q3::<P>(
+            &g1_affine_srs,
+            &domain_w,
+            &roots_of_unity_w,
+            num_table_segments,
+        );
 
         let domain_log_n: Radix2EvaluationDomain<P::ScalarField> =
             Radix2EvaluationDomain::<P::ScalarField>::new(log_num_table_segments)
@@ -276,6 +676,10 @@ impl<P: Pairing> PublicParametersBuilder<P> {
         serialize_usize(num_table_segments, &mut buffer);
         serialize_usize(num_witness_segments, &mut buffer);
         serialize_usize(segment_size, &mut buffer);
+        serialize_usize(logical_num_table_segments, &mut buffer);
+        serialize_usize(logical_num_witness_segments, &mut buffer);
+        serialize_usize(logical_segment_size, &mut buffer);
+        buffer.push(self.zero_knowledge as u8);
         g2_affine_zw
             .serialize_with_mode(&mut buffer, COMPRESS_MOD)
             .map_err(|_| Error::FailedToSerializeElement)?;
@@ -309,6 +713,12 @@ impl<P: Pairing> PublicParametersBuilder<P> {
         hasher.update(&buffer);
         buffer.clear();
 
+        g1_affine_srs_gamma
+            .serialize_with_mode(&mut buffer, COMPRESS_MOD)
+            .map_err(|_| Error::FailedToSerializeElement)?;
+        hasher.update(&buffer);
+        buffer.clear();
+
         g2_affine_srs
             .serialize_with_mode(&mut buffer, COMPRESS_MOD)
             .map_err(|_| Error::FailedToSerializeElement)?;
@@ -332,19 +742,28 @@ impl<P: Pairing> PublicParametersBuilder<P> {
             num_table_segments,
             num_witness_segments,
             segment_size,
+            logical_num_table_segments,
+            logical_num_witness_segments,
+            logical_segment_size,
+            zero_knowledge: self.zero_knowledge,
             table_element_size,
             witness_element_size,
 
-            g1_affine_srs,
-            g2_affine_srs,
+            g1_affine_srs: g1_affine_srs.into(),
+            g1_affine_srs_gamma,
+            g2_affine_srs: g2_affine_srs.into(),
             g2_affine_zw,
             g2_affine_zv,
             g2_affine_zk,
-            g1_affine_list_q2,
-            g1_affine_list_q3,
-            g1_affine_list_lw,
-            g1_affine_lw_opening_proofs_at_zero,
-            g1_affine_list_lv,
+            g2_affine_list_padding_selectors,
+            g1_affine_list_q3: g1_affine_list_q3.into(),
+            g1_affine_list_lw: g1_affine_list_lw.into(),
+            g1_affine_lw_opening_proofs_at_zero: g1_affine_lw_opening_proofs_at_zero.into(),
+            g1_affine_lw_shifted_opening_proofs_at_zero:
+                g1_affine_lw_shifted_opening_proofs_at_zero.into(),
+            g1_affine_lw_quotients: g1_affine_lw_quotients.into(),
+            g1_affine_lw_padding_quotient,
+            g1_affine_list_lv: g1_affine_list_lv.into(),
 
             domain_w,
             domain_v,
@@ -364,6 +783,63 @@ impl<P: Pairing> PublicParametersBuilder<P> {
     }
 }
 
+// Returns the shifts t_A and t_B of the degree checks
+// P_A(X) = A_0(X) * X^{t_A} and P_B(X) = R_B(X) * X^{t_B}, where R_B(X) is
+// B_0(X) mod Z_V(X). As P_A(X) and P_B(X) are committed with the SRS of the
+// largest power D, they bound A_0(X) by n * s - 2 and R_B(X) by k * s - 2, so
+// that A(X) and B(X) have degrees below the sizes of domains W and V.
+// A_0(X) is already bounded by D - 1 since A(X) is committed, so t_A is None
+// if D = n * s - 1.
+fn degree_check_shifts(
+    max_pow_of_tau_g1: usize,
+    table_element_size: usize,
+    witness_element_size: usize,
+) -> (Option<usize>, usize) {
+    let shift_a0 = (max_pow_of_tau_g1 >= table_element_size)
+        .then(|| max_pow_of_tau_g1 - table_element_size.saturating_sub(2));
+    let shift_b0 = max_pow_of_tau_g1 - witness_element_size.saturating_sub(2);
+
+    (shift_a0, shift_b0)
+}
+
+// Computes q_{i, 3} from the SRS alone:
+// (tau^n - w^{in}) / (tau - w^i) = sum_{j=0}^{n-1} w^{ij} * tau^{n-1-j}, so
+// q_{i, 3} = (w^i / ns) * sum_{j=0}^{n-1} w^{ij} * [tau^{n-1-j}]_1,
+// which is an FFT over domain W in the exponent, costing O(ns log(ns)).
+fn compute_quotients_q3<P: Pairing>(
+    g1_affine_srs: &[P::G1Affine],
+    domain_w: &Radix2EvaluationDomain<P::ScalarField>,
+    roots_of_unity_w: &[P::ScalarField],
+    num_table_segments: usize,
+) -> Vec<P::G1Affine> {
+    let mut g1_list: Vec<P::G1> = g1_affine_srs[..num_table_segments]
+        .iter()
+        .rev()
+        .map(|g1| g1.into_group())
+        .collect();
+    g1_list.resize(domain_w.size(), P::G1::zero());
+    domain_w.fft_in_place(&mut g1_list);
+
+    let fr_inv_ns = domain_w.size_inv;
+    g1_list
+        .par_iter_mut()
+        .zip(roots_of_unity_w.par_iter())
+        .for_each(|(g1, &w_pow_i)| g1.mul_assign(w_pow_i * fr_inv_ns));
+
+    P::G1::normalize_batch(&g1_list)
+}
+
+// Derives the secret gamma of Pedersen statements from tau, by hashing tau with
+// a domain separator, so that gamma is independent of the powers of tau but
+// discarded with tau.
+fn pedersen_gamma_from_tau<F: PrimeField>(tau: F) -> F {
+    let mut hasher = Blake2b512::new();
+    hasher.update(b"segment_lookup hiding gamma");
+    hasher.update(tau.into_bigint().to_bytes_le());
+
+    F::from_le_bytes_mod_order(&hasher.finalize())
+}
+
 fn serialize_usize(input: usize, buf: &mut Vec<u8>) {
     buf.extend_from_slice(&input.to_le_bytes());
 }
@@ -371,10 +847,23 @@ fn serialize_usize(input: usize, buf: &mut Vec<u8>) {
 #[cfg(test)]
 mod test {
     use ark_bn254::Bn254;
+    use ark_serialize::{Compress, Validate};
+    use ark_std::rand::SeedableRng;
     use ark_std::test_rng;
+    use std::ops::Mul;
+
+    use crate::prover::prove;
+    use crate::table::{rand_segments, Table};
+    use crate::verifier::verify;
+    use crate::witness::Witness;
 
     use super::*;
 
+    type Fr = <Bn254 as Pairing>::ScalarField;
+    type G1 = <Bn254 as Pairing>::G1;
+    type G1Affine = <Bn254 as Pairing>::G1Affine;
+    type G2Affine = <Bn254 as Pairing>::G2Affine;
+
     #[test]
     fn test_public_parameters_builder() {
         let mut rng = test_rng();
@@ -385,4 +874,442 @@ mod test {
             .build(&mut rng)
             .unwrap();
     }
+
+    #[test]
+    fn test_public_parameters_builder_with_padding() {
+        let mut rng = test_rng();
+        let pp_padded = PublicParameters::<Bn254>::builder()
+            .num_table_segments(5)
+            .num_witness_segments(3)
+            .segment_size(3)
+            .tau(Fr::from(7u64))
+            .build(&mut rng)
+            .unwrap();
+        assert_eq!(pp_padded.num_table_segments, 8);
+        assert_eq!(pp_padded.num_witness_segments, 4);
+        assert_eq!(pp_padded.segment_size, 4);
+        assert_eq!(pp_padded.domain_w.size(), 32);
+        assert_eq!(pp_padded.domain_v.size(), 16);
+        assert_eq!(pp_padded.domain_k.size(), 4);
+
+        // The logical sizes are bound to the hash representation.
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .tau(Fr::from(7u64))
+            .build(&mut rng)
+            .unwrap();
+        assert_ne!(pp.hash_representation, pp_padded.hash_representation);
+
+        assert!(PublicParameters::<Bn254>::builder()
+            .num_table_segments(0)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .is_err());
+    }
+
+    #[test]
+    fn test_pedersen_statements_gamma_from_setup() {
+        let builder = || {
+            PublicParameters::<Bn254>::builder()
+                .num_table_segments(8)
+                .num_witness_segments(4)
+                .segment_size(4)
+                .zero_knowledge(true)
+                .pedersen_statements(true)
+                .tau(Fr::from(7u64))
+        };
+
+        // gamma only depends on tau, not on the rng of the builder.
+        let pp = builder().build(&mut test_rng()).unwrap();
+        let pp_other_rng = builder().build(&mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(pp.g1_affine_srs_gamma, pp_other_rng.g1_affine_srs_gamma);
+        assert_eq!(pp.hash_representation, pp_other_rng.hash_representation);
+        assert_ne!(
+            pp.g1_affine_srs_gamma[0],
+            pp.g1_affine_srs.as_slice().unwrap()[0]
+        );
+        assert_ne!(
+            pp.g1_affine_srs_gamma[0],
+            pp.g1_affine_srs.as_slice().unwrap()[1]
+        );
+
+        // Without zero knowledge, the proof reveals the segments of F(X).
+        assert!(matches!(
+            builder().zero_knowledge(false).build(&mut test_rng()),
+            Err(Error::ZeroKnowledgeNotEnabled)
+        ));
+    }
+
+    #[test]
+    fn test_public_parameters_serialization() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .unwrap();
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut bytes = Vec::new();
+            pp.serialize_with_mode(&mut bytes, compress).unwrap();
+            assert_eq!(bytes.len(), pp.serialized_size(compress));
+
+            let pp_deserialized = PublicParameters::<Bn254>::deserialize_with_mode(
+                &bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+            let mut bytes_deserialized = Vec::new();
+            pp_deserialized
+                .serialize_with_mode(&mut bytes_deserialized, compress)
+                .unwrap();
+
+            assert_eq!(bytes, bytes_deserialized);
+            assert_eq!(pp.hash_representation, pp_deserialized.hash_representation);
+            assert_eq!(pp.domain_w, pp_deserialized.domain_w);
+            assert_eq!(pp.domain_log_n, pp_deserialized.domain_log_n);
+        }
+    }
+
+    #[test]
+    fn test_public_parameters_open_mapped() {
+        let mut rng = test_rng();
+        for zero_knowledge in [false, true] {
+            let pp = PublicParameters::<Bn254>::builder()
+                .num_table_segments(6)
+                .num_witness_segments(3)
+                .segment_size(4)
+                .zero_knowledge(zero_knowledge)
+                .pedersen_statements(zero_knowledge)
+                .build(&mut rng)
+                .unwrap();
+            assert_eq!(
+                pp.g1_affine_srs_gamma.len(),
+                if zero_knowledge { PEDERSEN_SRS_SIZE } else { 0 }
+            );
+            let path = std::env::temp_dir().join(format!(
+                "segment-lookup-pp-{}-{}.bin",
+                std::process::id(),
+                zero_knowledge
+            ));
+            let mut bytes = Vec::new();
+            pp.serialize_uncompressed(&mut bytes).unwrap();
+            std::fs::write(&path, &bytes).unwrap();
+
+            let pp_mapped = PublicParameters::<Bn254>::open_mapped(&path).unwrap();
+            // Nothing of the SRS or of the per-element lists is decoded on
+            // opening, nor to derive the verifier key.
+            let lazy_lists = |pp: &PublicParameters<Bn254>| {
+                [
+                    &pp.g1_affine_srs,
+                    &pp.g1_affine_list_q3,
+                    &pp.g1_affine_list_lw,
+                    &pp.g1_affine_lw_opening_proofs_at_zero,
+                    &pp.g1_affine_lw_quotients,
+                    &pp.g1_affine_list_lv,
+                ]
+                .map(|points| points.is_mapped() && !points.is_decoded())
+            };
+            assert_eq!(lazy_lists(&pp_mapped), [true; 6]);
+            assert!(pp_mapped.g2_affine_srs.is_mapped() && !pp_mapped.g2_affine_srs.is_decoded());
+            let mut vk_bytes = Vec::new();
+            pp.verifier_key()
+                .unwrap()
+                .serialize_uncompressed(&mut vk_bytes)
+                .unwrap();
+            let mut vk_bytes_mapped = Vec::new();
+            pp_mapped
+                .verifier_key()
+                .unwrap()
+                .serialize_uncompressed(&mut vk_bytes_mapped)
+                .unwrap();
+            assert_eq!(vk_bytes, vk_bytes_mapped);
+            assert_eq!(lazy_lists(&pp_mapped), [true; 6]);
+            assert!(!pp_mapped.g2_affine_srs.is_decoded());
+            let mut bytes_mapped = Vec::new();
+            pp_mapped.serialize_uncompressed(&mut bytes_mapped).unwrap();
+            assert!(bytes == bytes_mapped);
+
+            // The mapped parameters prove like the in-memory ones.
+            let segments = rand_segments::generate(&pp);
+            let t = Table::<Bn254>::new(&pp_mapped, segments).unwrap();
+            let tpp = t.preprocess(&pp_mapped).unwrap();
+            // In-memory preprocessing decodes the G1 SRS as a whole.
+            assert_eq!(
+                lazy_lists(&pp_mapped),
+                [false, true, true, true, true, true]
+            );
+            let witness = if zero_knowledge {
+                Witness::new_blinded(&pp_mapped, &tpp.adjusted_table_values, &[5, 0, 2], &mut rng)
+            } else {
+                Witness::new(&pp_mapped, &tpp.adjusted_table_values, &[5, 0, 2])
+            }
+            .unwrap();
+            let statement = witness.generate_statement(pp_mapped.g1_affine_srs.as_slice().unwrap());
+            let proof = prove(&pp_mapped, &tpp, &witness, statement, &mut rng).unwrap();
+            // Proving only decodes the queried entries of the per-element
+            // lists.
+            assert_eq!(
+                lazy_lists(&pp_mapped),
+                [false, true, true, true, true, true]
+            );
+            verify(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                &mut rng,
+            )
+            .unwrap();
+
+            std::fs::remove_file(&path).unwrap();
+            assert!(PublicParameters::<Bn254>::open_mapped(&path).is_err());
+        }
+    }
+
+    #[test]
+    fn test_public_parameters_open_mapped_corrupted() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(4)
+            .num_witness_segments(2)
+            .segment_size(2)
+            .build(&mut rng)
+            .unwrap();
+        let mut bytes = Vec::new();
+        pp.serialize_uncompressed(&mut bytes).unwrap();
+
+        // Overwrite [tau]_2 and [1]_1 with coordinates that are not field
+        // elements, which the mapped parameters only notice on access.
+        let srs = pp.g1_affine_srs.as_slice().unwrap();
+        let g2_srs = pp.g2_affine_srs.as_slice().unwrap();
+        let mut corrupt = |point_bytes: Vec<u8>| {
+            let start = bytes
+                .windows(point_bytes.len())
+                .position(|window| window == point_bytes)
+                .unwrap();
+            bytes[start..start + point_bytes.len()].fill(0xff);
+        };
+        let mut point_bytes = Vec::new();
+        g2_srs[1].serialize_uncompressed(&mut point_bytes).unwrap();
+        corrupt(point_bytes);
+        let mut point_bytes = Vec::new();
+        srs[0].serialize_uncompressed(&mut point_bytes).unwrap();
+        corrupt(point_bytes);
+        let path = std::env::temp_dir().join(format!(
+            "segment-lookup-pp-corrupted-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let pp_mapped = PublicParameters::<Bn254>::open_mapped(&path).unwrap();
+        assert!(matches!(
+            pp_mapped.verifier_key(),
+            Err(Error::FailedToDeserializeElement)
+        ));
+        assert!(matches!(
+            pp_mapped.g1_affine_srs.as_slice(),
+            Err(Error::FailedToDeserializeElement)
+        ));
+        let t = Table::<Bn254>::new(&pp_mapped, rand_segments::generate(&pp)).unwrap();
+        assert!(matches!(
+            t.preprocess(&pp_mapped),
+            Err(Error::FailedToDeserializeElement)
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_degree_check_shifts() {
+        // The shifts bound A_0(X) by exactly ns - 2 and R_B(X) by exactly
+        // ks - 2 with the largest power D of the SRS, and A_0(X) is only
+        // checked if D > ns - 1.
+        let mut rng = test_rng();
+        let inputs = [
+            (8, 4, false),
+            (4, 8, false),
+            (4, 4, false),
+            (4, 4, true),
+            (8, 4, true),
+        ];
+        for (num_table_segments, num_witness_segments, zero_knowledge) in inputs {
+            let pp = PublicParameters::<Bn254>::builder()
+                .num_table_segments(num_table_segments)
+                .num_witness_segments(num_witness_segments)
+                .segment_size(4)
+                .zero_knowledge(zero_knowledge)
+                .build(&mut rng)
+                .unwrap();
+            let vk = pp.verifier_key().unwrap();
+            let max_pow_of_tau_g1 = pp.g1_affine_srs.len() - 1;
+            let (shift_a0, shift_b0) = pp.degree_check_shifts();
+
+            assert_eq!(
+                shift_a0.is_some(),
+                max_pow_of_tau_g1 > pp.table_element_size - 1
+            );
+            if let Some(shift_a0) = shift_a0 {
+                assert_eq!(shift_a0 + pp.table_element_size - 2, max_pow_of_tau_g1);
+                assert_eq!(
+                    vk.g2_affine_degree_check_a0,
+                    Some(pp.g2_affine_srs.as_slice().unwrap()[shift_a0])
+                );
+                assert_eq!(
+                    pp.g1_affine_lw_shifted_opening_proofs_at_zero.len(),
+                    pp.table_element_size
+                );
+            } else {
+                assert_eq!(vk.g2_affine_degree_check_a0, None);
+                assert_eq!(pp.g1_affine_lw_shifted_opening_proofs_at_zero.len(), 0);
+            }
+            assert_eq!(shift_b0 + pp.witness_element_size - 2, max_pow_of_tau_g1);
+            assert_eq!(
+                vk.g2_affine_degree_check_b0,
+                pp.g2_affine_srs.as_slice().unwrap()[shift_b0]
+            );
+        }
+    }
+
+    #[test]
+    fn test_compute_quotients_q3() {
+        let mut rng = test_rng();
+        let inputs = [(4, 1), (4, 4), (8, 2), (16, 4)];
+        for (num_table_segments, segment_size) in inputs.into_iter() {
+            let tau = Fr::rand(&mut rng);
+            let pp = PublicParameters::<Bn254>::builder()
+                .num_table_segments(num_table_segments)
+                .num_witness_segments(4)
+                .segment_size(segment_size)
+                .tau(tau)
+                .build(&mut rng)
+                .unwrap();
+
+            // q_{i, 3} = [(w^i / ns) * (tau^n - w^{in}) / (tau - w^i)]_1 from tau.
+            let fr_inv_ns = pp.domain_w.size_inv;
+            let fr_tau_pow_n = tau.pow([pp.num_table_segments as u64]);
+            let g1_affine_list_q3_expected: Vec<G1Affine> = pp
+                .domain_w
+                .elements()
+                .map(|w_pow_i| {
+                    let fr_q3 = w_pow_i
+                        * fr_inv_ns
+                        * (fr_tau_pow_n - w_pow_i.pow([pp.num_table_segments as u64]))
+                        / (tau - w_pow_i);
+
+                    G1Affine::generator().mul(fr_q3).into_affine()
+                })
+                .collect();
+
+            assert_eq!(
+                pp.g1_affine_list_q3.to_vec().unwrap(),
+                g1_affine_list_q3_expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_verifier_key_size() {
+        let mut rng = test_rng();
+        let vk_sizes: Vec<(usize, usize)> = [31, 255]
+            .into_iter()
+            .map(|num_table_segments| {
+                let pp = PublicParameters::<Bn254>::builder()
+                    .num_table_segments(num_table_segments)
+                    .num_witness_segments(4)
+                    .segment_size(4)
+                    .build(&mut rng)
+                    .unwrap();
+
+                (
+                    pp.verifier_key().unwrap().compressed_size(),
+                    pp.compressed_size(),
+                )
+            })
+            .collect();
+
+        // With the padding segment, n = 32 and n = 256. log(32) and log(256)
+        // round up to the same power of two, so the Caulk SRS has the same
+        // length and the verifier key size stays constant.
+        assert_eq!(vk_sizes[0].0, vk_sizes[1].0);
+        assert!(vk_sizes[0].1 < vk_sizes[1].1);
+        assert!(vk_sizes[1].0 * 10 < vk_sizes[1].1);
+    }
+
+    #[test]
+    fn test_public_parameters_size_without_q2_list() {
+        // With the padding segment, n = 32 and n = 256, for which the Caulk
+        // SRS has the same length (see `test_verifier_key_size`). Only the
+        // lists with one entry per table element grow.
+        let mut rng = test_rng();
+        let sizes: Vec<(usize, usize)> = [31, 255]
+            .into_iter()
+            .map(|num_table_segments| {
+                let pp = PublicParameters::<Bn254>::builder()
+                    .num_table_segments(num_table_segments)
+                    .num_witness_segments(4)
+                    .segment_size(4)
+                    .build(&mut rng)
+                    .unwrap();
+                let mut bytes = Vec::new();
+                pp.serialize_uncompressed(&mut bytes).unwrap();
+
+                (pp.table_element_size, bytes.len())
+            })
+            .collect();
+        let (table_element_sizes, pp_sizes): (Vec<usize>, Vec<usize>) = sizes.into_iter().unzip();
+        let added_elements = table_element_sizes[1] - table_element_sizes[0];
+        let added_bytes = pp_sizes[1] - pp_sizes[0];
+        assert_eq!(added_bytes % added_elements, 0);
+
+        // Per table element, the parameters hold [tau^i]_1, q_{i, 3},
+        // [L^W_i(tau)]_1, its opening proof at zero, and [tau^i]_2, which is
+        // 384 bytes. The q_{i, 2} list held one more G1 point, so the
+        // parameters took 448 bytes per element with it.
+        let g1_size = G1Affine::zero().uncompressed_size();
+        let g2_size = G2Affine::zero().uncompressed_size();
+        assert_eq!(added_bytes / added_elements, 4 * g1_size + g2_size);
+    }
+
+    // Measures the memory saved by the q_{i, 2} list at ns = 2^20. The setup
+    // takes minutes, so run it with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_public_parameters_size_without_q2_list_large() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments((1 << 16) - 1)
+            .num_witness_segments(4)
+            .segment_size(16)
+            .build(&mut rng)
+            .unwrap();
+        assert_eq!(pp.table_element_size, 1 << 20);
+        let pp_size = pp.uncompressed_size();
+
+        // The list of q_{i, 2} = [w^i / ns]_1 that earlier versions stored.
+        let g1_affine_srs = pp.g1_affine_srs.as_slice().unwrap();
+        let fr_inv_ns = Fr::from(pp.table_element_size as u64).inverse().unwrap();
+        let g1_list_q2: Vec<G1> = (0..pp.table_element_size)
+            .into_par_iter()
+            .map(|i| g1_affine_srs[0].mul(pp.domain_w.element(i) * fr_inv_ns))
+            .collect();
+        let g1_affine_list_q2 = G1::normalize_batch(&g1_list_q2);
+        let saved_size = g1_affine_list_q2.uncompressed_size();
+        println!(
+            "public parameters: {} bytes, q_{{i, 2}} list: {} bytes ({:.1}%)",
+            pp_size,
+            saved_size,
+            100.0 * saved_size as f64 / (pp_size + saved_size) as f64
+        );
+
+        // 64 MiB of the 448 bytes per element, about one seventh.
+        let g1_size = G1Affine::zero().uncompressed_size();
+        assert_eq!(saved_size, 8 + (1 << 20) * g1_size);
+        assert!(8 * saved_size > pp_size + saved_size);
+    }
 }
@@ -0,0 +1,618 @@
+use crate::error::Error;
+use crate::public_parameters::PEDERSEN_SRS_SIZE;
+use ark_ec::pairing::Pairing;
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::{UniformRand, Zero};
+use std::io::{Read, Seek, SeekFrom};
+
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_SECTION_HEADER: u32 = 1;
+const PTAU_SECTION_TAU_G1: u32 = 2;
+const PTAU_SECTION_TAU_G2: u32 = 3;
+const PTAU_SECTION_ALPHA_TAU_G1: u32 = 4;
+
+/// Powers of tau [tau^i]_1 and [tau^i]_2 produced by an external trusted setup
+/// ceremony. They replace `kzg::unsafe_setup_from_tau` via
+/// `PublicParametersBuilder::powers_of_tau`.
+///
+/// The ceremony's [alpha * tau^i]_1 for i in 0..2, if present, are the
+/// [gamma * tau^i]_1 of Pedersen statements, with gamma = alpha.
+///
+/// The canonical (arkworks) serialization of this struct is the plain list of
+/// G1 powers, followed by the list of G2 powers and the list of alpha powers.
+#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PowersOfTau<P: Pairing> {
+    pub g1_affine_powers: Vec<P::G1Affine>,
+    pub g2_affine_powers: Vec<P::G2Affine>,
+    pub g1_affine_alpha_powers: Vec<P::G1Affine>,
+}
+
+impl<P: Pairing> PowersOfTau<P> {
+    /// Reads the first `num_powers` powers of tau in G1 and G2 from a snarkjs
+    /// `.ptau` file, e.g., one from the Perpetual Powers of Tau ceremony, and
+    /// the first two powers of alpha * tau in G1 if the file has them.
+    pub fn from_ptau<R, C1, C2>(reader: &mut R, num_powers: usize) -> Result<Self, Error>
+    where
+        R: Read + Seek,
+        C1: SWCurveConfig,
+        C2: SWCurveConfig,
+        P: Pairing<G1Affine = Affine<C1>, G2Affine = Affine<C2>>,
+    {
+        let mut magic = [0u8; 4];
+        read_exact(reader, &mut magic)?;
+        if &magic != PTAU_MAGIC {
+            return Err(Error::InvalidPowersOfTau("not a ptau file".to_string()));
+        }
+        let _version = read_u32(reader)?;
+        let num_sections = read_u32(reader)?;
+
+        // Locate the sections, which may appear in any order.
+        let mut header_section = None;
+        let mut tau_g1_section = None;
+        let mut tau_g2_section = None;
+        let mut alpha_tau_g1_section = None;
+        for _ in 0..num_sections {
+            let section_type = read_u32(reader)?;
+            let section_size = read_u64(reader)?;
+            let section_start = reader.stream_position().map_err(io_error)?;
+            match section_type {
+                PTAU_SECTION_HEADER => header_section = Some(section_start),
+                PTAU_SECTION_TAU_G1 => tau_g1_section = Some(section_start),
+                PTAU_SECTION_TAU_G2 => tau_g2_section = Some(section_start),
+                PTAU_SECTION_ALPHA_TAU_G1 => alpha_tau_g1_section = Some(section_start),
+                _ => {},
+            }
+            reader
+                .seek(SeekFrom::Start(section_start + section_size))
+                .map_err(io_error)?;
+        }
+        let missing_section = |name: &str| Error::InvalidPowersOfTau(format!("missing {}", name));
+        let header_section = header_section.ok_or_else(|| missing_section("header"))?;
+        let tau_g1_section = tau_g1_section.ok_or_else(|| missing_section("tau G1"))?;
+        let tau_g2_section = tau_g2_section.ok_or_else(|| missing_section("tau G2"))?;
+
+        // Header: n8, q, power, ceremony power.
+        reader
+            .seek(SeekFrom::Start(header_section))
+            .map_err(io_error)?;
+        // The header is untrusted, so n8 is checked against the byte size of
+        // the base field before any buffer of that size is allocated.
+        let n8 = read_u32(reader)? as usize;
+        let expected_modulus = <C1::BaseField as Field>::BasePrimeField::MODULUS.to_bytes_le();
+        if n8 != expected_modulus.len() {
+            return Err(Error::InvalidPowersOfTau(format!(
+                "field elements of {} bytes, but the base field has {}",
+                n8,
+                expected_modulus.len()
+            )));
+        }
+        let mut modulus = vec![0u8; n8];
+        read_exact(reader, &mut modulus)?;
+        if modulus != expected_modulus {
+            return Err(Error::InvalidPowersOfTau(
+                "the base field does not match the curve".to_string(),
+            ));
+        }
+        let power = read_u32(reader)?;
+        if power >= usize::BITS {
+            return Err(Error::InvalidPowersOfTau(format!(
+                "2^{} powers do not fit in memory",
+                power
+            )));
+        }
+        // The file contains 2^power powers in G2 and 2^{power + 1} - 1 in G1.
+        if num_powers > 1usize << power {
+            return Err(Error::InvalidPowersOfTau(format!(
+                "requested {} powers but the file contains 2^{}",
+                num_powers, power
+            )));
+        }
+
+        reader
+            .seek(SeekFrom::Start(tau_g1_section))
+            .map_err(io_error)?;
+        let g1_affine_powers = (0..num_powers)
+            .map(|_| read_ptau_point::<_, C1>(reader, n8))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        reader
+            .seek(SeekFrom::Start(tau_g2_section))
+            .map_err(io_error)?;
+        let g2_affine_powers = (0..num_powers)
+            .map(|_| read_ptau_point::<_, C2>(reader, n8))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The file contains 2^power powers of alpha * tau in G1.
+        let g1_affine_alpha_powers = match alpha_tau_g1_section {
+            Some(alpha_tau_g1_section) if PEDERSEN_SRS_SIZE <= 1usize << power => {
+                reader
+                    .seek(SeekFrom::Start(alpha_tau_g1_section))
+                    .map_err(io_error)?;
+                (0..PEDERSEN_SRS_SIZE)
+                    .map(|_| read_ptau_point::<_, C1>(reader, n8))
+                    .collect::<Result<Vec<_>, _>>()?
+            },
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            g1_affine_powers,
+            g2_affine_powers,
+            g1_affine_alpha_powers,
+        })
+    }
+
+    // Checks that the powers start from the generators and that consecutive
+    // powers differ by the same tau in both groups, i.e.,
+    // e(sum_i r^i [tau^{i+1}]_1, [1]_2) = e(sum_i r^i [tau^i]_1, [tau]_2) and
+    // e([1]_1, sum_i r^i [tau^{i+1}]_2) = e([tau]_1, sum_i r^i [tau^i]_2),
+    // and that the alpha powers, if any, are not zero and differ by tau, i.e.,
+    // e([alpha * tau]_1, [1]_2) = e([alpha]_1, [tau]_2), folded into one
+    // multi-pairing.
+    pub(crate) fn verify<R: Rng + ?Sized>(&self, rng: &mut R) -> bool {
+        let g1_powers = &self.g1_affine_powers;
+        let g2_powers = &self.g2_affine_powers;
+        if g1_powers.len() < 2 || g2_powers.len() < 2 {
+            return false;
+        }
+        if g1_powers[0] != P::G1Affine::generator() || g2_powers[0] != P::G2Affine::generator() {
+            return false;
+        }
+        let alpha_powers = &self.g1_affine_alpha_powers;
+        if !alpha_powers.is_empty()
+            && (alpha_powers.len() != PEDERSEN_SRS_SIZE || alpha_powers[0].is_zero())
+        {
+            return false;
+        }
+
+        let fr_r = P::ScalarField::rand(rng);
+        let fr_weights = |len: usize| {
+            let mut fr_r_pow = P::ScalarField::from(1u64);
+            (0..len)
+                .map(|_| {
+                    let current = fr_r_pow;
+                    fr_r_pow *= fr_r;
+                    current
+                })
+                .collect::<Vec<_>>()
+        };
+        let fr_g1_weights = fr_weights(g1_powers.len() - 1);
+        let fr_g2_weights = fr_weights(g2_powers.len() - 1);
+        let fr_separation = P::ScalarField::rand(rng);
+        let fr_alpha_separation = P::ScalarField::rand(rng);
+
+        let g1_shifted = P::G1::msm_unchecked(&g1_powers[1..], &fr_g1_weights);
+        let g1_unshifted = P::G1::msm_unchecked(&g1_powers[..g1_powers.len() - 1], &fr_g1_weights);
+        let g2_shifted = P::G2::msm_unchecked(&g2_powers[1..], &fr_g2_weights);
+        let g2_unshifted = P::G2::msm_unchecked(&g2_powers[..g2_powers.len() - 1], &fr_g2_weights);
+
+        let mut g1_list = vec![
+            g1_shifted,
+            -g1_unshifted,
+            g1_powers[0] * fr_separation,
+            -(g1_powers[1] * fr_separation),
+        ];
+        let mut g2_list = vec![
+            g2_powers[0].into_group(),
+            g2_powers[1].into_group(),
+            g2_shifted,
+            g2_unshifted,
+        ];
+        if !alpha_powers.is_empty() {
+            g1_list.push(alpha_powers[1] * fr_alpha_separation);
+            g1_list.push(-(alpha_powers[0] * fr_alpha_separation));
+            g2_list.push(g2_powers[0].into_group());
+            g2_list.push(g2_powers[1].into_group());
+        }
+        let g1_affine_list = P::G1::normalize_batch(&g1_list);
+        let g2_affine_list = P::G2::normalize_batch(&g2_list);
+
+        P::multi_pairing(g1_affine_list, g2_affine_list).is_zero()
+    }
+
+    // Splits the powers into the main and Caulk SRS in the same shape as
+    // `kzg::unsafe_setup_from_tau`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn split(
+        &self,
+        max_power_g1: usize,
+        caulk_max_power_g1: usize,
+    ) -> Result<
+        (
+            Vec<P::G1Affine>,
+            Vec<P::G2Affine>,
+            Vec<P::G1Affine>,
+            Vec<P::G2Affine>,
+        ),
+        Error,
+    > {
+        let max_power_g2 = max_power_g1 + 1;
+        let max_power_caulk_g2 = caulk_max_power_g1 + 1;
+        if self.g1_affine_powers.len() <= max_power_g1.max(caulk_max_power_g1)
+            || self.g2_affine_powers.len() <= max_power_g2.max(max_power_caulk_g2)
+        {
+            return Err(Error::InvalidStructuredReferenceStrings);
+        }
+
+        Ok((
+            self.g1_affine_powers[..=max_power_g1].to_vec(),
+            self.g2_affine_powers[..=max_power_g2].to_vec(),
+            self.g1_affine_powers[..=caulk_max_power_g1].to_vec(),
+            self.g2_affine_powers[..=max_power_caulk_g2].to_vec(),
+        ))
+    }
+}
+
+// Reads an affine point (x, y) whose coordinates consist of base prime field
+// elements in little-endian Montgomery form, i.e., x * R with R = 2^{8 * n8}.
+// The point at infinity is all zeros.
+fn read_ptau_point<R: Read, C: SWCurveConfig>(
+    reader: &mut R,
+    n8: usize,
+) -> Result<Affine<C>, Error> {
+    let fr_inv_montgomery_r = <C::BaseField as Field>::BasePrimeField::from(2u64)
+        .pow([8 * n8 as u64])
+        .inverse()
+        .ok_or(Error::FailedToInverseFieldElement)?;
+    let extension_degree = C::BaseField::extension_degree() as usize;
+    let mut bytes = vec![0u8; 2 * extension_degree * n8];
+    read_exact(reader, &mut bytes)?;
+    if bytes.iter().all(|&b| b == 0) {
+        return Ok(Affine::zero());
+    }
+
+    let mut coordinates = bytes.chunks(extension_degree * n8).map(|coordinate| {
+        let elements = coordinate.chunks(n8).map(|element| {
+            <C::BaseField as Field>::BasePrimeField::from_le_bytes_mod_order(element)
+                * fr_inv_montgomery_r
+        });
+        C::BaseField::from_base_prime_field_elems(elements)
+            .ok_or_else(|| Error::InvalidPowersOfTau("invalid coordinate".to_string()))
+    });
+    let x = coordinates.next().unwrap()?;
+    let y = coordinates.next().unwrap()?;
+
+    let point = Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::InvalidPowersOfTau(
+            "point is not in the prime order subgroup".to_string(),
+        ));
+    }
+
+    Ok(point)
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), Error> {
+    reader.read_exact(buf).map_err(io_error)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let mut buf = [0u8; 4];
+    read_exact(reader, &mut buf)?;
+
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    read_exact(reader, &mut buf)?;
+
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    Error::InvalidPowersOfTau(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kzg::unsafe_setup_from_tau;
+    use crate::prover::prove;
+    use crate::public_parameters::PublicParameters;
+    use crate::table::{rand_segments, Table};
+    use crate::verifier::verify;
+    use crate::witness::Witness;
+    use ark_bn254::{Bn254, Fq2};
+    use ark_ff::MontFp;
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::RngCore;
+    use ark_std::test_rng;
+    use std::io::Cursor;
+
+    type Fr = <Bn254 as Pairing>::ScalarField;
+    type G1Affine = <Bn254 as Pairing>::G1Affine;
+    type G2Affine = <Bn254 as Pairing>::G2Affine;
+
+    // Writes the header, tau and alpha tau sections of a snarkjs ptau file.
+    fn write_ptau(powers_of_tau: &PowersOfTau<Bn254>, power: u32) -> Vec<u8> {
+        let n8 = 32;
+        let write_field = |buf: &mut Vec<u8>, element: ark_bn254::Fq| {
+            let montgomery_r = ark_bn254::Fq::from(2u64).pow([8 * n8 as u64]);
+            buf.extend_from_slice(&(element * montgomery_r).into_bigint().to_bytes_le());
+        };
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&(n8 as u32).to_le_bytes());
+        header.extend_from_slice(&ark_bn254::Fq::MODULUS.to_bytes_le());
+        header.extend_from_slice(&power.to_le_bytes());
+        header.extend_from_slice(&power.to_le_bytes());
+
+        let mut tau_g1 = Vec::new();
+        for g1 in &powers_of_tau.g1_affine_powers {
+            write_field(&mut tau_g1, g1.x);
+            write_field(&mut tau_g1, g1.y);
+        }
+        let mut tau_g2 = Vec::new();
+        for g2 in &powers_of_tau.g2_affine_powers {
+            for element in [g2.x.c0, g2.x.c1, g2.y.c0, g2.y.c1] {
+                write_field(&mut tau_g2, element);
+            }
+        }
+        let mut alpha_tau_g1 = Vec::new();
+        for g1 in &powers_of_tau.g1_affine_alpha_powers {
+            write_field(&mut alpha_tau_g1, g1.x);
+            write_field(&mut alpha_tau_g1, g1.y);
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(PTAU_MAGIC);
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&4u32.to_le_bytes());
+        // Put the G2 section first, since readers must not rely on the order.
+        for (section_type, section) in [
+            (PTAU_SECTION_TAU_G2, tau_g2),
+            (PTAU_SECTION_HEADER, header),
+            (PTAU_SECTION_TAU_G1, tau_g1),
+            (PTAU_SECTION_ALPHA_TAU_G1, alpha_tau_g1),
+        ] {
+            bytes.extend_from_slice(&section_type.to_le_bytes());
+            bytes.extend_from_slice(&(section.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&section);
+        }
+
+        bytes
+    }
+
+    fn powers_of_tau_from_tau(tau: Fr, alpha: Fr, num_powers: usize) -> PowersOfTau<Bn254> {
+        let (g1_affine_powers, g2_affine_powers, _, _) =
+            unsafe_setup_from_tau::<Bn254, StdRng>(num_powers - 1, 0, tau);
+        let g1_affine_alpha_powers = g1_affine_powers[..PEDERSEN_SRS_SIZE]
+            .iter()
+            .map(|&g1| (g1 * alpha).into_affine())
+            .collect();
+
+        PowersOfTau {
+            g1_affine_powers,
+            g2_affine_powers: g2_affine_powers[..num_powers].to_vec(),
+            g1_affine_alpha_powers,
+        }
+    }
+
+    #[test]
+    fn test_read_ptau() {
+        let mut rng = test_rng();
+        let powers_of_tau = powers_of_tau_from_tau(Fr::rand(&mut rng), Fr::rand(&mut rng), 64);
+        let ptau = write_ptau(&powers_of_tau, 6);
+
+        let powers_of_tau_read =
+            PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&ptau), 64).unwrap();
+        assert_eq!(powers_of_tau, powers_of_tau_read);
+        assert!(powers_of_tau_read.verify(&mut rng));
+
+        let powers_of_tau_prefix =
+            PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&ptau), 16).unwrap();
+        assert_eq!(
+            powers_of_tau_prefix.g1_affine_powers,
+            powers_of_tau.g1_affine_powers[..16]
+        );
+        assert_eq!(
+            powers_of_tau_prefix.g1_affine_alpha_powers,
+            powers_of_tau.g1_affine_alpha_powers
+        );
+
+        assert!(PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&ptau), 65).is_err());
+        assert!(PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&ptau[..100]), 64).is_err());
+    }
+
+    #[test]
+    fn test_read_ptau_untrusted_header() {
+        let mut rng = test_rng();
+        let powers_of_tau = powers_of_tau_from_tau(Fr::rand(&mut rng), Fr::rand(&mut rng), 8);
+        let ptau = write_ptau(&powers_of_tau, 3);
+        // The header section follows the G2 section, 12 bytes of the file
+        // header and 12 bytes of each section header.
+        let header_start = 12 + 12 + 8 * 128 + 12;
+        let n8_offset = header_start;
+        let power_offset = header_start + 4 + 32;
+        assert_eq!(ptau[n8_offset..n8_offset + 4], 32u32.to_le_bytes());
+        assert_eq!(ptau[power_offset..power_offset + 4], 3u32.to_le_bytes());
+
+        // A huge n8 must be rejected before allocating a buffer of its size.
+        for n8 in [u32::MAX, 48] {
+            let mut corrupted = ptau.clone();
+            corrupted[n8_offset..n8_offset + 4].copy_from_slice(&n8.to_le_bytes());
+            assert!(matches!(
+                PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&corrupted), 8),
+                Err(Error::InvalidPowersOfTau(_))
+            ));
+        }
+
+        // A power beyond the bit size of usize must not overflow the shift.
+        for power in [usize::BITS, u32::MAX] {
+            let mut corrupted = ptau.clone();
+            corrupted[power_offset..power_offset + 4].copy_from_slice(&power.to_le_bytes());
+            assert!(matches!(
+                PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&corrupted), 8),
+                Err(Error::InvalidPowersOfTau(_))
+            ));
+        }
+    }
+
+    // A BN254 ptau file in the snarkjs layout with tau = 2, alpha = 3 and 2^3
+    // powers, generated independently of `write_ptau` by
+    // fixtures/generate_ptau.py.
+    const PTAU_FIXTURE: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/bn254_tau2_power3.ptau"
+    ));
+
+    #[test]
+    fn test_read_ptau_fixture() {
+        let mut rng = test_rng();
+        let powers_of_tau =
+            PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(PTAU_FIXTURE), 8).unwrap();
+
+        // [2]_1 and [2]_2 are the well-known doubles of the generators.
+        let g1_affine_two = G1Affine::new(
+            MontFp!("1368015179489954701390400359078579693043519447331113978918064868415326638035"),
+            MontFp!("9918110051302171585080402603319702774565515993150576347155970296011118125764"),
+        );
+        let g2_affine_two = G2Affine::new(
+            Fq2::new(
+                MontFp!(
+                    "18029695676650738226693292988307914797657423701064905010927197838374790804409"
+                ),
+                MontFp!(
+                    "14583779054894525174450323658765874724019480979794335525732096752006891875705"
+                ),
+            ),
+            Fq2::new(
+                MontFp!(
+                    "2140229616977736810657479771656733941598412651537078903776637920509952744750"
+                ),
+                MontFp!(
+                    "11474861747383700316476719153975578001603231366361248090558603872215261634898"
+                ),
+            ),
+        );
+        assert_eq!(powers_of_tau.g1_affine_powers[0], G1Affine::generator());
+        assert_eq!(powers_of_tau.g2_affine_powers[0], G2Affine::generator());
+        assert_eq!(powers_of_tau.g1_affine_powers[1], g1_affine_two);
+        assert_eq!(powers_of_tau.g2_affine_powers[1], g2_affine_two);
+        assert_eq!(
+            powers_of_tau,
+            powers_of_tau_from_tau(Fr::from(2u64), Fr::from(3u64), 8)
+        );
+        assert!(powers_of_tau.verify(&mut rng));
+
+        // The file has 2^3 powers in G2.
+        assert!(PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(PTAU_FIXTURE), 9).is_err());
+    }
+
+    #[test]
+    fn test_verify_powers_of_tau() {
+        let mut rng = test_rng();
+        let powers_of_tau = powers_of_tau_from_tau(Fr::rand(&mut rng), Fr::rand(&mut rng), 16);
+        assert!(powers_of_tau.verify(&mut rng));
+
+        let mut wrong_powers_of_tau = powers_of_tau.clone();
+        wrong_powers_of_tau.g1_affine_powers[5] = wrong_powers_of_tau.g1_affine_powers[6];
+        assert!(!wrong_powers_of_tau.verify(&mut rng));
+
+        // The alpha powers must differ by tau too, and alpha must not be zero.
+        let mut wrong_powers_of_tau = powers_of_tau.clone();
+        wrong_powers_of_tau.g1_affine_alpha_powers[1] = wrong_powers_of_tau.g1_affine_powers[1];
+        assert!(!wrong_powers_of_tau.verify(&mut rng));
+        let mut wrong_powers_of_tau = powers_of_tau.clone();
+        wrong_powers_of_tau.g1_affine_alpha_powers = vec![G1Affine::zero(); PEDERSEN_SRS_SIZE];
+        assert!(!wrong_powers_of_tau.verify(&mut rng));
+
+        // A ceremony without alpha powers is still valid.
+        let mut powers_of_tau = powers_of_tau;
+        powers_of_tau.g1_affine_alpha_powers.clear();
+        assert!(powers_of_tau.verify(&mut rng));
+    }
+
+    #[test]
+    fn test_prove_and_verify_with_ptau() {
+        let mut rng = test_rng();
+        let tau = Fr::rand(&mut rng);
+        let ptau = write_ptau(&powers_of_tau_from_tau(tau, Fr::rand(&mut rng), 128), 7);
+        let powers_of_tau = PowersOfTau::<Bn254>::from_ptau(&mut Cursor::new(&ptau), 128).unwrap();
+
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(15)
+            .num_witness_segments(8)
+            .segment_size(4)
+            .powers_of_tau(powers_of_tau)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let pp_from_tau = PublicParameters::<Bn254>::builder()
+            .num_table_segments(15)
+            .num_witness_segments(8)
+            .segment_size(4)
+            .tau(tau)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        assert_eq!(pp.hash_representation, pp_from_tau.hash_representation);
+
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+
+        assert!(verify(
+            &pp.verifier_key().unwrap(),
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut rng
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_build_with_insufficient_powers_of_tau() {
+        let mut rng = test_rng();
+        let powers_of_tau = powers_of_tau_from_tau(Fr::rand(&mut rng), Fr::rand(&mut rng), 32);
+
+        let result = PublicParameters::<Bn254>::builder()
+            .num_table_segments(16)
+            .num_witness_segments(8)
+            .segment_size(4)
+            .powers_of_tau(powers_of_tau)
+            .build(&mut rng);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidStructuredReferenceStrings)
+        ));
+    }
+
+    #[test]
+    fn test_pedersen_statements_with_powers_of_tau() {
+        let mut rng = test_rng();
+        let mut powers_of_tau = powers_of_tau_from_tau(Fr::rand(&mut rng), Fr::rand(&mut rng), 64);
+        let builder = || {
+            PublicParameters::<Bn254>::builder()
+                .num_table_segments(7)
+                .num_witness_segments(4)
+                .segment_size(4)
+                .zero_knowledge(true)
+                .pedersen_statements(true)
+        };
+
+        // gamma is the alpha of the ceremony.
+        let pp = builder()
+            .powers_of_tau(powers_of_tau.clone())
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        assert_eq!(pp.g1_affine_srs_gamma, powers_of_tau.g1_affine_alpha_powers);
+
+        powers_of_tau.g1_affine_alpha_powers.clear();
+        assert!(matches!(
+            builder().powers_of_tau(powers_of_tau).build(&mut rng),
+            Err(Error::InvalidStructuredReferenceStrings)
+        ));
+    }
+}
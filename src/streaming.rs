@@ -0,0 +1,776 @@
+use crate::domain::create_domain_with_generator;
+use crate::error::Error;
+use crate::lazy_points::LazyPoints;
+use crate::public_parameters::PublicParameters;
+use crate::table::{
+    hash_representation, position_multiplier, position_offsets_commitment, TableVerifierKey,
+    UniquenessEncoding,
+};
+use crate::table_io::{read_binary_header, read_binary_segment};
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField};
+use ark_poly::domain::DomainCoeff;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use ark_std::{One, Zero};
+use rayon::prelude::*;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// Preprocesses a table that does not fit in memory.
+///
+/// The table is streamed from the binary table format (see
+/// [`Table::from_binary`](crate::table::Table::from_binary)), and the
+/// serialized [`TablePreprocessedParameters`](crate::table::TablePreprocessedParameters)
+/// are written to a writer. The output is byte-identical to serializing the
+/// result of [`Table::preprocess_with_encoding`](crate::table::Table::preprocess_with_encoding)
+/// with the same compression.
+///
+/// Intermediate vectors of the table size are kept in scratch files, and at
+/// most a few chunks of `chunk_size` elements are held in memory at once. The
+/// chunk size must be at least the square root of the table size.
+///
+/// The G2 SRS and the per-element lists of the public parameters are read
+/// chunk by chunk as well, so the peak memory is only bounded when the public
+/// parameters are opened with
+/// [`PublicParameters::open_mapped`](crate::public_parameters::PublicParameters::open_mapped).
+/// Public parameters in memory already hold O(ns) points.
+///
+/// The public parameters must be built with
+/// [`PublicParametersBuilder::lagrange_quotients`](crate::public_parameters::PublicParametersBuilder::lagrange_quotients).
+pub struct StreamingPreprocessor<'a, P: Pairing> {
+    pp: &'a PublicParameters<P>,
+    encoding: UniquenessEncoding,
+    chunk_size: usize,
+    scratch_dir: PathBuf,
+    compress: Compress,
+}
+
+impl<'a, P: Pairing> StreamingPreprocessor<'a, P> {
+    pub fn new(pp: &'a PublicParameters<P>) -> Self {
+        Self {
+            pp,
+            encoding: UniquenessEncoding::default(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            scratch_dir: std::env::temp_dir(),
+            compress: Compress::Yes,
+        }
+    }
+
+    /// Sets the uniqueness encoding of the table elements.
+    pub fn encoding(mut self, encoding: UniquenessEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the maximum number of elements per in-memory chunk. It is rounded
+    /// down to a power of two.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets the directory of the scratch files, which are removed when the
+    /// preprocessing ends.
+    pub fn scratch_dir(mut self, scratch_dir: impl AsRef<Path>) -> Self {
+        self.scratch_dir = scratch_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sets the compression of the output.
+    pub fn compress(mut self, compress: Compress) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Preprocesses the table read from `table_reader`, and writes the
+    /// serialized preprocessed parameters to `writer`. Returns the verifier key
+    /// of the table, so that it does not have to be read back.
+    pub fn preprocess<R: Read, W: Write>(
+        &self,
+        table_reader: R,
+        writer: W,
+    ) -> Result<TableVerifierKey<P>, Error> {
+        let pp = self.pp;
+        let table_element_size = pp.table_element_size;
+        if self.chunk_size == 0 {
+            return Err(Error::InvalidChunkSize(self.chunk_size));
+        }
+        if pp.g1_affine_lw_quotients.len() != table_element_size {
+            return Err(Error::LagrangeQuotientsNotEnabled);
+        }
+        let chunk_size = prev_power_of_two(self.chunk_size).min(table_element_size);
+        let fft = OutOfCoreFft::new(&pp.domain_w, chunk_size, &self.scratch_dir)?;
+
+        // Step 1: Copy the padded table values to a scratch file, and find
+        // T_{max} for the position offset encoding.
+        let (mut table_values, max_value) = self.load_table(table_reader, chunk_size)?;
+
+        // Step 2: Compute [T(tau)]_2 and the position multiplier c.
+        let mut poly_coeff_list_t = fft.ifft(&mut table_values)?;
+        let g2_affine_t = commit_g2::<P>(&pp.g2_affine_srs, &mut poly_coeff_list_t, chunk_size)?;
+        drop(poly_coeff_list_t);
+        let fr_position_multiplier =
+            position_multiplier::<P>(|| max_value, &g2_affine_t, pp.segment_size, self.encoding)?;
+
+        // Step 3: Compute the adjusted table values t'_i = t_i + (i mod s) * c,
+        // their sum, [T'(tau)]_1, and [T'(tau)]_2.
+        let segment_size = pp.segment_size;
+        let mut adjusted_table_values =
+            table_values.map(&self.scratch_dir, chunk_size, |start, chunk| {
+                Ok(chunk
+                    .par_iter()
+                    .enumerate()
+                    .map(|(j, &t_i)| {
+                        let fr_j = P::ScalarField::from(((start + j) % segment_size) as u128);
+
+                        t_i + fr_j * fr_position_multiplier
+                    })
+                    .collect())
+            })?;
+        drop(table_values);
+        let mut fr_sum_adjusted_t = P::ScalarField::zero();
+        let mut g1_adjusted_t = P::G1::zero();
+        adjusted_table_values.for_each_chunk(chunk_size, |start, chunk| {
+            fr_sum_adjusted_t += chunk.iter().sum::<P::ScalarField>();
+            let lw_chunk = pp.g1_affine_list_lw.read(start, chunk.len())?;
+            g1_adjusted_t += P::G1::msm_unchecked(&lw_chunk, chunk);
+            Ok(())
+        })?;
+        let g1_affine_adjusted_t = g1_adjusted_t.into_affine();
+        let mut poly_coeff_list_adjusted_t = fft.ifft(&mut adjusted_table_values)?;
+        let g2_affine_adjusted_t = commit_g2::<P>(
+            &pp.g2_affine_srs,
+            &mut poly_coeff_list_adjusted_t,
+            chunk_size,
+        )?;
+        drop(poly_coeff_list_adjusted_t);
+
+        let g1_affine_d = position_offsets_commitment(pp, fr_position_multiplier)?;
+        let hash_representation = hash_representation::<P>(
+            &g1_affine_d,
+            &g2_affine_t,
+            &g2_affine_adjusted_t,
+            self.encoding,
+        )?;
+
+        // Step 4: Compute the quotients without the Toeplitz matrix of the SRS.
+        // With a_k = 1 / (w^k - 1) for k != 0 and a_0 = 0, and
+        // L_j(X) / (X - w^i) = (L_j(X) - w^{j-i} * L_i(X)) / (w^j - w^i),
+        // q_{i, 1} = (w^i / N) * [(T'(X) - t'_i) / (X - w^i)]_1
+        //          = (1 / N) * (G_i - S_i * [L_i]_1 + w^i * t'_i * [U_i]_1),
+        // where G_i = sum_j a_{j-i} * t'_j * [L_j]_1,
+        // S_i = sum_{j != i} t'_j + sum_j a_{j-i} * t'_j,
+        // and U_i(X) = (L_i(X) - 1) / (X - w^i).
+        // Both sums over j are circular correlations with a, whose DFT is
+        // i - (N - 1) / 2, so they take an FFT, a pointwise product, and an
+        // inverse FFT.
+        let domain_size = P::ScalarField::from(table_element_size as u64);
+        let fr_inv_n = domain_size
+            .inverse()
+            .ok_or(Error::FailedToInverseFieldElement)?;
+        let fr_inv_two = P::ScalarField::from(2u64)
+            .inverse()
+            .ok_or(Error::FailedToInverseFieldElement)?;
+        let fr_half_n_minus_one = (domain_size - P::ScalarField::one()) * fr_inv_two;
+        let correlation_factor =
+            |i: usize| (P::ScalarField::from(i as u64) - fr_half_n_minus_one) * fr_inv_n;
+
+        // Step 4-a: G_i / N.
+        let g1_affine_list_lw = &pp.g1_affine_list_lw;
+        let mut g1_list_g =
+            adjusted_table_values.map(&self.scratch_dir, chunk_size, |start, chunk| {
+                let lw_chunk = g1_affine_list_lw.read(start, chunk.len())?;
+
+                Ok(chunk
+                    .par_iter()
+                    .zip(lw_chunk)
+                    .map(|(&t_j, lw_j)| lw_j * t_j)
+                    .collect())
+            })?;
+        let mut g1_list_g = fft.fft(&mut g1_list_g)?;
+        let mut g1_list_g = g1_list_g.map(&self.scratch_dir, chunk_size, |start, chunk| {
+            Ok(chunk
+                .par_iter()
+                .enumerate()
+                .map(|(j, &g_j)| g_j * correlation_factor(start + j))
+                .collect())
+        })?;
+        let mut g1_list_g = fft.ifft(&mut g1_list_g)?;
+
+        // Step 4-b: sum_j a_{j-i} * t'_j / N.
+        let mut fr_list_correlation = fft.fft(&mut adjusted_table_values)?;
+        let mut fr_list_correlation =
+            fr_list_correlation.map(&self.scratch_dir, chunk_size, |start, chunk| {
+                Ok(chunk
+                    .par_iter()
+                    .enumerate()
+                    .map(|(j, &c_j)| c_j * correlation_factor(start + j))
+                    .collect())
+            })?;
+        let mut fr_list_correlation = fft.ifft(&mut fr_list_correlation)?;
+
+        // Step 5: Write the preprocessed parameters in the order of their
+        // serialization, with the quotients computed chunk by chunk.
+        let mut writer = BufWriter::new(writer);
+        serialize(&(table_element_size as u64), &mut writer, self.compress)?;
+        let g1_affine_lw_quotients = &pp.g1_affine_lw_quotients;
+        let domain = pp.domain_w;
+        for start in (0..table_element_size).step_by(chunk_size) {
+            let adjusted_chunk = adjusted_table_values.read(start, chunk_size)?;
+            let g_chunk = g1_list_g.read(start, chunk_size)?;
+            let correlation_chunk = fr_list_correlation.read(start, chunk_size)?;
+            let lw_chunk = g1_affine_list_lw.read(start, chunk_size)?;
+            let lw_quotients_chunk = g1_affine_lw_quotients.read(start, chunk_size)?;
+            let fr_w_start = domain.group_gen.pow([start as u64]);
+            let q1_chunk: Vec<P::G1> = (0..chunk_size)
+                .into_par_iter()
+                .map(|j| {
+                    let fr_w_i = fr_w_start * domain.group_gen.pow([j as u64]);
+                    let t_i = adjusted_chunk[j];
+                    let fr_s_i = (fr_sum_adjusted_t - t_i) * fr_inv_n + correlation_chunk[j];
+
+                    g_chunk[j] - lw_chunk[j] * fr_s_i
+                        + lw_quotients_chunk[j] * (fr_w_i * t_i * fr_inv_n)
+                })
+                .collect();
+            for q1 in P::G1::normalize_batch(&q1_chunk) {
+                serialize(&q1, &mut writer, self.compress)?;
+            }
+        }
+        serialize(&g1_affine_d, &mut writer, self.compress)?;
+        serialize(&g1_affine_adjusted_t, &mut writer, self.compress)?;
+        serialize(&g2_affine_t, &mut writer, self.compress)?;
+        serialize(&g2_affine_adjusted_t, &mut writer, self.compress)?;
+        serialize(&(table_element_size as u64), &mut writer, self.compress)?;
+        adjusted_table_values.for_each_chunk(chunk_size, |_, chunk| {
+            chunk
+                .iter()
+                .try_for_each(|t_i| serialize(t_i, &mut writer, self.compress))
+        })?;
+        serialize(&self.encoding, &mut writer, self.compress)?;
+        serialize(&fr_position_multiplier, &mut writer, self.compress)?;
+        serialize(&hash_representation, &mut writer, self.compress)?;
+        writer
+            .flush()
+            .map_err(|_| Error::FailedToSerializeElement)?;
+
+        Ok(TableVerifierKey {
+            g2_affine_adjusted_t,
+            hash_representation,
+        })
+    }
+
+    // Copies the table to a scratch file, padding each segment with zeros and
+    // the table with the padding segment and copies of the first segment as
+    // in `Table::new`.
+    #[allow(clippy::type_complexity)]
+    fn load_table<R: Read>(
+        &self,
+        table_reader: R,
+        chunk_size: usize,
+    ) -> Result<
+        (
+            ScratchVec<P::ScalarField>,
+            <P::ScalarField as PrimeField>::BigInt,
+        ),
+        Error,
+    > {
+        let pp = self.pp;
+        let mut reader = BufReader::new(table_reader);
+        read_binary_header(pp, &mut reader)?;
+
+        let mut table_values = ScratchVec::create(&self.scratch_dir, pp.table_element_size)?;
+        let mut max_value = <P::ScalarField as PrimeField>::BigInt::default();
+        let mut first_segment = Vec::new();
+        let mut buffer = Vec::with_capacity(chunk_size + pp.segment_size);
+        let mut buffer_start = 0;
+        for segment_index in 0..pp.num_table_segments {
+            let segment = if segment_index < pp.logical_num_table_segments {
+                let mut segment = read_binary_segment(pp, &mut reader, segment_index)?;
+                segment.resize(pp.segment_size, P::ScalarField::zero());
+                if let Some(segment_max) = segment.iter().map(|value| value.into_bigint()).max() {
+                    max_value = max_value.max(segment_max);
+                }
+                if segment_index == 0 {
+                    first_segment = segment.clone();
+                }
+                segment
+            } else if segment_index == pp.padding_segment_index() {
+                vec![P::ScalarField::zero(); pp.segment_size]
+            } else {
+                first_segment.clone()
+            };
+
+            buffer.extend(segment);
+            if buffer.len() >= chunk_size {
+                table_values.write(buffer_start, &buffer)?;
+                buffer_start += buffer.len();
+                buffer.clear();
+            }
+        }
+        table_values.write(buffer_start, &buffer)?;
+
+        Ok((table_values, max_value))
+    }
+}
+
+fn prev_power_of_two(n: usize) -> usize {
+    1 << (usize::BITS - 1 - n.leading_zeros())
+}
+
+fn serialize<T: CanonicalSerialize, W: Write>(
+    value: &T,
+    writer: &mut W,
+    compress: Compress,
+) -> Result<(), Error> {
+    value
+        .serialize_with_mode(writer, compress)
+        .map_err(|_| Error::FailedToSerializeElement)
+}
+
+// Commits to the coefficients in the scratch file with chunked MSMs, reading
+// the SRS chunk by chunk.
+fn commit_g2<P: Pairing>(
+    g2_affine_srs: &LazyPoints<P::G2Affine>,
+    poly_coeff_list: &mut ScratchVec<P::ScalarField>,
+    chunk_size: usize,
+) -> Result<P::G2Affine, Error> {
+    if g2_affine_srs.len() < poly_coeff_list.len() {
+        return Err(Error::InvalidStructuredReferenceStrings);
+    }
+
+    let mut g2_commitment = P::G2::zero();
+    poly_coeff_list.for_each_chunk(chunk_size, |start, chunk| {
+        let srs_chunk = g2_affine_srs.read(start, chunk.len())?;
+        g2_commitment += P::G2::msm_unchecked(&srs_chunk, chunk);
+        Ok(())
+    })?;
+
+    Ok(g2_commitment.into_affine())
+}
+
+static SCRATCH_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A vector of fixed-size elements in a scratch file, which is removed on drop.
+struct ScratchVec<T> {
+    file: File,
+    path: PathBuf,
+    len: usize,
+    element_size: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: CanonicalSerialize + CanonicalDeserialize + Zero + Send + Sync> ScratchVec<T> {
+    fn create(dir: &Path, len: usize) -> Result<Self, Error> {
+        let path = dir.join(format!(
+            "segment-lookup-{}-{}.scratch",
+            std::process::id(),
+            SCRATCH_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))?;
+        let scratch = Self {
+            file,
+            path,
+            len,
+            element_size: T::zero().serialized_size(Compress::No),
+            _marker: PhantomData,
+        };
+        scratch
+            .file
+            .set_len((len * scratch.element_size) as u64)
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))?;
+
+        Ok(scratch)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn seek(&mut self, start: usize) -> Result<(), Error> {
+        self.file
+            .seek(SeekFrom::Start((start * self.element_size) as u64))
+            .map(|_| ())
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))
+    }
+
+    // Reads up to `len` elements from `start`.
+    fn read(&mut self, start: usize, len: usize) -> Result<Vec<T>, Error> {
+        let len = len.min(self.len - start);
+        let mut bytes = vec![0u8; len * self.element_size];
+        self.seek(start)?;
+        self.file
+            .read_exact(&mut bytes)
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))?;
+
+        bytes
+            .par_chunks(self.element_size)
+            .map(|element_bytes| {
+                T::deserialize_with_mode(element_bytes, Compress::No, Validate::No)
+                    .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))
+            })
+            .collect()
+    }
+
+    fn write(&mut self, start: usize, elements: &[T]) -> Result<(), Error> {
+        let element_size = self.element_size;
+        let mut bytes = vec![0u8; elements.len() * element_size];
+        bytes
+            .par_chunks_mut(element_size)
+            .zip(elements)
+            .try_for_each(|(element_bytes, element)| {
+                element.serialize_with_mode(element_bytes, Compress::No)
+            })
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))?;
+        self.seek(start)?;
+
+        self.file
+            .write_all(&bytes)
+            .map_err(|err| Error::FailedToAccessScratchFile(err.to_string()))
+    }
+
+    fn for_each_chunk(
+        &mut self,
+        chunk_size: usize,
+        mut f: impl FnMut(usize, &[T]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        for start in (0..self.len).step_by(chunk_size) {
+            let chunk = self.read(start, chunk_size)?;
+            f(start, &chunk)?;
+        }
+
+        Ok(())
+    }
+
+    // Maps the elements chunk by chunk into a new scratch file.
+    fn map<U: CanonicalSerialize + CanonicalDeserialize + Zero + Send + Sync>(
+        &mut self,
+        dir: &Path,
+        chunk_size: usize,
+        f: impl Fn(usize, &[T]) -> Result<Vec<U>, Error>,
+    ) -> Result<ScratchVec<U>, Error> {
+        let mut output = ScratchVec::create(dir, self.len)?;
+        self.for_each_chunk(chunk_size, |start, chunk| {
+            output.write(start, &f(start, chunk)?)
+        })?;
+
+        Ok(output)
+    }
+}
+
+impl<T> Drop for ScratchVec<T> {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// Four-step FFT over a domain of size N = m * M, with vectors in scratch
+// files and M elements in memory at a time. The input index is n + l * M and
+// the output index is r + m * k for l, r in 0..m and n, k in 0..M:
+// 1. Transform each column (x_{n + l * M})_l over the domain of size m, and
+//    multiply the r-th result by w^{n * r}.
+// 2. Transform each row r over the domain of size M.
+// 3. Transpose the rows into the natural output order.
+struct OutOfCoreFft<'a, F: PrimeField> {
+    domain: Radix2EvaluationDomain<F>,
+    // Domain of size m generated by w^M.
+    domain_columns: Radix2EvaluationDomain<F>,
+    // Domain of size M generated by w^m.
+    domain_rows: Radix2EvaluationDomain<F>,
+    num_rows: usize,
+    row_size: usize,
+    scratch_dir: &'a Path,
+}
+
+impl<'a, F: PrimeField> OutOfCoreFft<'a, F> {
+    fn new(
+        domain: &Radix2EvaluationDomain<F>,
+        chunk_size: usize,
+        scratch_dir: &'a Path,
+    ) -> Result<Self, Error> {
+        let row_size = chunk_size;
+        let num_rows = domain.size() / row_size;
+        if num_rows > row_size {
+            return Err(Error::InvalidChunkSize(chunk_size));
+        }
+        let domain_columns =
+            create_domain_with_generator(domain.group_gen.pow([row_size as u64]), num_rows)?;
+        let domain_rows =
+            create_domain_with_generator(domain.group_gen.pow([num_rows as u64]), row_size)?;
+
+        Ok(Self {
+            domain: *domain,
+            domain_columns,
+            domain_rows,
+            num_rows,
+            row_size,
+            scratch_dir,
+        })
+    }
+
+    fn fft<T>(&self, input: &mut ScratchVec<T>) -> Result<ScratchVec<T>, Error>
+    where
+        T: DomainCoeff<F> + CanonicalSerialize + CanonicalDeserialize,
+    {
+        self.transform(input, false)
+    }
+
+    fn ifft<T>(&self, input: &mut ScratchVec<T>) -> Result<ScratchVec<T>, Error>
+    where
+        T: DomainCoeff<F> + CanonicalSerialize + CanonicalDeserialize,
+    {
+        self.transform(input, true)
+    }
+
+    fn transform<T>(&self, input: &mut ScratchVec<T>, inverse: bool) -> Result<ScratchVec<T>, Error>
+    where
+        T: DomainCoeff<F> + CanonicalSerialize + CanonicalDeserialize,
+    {
+        let num_rows = self.num_rows;
+        let row_size = self.row_size;
+        // Number of columns per block, so that a block has M elements.
+        let block_size = row_size / num_rows;
+        let fr_w = if inverse {
+            self.domain.group_gen_inv
+        } else {
+            self.domain.group_gen
+        };
+
+        // Step 1: Transform the columns and apply the twiddle factors.
+        let mut rows = ScratchVec::create(self.scratch_dir, self.domain.size())?;
+        for column_start in (0..row_size).step_by(block_size) {
+            let block = (0..num_rows)
+                .map(|l| input.read(l * row_size + column_start, block_size))
+                .collect::<Result<Vec<_>, _>>()?;
+            let columns: Vec<Vec<T>> = (0..block_size)
+                .into_par_iter()
+                .map(|j| {
+                    let mut column: Vec<T> = block.iter().map(|row| row[j]).collect();
+                    if inverse {
+                        self.domain_columns.ifft_in_place(&mut column);
+                    } else {
+                        self.domain_columns.fft_in_place(&mut column);
+                    }
+                    let fr_w_n = fr_w.pow([(column_start + j) as u64]);
+                    let mut fr_twiddle = F::one();
+                    for element in column.iter_mut() {
+                        *element *= fr_twiddle;
+                        fr_twiddle *= fr_w_n;
+                    }
+
+                    column
+                })
+                .collect();
+            for r in 0..num_rows {
+                let row: Vec<T> = columns.iter().map(|column| column[r]).collect();
+                rows.write(r * row_size + column_start, &row)?;
+            }
+        }
+
+        // Step 2: Transform the rows.
+        for r in 0..num_rows {
+            let mut row = rows.read(r * row_size, row_size)?;
+            if inverse {
+                self.domain_rows.ifft_in_place(&mut row);
+            } else {
+                self.domain_rows.fft_in_place(&mut row);
+            }
+            rows.write(r * row_size, &row)?;
+        }
+
+        // Step 3: Transpose into the natural order.
+        let mut output = ScratchVec::create(self.scratch_dir, self.domain.size())?;
+        for k_start in (0..row_size).step_by(block_size) {
+            let block = (0..num_rows)
+                .map(|r| rows.read(r * row_size + k_start, block_size))
+                .collect::<Result<Vec<_>, _>>()?;
+            let chunk: Vec<T> = (0..block_size)
+                .flat_map(|j| block.iter().map(move |row| row[j]))
+                .collect();
+            output.write(k_start * num_rows, &chunk)?;
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::{test_rng, UniformRand};
+
+    use crate::table::{rand_segments, Table};
+
+    use super::*;
+
+    type Fr = <Bn254 as Pairing>::ScalarField;
+
+    fn assert_streaming_matches_in_memory(
+        num_table_segments: usize,
+        segment_size: usize,
+        encoding: UniquenessEncoding,
+        chunk_size: usize,
+    ) {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(num_table_segments)
+            .num_witness_segments(2)
+            .segment_size(segment_size)
+            .lagrange_quotients(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let mut segments = rand_segments::generate(&pp);
+        if encoding == UniquenessEncoding::PositionOffset {
+            // Keep s * 2 * (T_{max} + 1) below the modulus.
+            segments = segments
+                .iter()
+                .map(|segment| {
+                    segment
+                        .iter()
+                        .map(|_| Fr::from(u32::rand(&mut rng)))
+                        .collect()
+                })
+                .collect();
+        }
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let mut table_bytes = Vec::new();
+        t.write_binary(&mut table_bytes)
+            .expect("Failed to write table");
+
+        let tpp = t
+            .preprocess_with_encoding(&pp, encoding)
+            .expect("Failed to preprocess table");
+        for compress in [Compress::Yes, Compress::No] {
+            let mut expected = Vec::new();
+            tpp.serialize_with_mode(&mut expected, compress)
+                .expect("Failed to serialize preprocessed parameters");
+
+            let mut streamed = Vec::new();
+            let tvk = StreamingPreprocessor::new(&pp)
+                .encoding(encoding)
+                .chunk_size(chunk_size)
+                .compress(compress)
+                .preprocess(&table_bytes[..], &mut streamed)
+                .expect("Failed to preprocess table");
+
+            assert!(streamed == expected);
+            assert_eq!(
+                tvk.g2_affine_adjusted_t,
+                tpp.verifier_key().g2_affine_adjusted_t
+            );
+            assert_eq!(tvk.hash_representation, tpp.hash_representation);
+        }
+    }
+
+    #[test]
+    fn test_streaming_preprocess() {
+        assert_streaming_matches_in_memory(15, 4, UniquenessEncoding::default(), 8);
+    }
+
+    #[test]
+    fn test_streaming_preprocess_with_padding_and_encodings() {
+        assert_streaming_matches_in_memory(5, 3, UniquenessEncoding::None, 16);
+        assert_streaming_matches_in_memory(5, 3, UniquenessEncoding::PositionOffset, 8);
+        assert_streaming_matches_in_memory(5, 3, UniquenessEncoding::RandomPositionTag, 1024);
+    }
+
+    #[test]
+    fn test_streaming_preprocess_with_mapped_parameters() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(2)
+            .segment_size(4)
+            .lagrange_quotients(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let path = std::env::temp_dir().join(format!(
+            "segment-lookup-streaming-pp-{}.bin",
+            std::process::id()
+        ));
+        let mut pp_bytes = Vec::new();
+        pp.serialize_uncompressed(&mut pp_bytes).unwrap();
+        std::fs::write(&path, &pp_bytes).unwrap();
+        let pp_mapped = PublicParameters::<Bn254>::open_mapped(&path).unwrap();
+
+        let t = Table::<Bn254>::new(&pp, rand_segments::generate(&pp)).unwrap();
+        let mut table_bytes = Vec::new();
+        t.write_binary(&mut table_bytes).unwrap();
+        let mut expected = Vec::new();
+        t.preprocess(&pp)
+            .unwrap()
+            .serialize_compressed(&mut expected)
+            .unwrap();
+
+        let mut streamed = Vec::new();
+        StreamingPreprocessor::new(&pp_mapped)
+            .chunk_size(8)
+            .preprocess(&table_bytes[..], &mut streamed)
+            .unwrap();
+        assert!(streamed == expected);
+        assert_eq!(
+            pp_mapped.verifier_key().unwrap().g2_affine_tau,
+            pp.g2_affine_srs.as_slice().unwrap()[1]
+        );
+        // The SRS was read chunk by chunk, and not decoded as a whole.
+        assert!(pp_mapped.g2_affine_srs.is_mapped());
+        assert!(!pp_mapped.g2_affine_srs.is_decoded());
+        assert!(!pp_mapped.g1_affine_srs.is_decoded());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_streaming_preprocess_without_lagrange_quotients() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(7)
+            .num_witness_segments(2)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let t = Table::<Bn254>::new(&pp, rand_segments::generate(&pp)).unwrap();
+        let mut table_bytes = Vec::new();
+        t.write_binary(&mut table_bytes).unwrap();
+
+        assert!(matches!(
+            StreamingPreprocessor::new(&pp).preprocess(&table_bytes[..], &mut Vec::new()),
+            Err(Error::LagrangeQuotientsNotEnabled)
+        ));
+    }
+
+    #[test]
+    fn test_out_of_core_fft() {
+        let mut rng = test_rng();
+        let domain = Radix2EvaluationDomain::<Fr>::new(64).unwrap();
+        let scratch_dir = std::env::temp_dir();
+        let values: Vec<Fr> = (0..64).map(|_| Fr::rand(&mut rng)).collect();
+        let mut input = ScratchVec::create(&scratch_dir, 64).unwrap();
+        input.write(0, &values).unwrap();
+
+        for chunk_size in [8, 16, 64] {
+            let fft = OutOfCoreFft::new(&domain, chunk_size, &scratch_dir).unwrap();
+            let mut output = fft.fft(&mut input).unwrap();
+            assert_eq!(output.read(0, 64).unwrap(), domain.fft(&values));
+            let mut inverse = fft.ifft(&mut output).unwrap();
+            assert_eq!(inverse.read(0, 64).unwrap(), values);
+        }
+
+        assert!(matches!(
+            OutOfCoreFft::new(&domain, 4, &scratch_dir),
+            Err(Error::InvalidChunkSize(4))
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::domain::roots_of_unity;
 use crate::error::Error;
 use crate::kzg::Kzg;
 use crate::public_parameters::PublicParameters;
@@ -5,32 +6,122 @@ use crate::toeplitz::UpperToeplitz;
 use crate::COMPRESS_MOD;
 use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::Field;
+use ark_ff::{batch_inversion, BigInteger, FftField, Field, PrimeField};
+use ark_poly::domain::DomainCoeff;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
-use ark_serialize::CanonicalSerialize;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
 use ark_std::{One, Zero};
 use blake2::{Blake2b512, Digest};
 use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::Mul;
+use std::sync::OnceLock;
 
 pub struct Table<P: Pairing> {
     pub num_segments: usize,
     pub segment_size: usize,
+    // Number of segments and segment size before padding.
+    pub logical_num_segments: usize,
+    pub logical_segment_size: usize,
     pub values: Vec<P::ScalarField>,
+    // Maps the values of each segment, without padding, to the first segment
+    // index holding them. Built on the first lookup by values.
+    segment_index: OnceLock<HashMap<Vec<P::ScalarField>, usize>>,
 }
 
+/// How the table elements are made unique before the lookup.
+/// The adjusted element at position j of a segment is t + j * c.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UniquenessEncoding {
+    /// No adjustment (c = 0). The caller guarantees that the table elements
+    /// are unique.
+    None = 0,
+    /// c = 2 * (T_{max} + 1), where T_{max} is the largest table value as an
+    /// integer in [0, p). The adjusted values of position j then lie in
+    /// [j * c, j * c + T_{max}], which are disjoint for different positions
+    /// as long as s * c does not exceed p. Fails if s * c may exceed p.
+    /// This is the encoding of earlier versions, so the adjusted values of
+    /// tables within the bound are unchanged.
+    #[default]
+    PositionOffset = 1,
+    /// c is a random challenge derived from the commitment to the table,
+    /// which works for arbitrary field elements.
+    RandomPositionTag = 2,
+}
+
+impl Valid for UniquenessEncoding {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalSerialize for UniquenessEncoding {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        (*self as u8).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        (*self as u8).serialized_size(compress)
+    }
+}
+
+impl CanonicalDeserialize for UniquenessEncoding {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            0 => Ok(Self::None),
+            1 => Ok(Self::PositionOffset),
+            2 => Ok(Self::RandomPositionTag),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
 pub struct TablePreprocessedParameters<P: Pairing> {
     pub(crate) g1_affine_list_q1: Vec<P::G1Affine>,
     pub g1_affine_d: P::G1Affine,
-    // pub(crate) g2_affine_t: P::G2Affine,
+    // [T'(tau)]_1, with which the prover blinds Q_A(X) in zero-knowledge mode.
+    pub(crate) g1_affine_adjusted_t: P::G1Affine,
+    pub(crate) g2_affine_t: P::G2Affine,
     pub(crate) g2_affine_adjusted_t: P::G2Affine,
     pub adjusted_table_values: Vec<P::ScalarField>,
+    pub uniqueness_encoding: UniquenessEncoding,
+    // The multiplier c of the position offsets E(w^i) = (i mod s) * c.
+    pub(crate) fr_position_multiplier: P::ScalarField,
+
+    pub(crate) hash_representation: Vec<u8>,
+}
+
+/// The subset of the table preprocessed parameters needed by the verifier,
+/// whose size does not depend on the table size.
+#[derive(Debug, Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TableVerifierKey<P: Pairing> {
+    // [T(tau)]_2 adjusted by the uniqueness encoding.
+    pub(crate) g2_affine_adjusted_t: P::G2Affine,
 
     pub(crate) hash_representation: Vec<u8>,
 }
 
 impl<P: Pairing> Table<P> {
+    /// Creates a table from n segments of s values each, as set in the public
+    /// parameters. Each segment is padded with zeros to the padded segment
+    /// size. The logical table is followed by the padding segment of zeros
+    /// (see [`PublicParameters::padding_segment_index`]), and the table is
+    /// padded to the padded number of segments with copies of the first
+    /// segment, so that a witness segment that matches a dummy segment also
+    /// matches a real segment.
     pub fn new(
         pp: &PublicParameters<P>,
         segment_values: Vec<Vec<P::ScalarField>>,
@@ -38,28 +129,87 @@ impl<P: Pairing> Table<P> {
         let num_segments = pp.num_table_segments;
         let segment_size = pp.segment_size;
 
-        if segment_values.len() != num_segments {
+        if segment_values.len() != pp.logical_num_table_segments {
             return Err(Error::InvalidNumberOfSegments(segment_values.len()));
         }
 
         let mut values = Vec::with_capacity(num_segments * segment_size);
         for segment in segment_values {
-            if segment.len() != segment_size {
+            if segment.len() != pp.logical_segment_size {
                 return Err(Error::InvalidSegmentSize(segment.len()));
             }
             values.extend_from_slice(&segment);
+            values.resize(
+                values.len() + segment_size - segment.len(),
+                P::ScalarField::zero(),
+            );
+        }
+        values.resize(values.len() + segment_size, P::ScalarField::zero());
+        for _ in pp.padding_segment_index() + 1..num_segments {
+            values.extend_from_within(..segment_size);
         }
 
         Ok(Self {
             num_segments,
             segment_size,
+            logical_num_segments: pp.logical_num_table_segments,
+            logical_segment_size: pp.logical_segment_size,
             values,
+            segment_index: OnceLock::new(),
         })
     }
 
+    /// Returns the index of a segment with the given values (without padding),
+    /// or `None` if the table does not contain it. The index of the segments
+    /// is built on the first call.
+    pub fn find_segment(&self, segment: &[P::ScalarField]) -> Option<usize> {
+        self.segment_index
+            .get_or_init(|| {
+                let mut segment_index = HashMap::with_capacity(self.logical_num_segments);
+                for (i, chunk) in self
+                    .values
+                    .chunks(self.segment_size)
+                    .take(self.logical_num_segments)
+                    .enumerate()
+                {
+                    segment_index
+                        .entry(chunk[..self.logical_segment_size].to_vec())
+                        .or_insert(i);
+                }
+
+                segment_index
+            })
+            .get(segment)
+            .copied()
+    }
+
+    /// Commits to the table values as [T(tau)]_1, which is the statement of a
+    /// dynamic table, i.e., a table committed by the prover at proving time.
+    pub fn commit(&self, pp: &PublicParameters<P>) -> Result<P::G1Affine, Error> {
+        if self.values.len() != pp.table_element_size {
+            return Err(Error::InvalidNumberOfSegments(self.num_segments));
+        }
+
+        let poly_t = DensePolynomial::from_coefficients_vec(pp.domain_w.ifft(&self.values));
+
+        Ok(Kzg::<P::G1>::commit(pp.g1_affine_srs.as_slice()?, &poly_t).into_affine())
+    }
+
+    /// Preprocesses the table with the default [`UniquenessEncoding`].
     pub fn preprocess(
         &self,
         pp: &PublicParameters<P>,
+    ) -> Result<TablePreprocessedParameters<P>, Error> {
+        self.preprocess_with_encoding(pp, UniquenessEncoding::default())
+    }
+
+    /// Preprocesses the table, making the table elements unique with the
+    /// given encoding. The witness must be built from the adjusted table
+    /// values of the returned parameters.
+    pub fn preprocess_with_encoding(
+        &self,
+        pp: &PublicParameters<P>,
+        encoding: UniquenessEncoding,
     ) -> Result<TablePreprocessedParameters<P>, Error> {
         if self.num_segments != pp.num_table_segments {
             return Err(Error::InvalidNumberOfSegments(self.num_segments));
@@ -70,31 +220,27 @@ impl<P: Pairing> Table<P> {
         }
 
         let domain = pp.domain_w;
-        let g1_affine_srs = &pp.g1_affine_srs;
-        let g2_affine_srs = &pp.g2_affine_srs;
+        let g1_affine_srs = pp.g1_affine_srs.as_slice()?;
+        let g2_affine_srs = pp.g2_affine_srs.as_slice()?;
 
         let table_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(&self.values));
-        let g2_t = Kzg::<P::G2>::commit(g2_affine_srs, &table_poly);
+        let g2_affine_t = Kzg::<P::G2>::commit(g2_affine_srs, &table_poly).into_affine();
 
-        // TODO: Make this a feature.
         // Make-Unique process.
-        // Find T_{max}.
-        let max_absolute_value = self
-            .values
-            .par_iter()
-            .map(|&value| {
-                let abs_value = if value < P::ScalarField::zero() {
-                    -value
-                } else {
-                    value
-                };
-                abs_value
-            })
-            .max()
-            .unwrap_or(P::ScalarField::zero());
-        // T'(X) = T(X) + E(X)
-        let fr_two = P::ScalarField::from(2u128);
-        let fr_max_abs_add_one = max_absolute_value + P::ScalarField::one(); // T_{max} + 1
+        // T'(X) = T(X) + E(X), where E(w^i) = j * c for the position j = i mod s
+        // of the element within its segment, and c depends on the encoding.
+        let fr_position_multiplier = position_multiplier::<P>(
+            || {
+                self.values
+                    .par_iter()
+                    .map(|value| value.into_bigint())
+                    .max()
+                    .unwrap_or_default()
+            },
+            &g2_affine_t,
+            self.segment_size,
+            encoding,
+        )?;
         let num_table_segments = pp.num_table_segments;
         let segment_size = pp.segment_size;
         let adjusted_table_values = self
@@ -103,64 +249,406 @@ impl<P: Pairing> Table<P> {
             .zip(0..num_table_segments * segment_size)
             .map(|(&t_i, i)| {
                 let fr_j = P::ScalarField::from((i % segment_size) as u128);
-                let e_i = fr_two * fr_j * fr_max_abs_add_one;
-                let adjusted_t_i = t_i + e_i;
+                let e_i = fr_j * fr_position_multiplier;
 
-                adjusted_t_i
+                t_i + e_i
             })
             .collect::<Vec<_>>();
         let poly_coeff_list_adjusted_t = pp.domain_w.ifft(&adjusted_table_values);
         let poly_adjusted_t = DensePolynomial::from_coefficients_vec(poly_coeff_list_adjusted_t);
-        let g2_adjusted_t = Kzg::<P::G2>::commit(&pp.g2_affine_srs, &poly_adjusted_t);
-
-        let g2_affine_list = P::G2::normalize_batch(&[g2_t, g2_adjusted_t]);
-        let g2_affine_t = g2_affine_list[0];
-        let g2_affine_adjusted_t = g2_affine_list[1];
+        let g1_affine_adjusted_t =
+            Kzg::<P::G1>::commit(g1_affine_srs, &poly_adjusted_t).into_affine();
+        let g2_affine_adjusted_t =
+            Kzg::<P::G2>::commit(g2_affine_srs, &poly_adjusted_t).into_affine();
 
         let g1_affine_list_q1 = compute_quotients::<P>(&poly_adjusted_t, &domain, g1_affine_srs)?;
 
-        let num_witness_segments = pp.num_witness_segments;
-        let mut poly_eval_list_d = (0..num_witness_segments * segment_size)
-            .into_par_iter()
-            .map(|i| {
-                let fr_j = P::ScalarField::from((i % segment_size) as u128);
-                let d_i = fr_two * fr_j * fr_max_abs_add_one;
+        let g1_affine_d = position_offsets_commitment(pp, fr_position_multiplier)?;
 
-                d_i
-            })
-            .collect::<Vec<_>>();
-        pp.domain_v.ifft_in_place(&mut poly_eval_list_d);
-        let poly_coeff_list_d = poly_eval_list_d;
-        let poly_d = DensePolynomial::from_coefficients_vec(poly_coeff_list_d);
-        let g1_affine_d = Kzg::<P::G1>::commit(&pp.g1_affine_srs, &poly_d).into_affine();
-
-        let mut buffer = Vec::new();
-        let mut hasher = Blake2b512::new();
-
-        g1_affine_d
-            .serialize_with_mode(&mut buffer, COMPRESS_MOD)
-            .map_err(|_| Error::FailedToSerializeElement)?;
-        g2_affine_t
-            .serialize_with_mode(&mut buffer, COMPRESS_MOD)
-            .map_err(|_| Error::FailedToSerializeElement)?;
-        g2_affine_adjusted_t
-            .serialize_with_mode(&mut buffer, COMPRESS_MOD)
-            .map_err(|_| Error::FailedToSerializeElement)?;
-
-        hasher.update(&buffer);
-        let hash_representation = hasher.finalize().to_vec();
+        let hash_representation =
+            hash_representation::<P>(&g1_affine_d, &g2_affine_t, &g2_affine_adjusted_t, encoding)?;
 
         Ok(TablePreprocessedParameters {
             g1_affine_list_q1,
-            // g2_affine_t,
+            g2_affine_t,
             g2_affine_adjusted_t,
             g1_affine_d,
+            g1_affine_adjusted_t,
             adjusted_table_values,
+            uniqueness_encoding: encoding,
+            fr_position_multiplier,
+            hash_representation,
+        })
+    }
+}
+
+impl<P: Pairing> TablePreprocessedParameters<P> {
+    /// Returns the hash of the table that is absorbed by the transcript.
+    pub fn hash_representation(&self) -> &[u8] {
+        &self.hash_representation
+    }
+
+    /// Extracts the verifier key of the table.
+    pub fn verifier_key(&self) -> TableVerifierKey<P> {
+        TableVerifierKey {
+            g2_affine_adjusted_t: self.g2_affine_adjusted_t,
+            hash_representation: self.hash_representation.clone(),
+        }
+    }
+
+    // Whether the segment at `segment_index` holds the given values, without
+    // padding, once adjusted with the position offsets.
+    pub(crate) fn has_segment(
+        &self,
+        segment_index: usize,
+        segment_size: usize,
+        segment_values: &[P::ScalarField],
+    ) -> bool {
+        let segment_start = segment_index * segment_size;
+        segment_values.iter().enumerate().all(|(j, &value)| {
+            let fr_adjusted_value =
+                value + P::ScalarField::from(j as u64) * self.fr_position_multiplier;
+            self.adjusted_table_values.get(segment_start + j) == Some(&fr_adjusted_value)
+        })
+    }
+
+    /// Replaces the values of one segment, given without padding, and patches
+    /// the table commitments with an MSM of size ns, and all the quotients
+    /// q_{i, 1} in O(ns log(s)) group operations, instead of preprocessing the
+    /// table again in O(ns log(ns)). Updating segment 0 also updates its
+    /// copies in the dummy segments, which costs up to O(ns log(ns)).
+    /// The public parameters must be built with
+    /// [`crate::public_parameters::PublicParametersBuilder::lagrange_quotients`].
+    /// The position multiplier of the uniqueness encoding is kept, so with
+    /// [`UniquenessEncoding::PositionOffset`] the new values must be below
+    /// the multiplier c = 2 * (T_{max} + 1) of the preprocessed table. Tables with
+    /// [`UniquenessEncoding::RandomPositionTag`] cannot be updated, since the
+    /// tag is derived from the table commitment, and must be preprocessed again.
+    /// The [`Table`] the parameters were preprocessed from is not modified.
+    pub fn update_segment(
+        &mut self,
+        pp: &PublicParameters<P>,
+        segment_index: usize,
+        new_values: &[P::ScalarField],
+    ) -> Result<(), Error> {
+        if segment_index >= pp.logical_num_table_segments {
+            return Err(Error::InvalidSegmentIndex(segment_index));
+        }
+        if new_values.len() != pp.logical_segment_size {
+            return Err(Error::InvalidSegmentSize(new_values.len()));
+        }
+        if pp.g1_affine_lw_quotients.len() != pp.table_element_size {
+            return Err(Error::LagrangeQuotientsNotEnabled);
+        }
+        match self.uniqueness_encoding {
+            UniquenessEncoding::None => {},
+            UniquenessEncoding::PositionOffset => {
+                let fr_bound = self.fr_position_multiplier.into_bigint();
+                if let Some(value) = new_values.iter().find(|v| v.into_bigint() >= fr_bound) {
+                    return Err(Error::TableValuesOutOfRange(
+                        value.into_bigint().num_bits() as usize
+                    ));
+                }
+            },
+            UniquenessEncoding::RandomPositionTag => {
+                return Err(Error::UnsupportedUniquenessEncoding);
+            },
+        }
+
+        // The dummy segments after the padding segment are copies of the
+        // first segment.
+        let segment_size = pp.segment_size;
+        let mut segment_indices = vec![segment_index];
+        if segment_index == 0 {
+            segment_indices.extend(pp.padding_segment_index() + 1..pp.num_table_segments);
+        }
+
+        // Collect the changes of the adjusted table values.
+        let mut deltas: Vec<(usize, P::ScalarField)> = Vec::new();
+        for &index in &segment_indices {
+            for j in 0..segment_size {
+                let i = index * segment_size + j;
+                let t_i = new_values
+                    .get(j)
+                    .copied()
+                    .unwrap_or_else(P::ScalarField::zero);
+                let adjusted_t_i =
+                    t_i + P::ScalarField::from(j as u64) * self.fr_position_multiplier;
+                let delta = adjusted_t_i - self.adjusted_table_values[i];
+                if !delta.is_zero() {
+                    deltas.push((i, delta));
+                    self.adjusted_table_values[i] = adjusted_t_i;
+                }
+            }
+        }
+        if deltas.is_empty() {
+            return Ok(());
+        }
+
+        // The raw and the adjusted table change by the same
+        // sum_i delta_i * [L^W_i(tau)]_2, and [T'(tau)]_1 by the same sum in G1.
+        let domain_w = pp.domain_w;
+        let mut poly_eval_list_delta = vec![P::ScalarField::zero(); domain_w.size()];
+        for &(i, delta) in &deltas {
+            poly_eval_list_delta[i] = delta;
+        }
+        domain_w.ifft_in_place(&mut poly_eval_list_delta);
+        let poly_delta = DensePolynomial::from_coefficients_vec(poly_eval_list_delta);
+        let g2_delta = Kzg::<P::G2>::commit(pp.g2_affine_srs.as_slice()?, &poly_delta);
+        let g2_affine_list = P::G2::normalize_batch(&[
+            self.g2_affine_t + g2_delta,
+            self.g2_affine_adjusted_t + g2_delta,
+        ]);
+        self.g2_affine_t = g2_affine_list[0];
+        self.g2_affine_adjusted_t = g2_affine_list[1];
+        self.g1_affine_adjusted_t = (self.g1_affine_adjusted_t
+            + Kzg::<P::G1>::commit(pp.g1_affine_srs.as_slice()?, &poly_delta))
+        .into_affine();
+
+        // q_{i, 1} = (1 / N) * (G_i - S_i * [L_i]_1 + w^i * t'_i * [U_i]_1) is
+        // linear in the adjusted table values (see `StreamingPreprocessor`), so
+        // it changes by the same expression in the deltas:
+        // G'_i = sum_j a_{j-i} * delta_j * [L_j]_1 and
+        // S'_i = sum_{j != i} delta_j + sum_j a_{j-i} * delta_j,
+        // with a_k = 1 / (w^k - 1) for k != 0 and a_0 = 0. Both sums over j
+        // only have nonzero terms for the changed elements.
+        let domain_size = domain_w.size();
+        let fr_inv_n = domain_w.size_inv;
+        let roots_of_unity_w = roots_of_unity::<P>(&domain_w);
+        let mut fr_list_a: Vec<P::ScalarField> = roots_of_unity_w
+            .iter()
+            .map(|&w_pow_k| w_pow_k - P::ScalarField::one())
+            .collect();
+        batch_inversion(&mut fr_list_a[1..]);
+        let g1_list_delta_lw = deltas
+            .iter()
+            .map(|&(j, delta)| Ok((j, pp.g1_affine_list_lw.get(j)? * (delta * fr_inv_n))))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let g1_list_g = sparse_correlation(&domain_w, &fr_list_a, &g1_list_delta_lw)?;
+        let fr_list_correlation = sparse_correlation(&domain_w, &fr_list_a, &deltas)?;
+        let fr_sum_delta: P::ScalarField = deltas.iter().map(|&(_, delta)| delta).sum();
+        let mut fr_list_delta = vec![P::ScalarField::zero(); domain_size];
+        for &(j, delta) in &deltas {
+            fr_list_delta[j] = delta;
+        }
+        let g1_list_q1 = (0..domain_size)
+            .into_par_iter()
+            .map(|i| {
+                let fr_delta_i = fr_list_delta[i];
+                let fr_s_i = (fr_sum_delta - fr_delta_i + fr_list_correlation[i]) * fr_inv_n;
+                let mut g1_q1 = self.g1_affine_list_q1[i].into_group() + g1_list_g[i]
+                    - pp.g1_affine_list_lw.get(i)? * fr_s_i;
+                if !fr_delta_i.is_zero() {
+                    g1_q1 += pp.g1_affine_lw_quotients.get(i)?
+                        * (roots_of_unity_w[i] * fr_delta_i * fr_inv_n);
+                }
+
+                Ok(g1_q1)
+            })
+            .collect::<Result<Vec<P::G1>, Error>>()?;
+        self.g1_affine_list_q1 = P::G1::normalize_batch(&g1_list_q1);
+
+        self.hash_representation = hash_representation::<P>(
+            &self.g1_affine_d,
+            &self.g2_affine_t,
+            &self.g2_affine_adjusted_t,
+            self.uniqueness_encoding,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<P: Pairing> TableVerifierKey<P> {
+    /// Returns the hash of the table that is absorbed by the transcript.
+    pub fn hash_representation(&self) -> &[u8] {
+        &self.hash_representation
+    }
+
+    // The verifier key of a dynamic table, given the prover's [T(tau)]_2.
+    // Dynamic tables are preprocessed without a uniqueness encoding, since the
+    // verifier cannot compute the adjustment.
+    pub(crate) fn from_dynamic_table_commitment(g2_affine_t: P::G2Affine) -> Result<Self, Error> {
+        let hash_representation = hash_representation::<P>(
+            &P::G1Affine::zero(),
+            &g2_affine_t,
+            &g2_affine_t,
+            UniquenessEncoding::None,
+        )?;
+
+        Ok(Self {
+            g2_affine_adjusted_t: g2_affine_t,
             hash_representation,
         })
     }
 }
 
+// Commits to D(X) over domain V, where D(v^i) = (i mod s) * c are the position
+// offsets of the witness elements.
+pub(crate) fn position_offsets_commitment<P: Pairing>(
+    pp: &PublicParameters<P>,
+    fr_position_multiplier: P::ScalarField,
+) -> Result<P::G1Affine, Error> {
+    let segment_size = pp.segment_size;
+    let num_witness_segments = pp.num_witness_segments;
+    let mut poly_eval_list_d = (0..num_witness_segments * segment_size)
+        .into_par_iter()
+        .map(|i| {
+            let fr_j = P::ScalarField::from((i % segment_size) as u128);
+
+            fr_j * fr_position_multiplier
+        })
+        .collect::<Vec<_>>();
+    pp.domain_v.ifft_in_place(&mut poly_eval_list_d);
+    let poly_coeff_list_d = poly_eval_list_d;
+    let poly_d = DensePolynomial::from_coefficients_vec(poly_coeff_list_d);
+    // Read only the first k * s powers, so that a mapped SRS is not decoded as
+    // a whole.
+    let g1_affine_srs = pp
+        .g1_affine_srs
+        .read(0, num_witness_segments * segment_size)?;
+
+    Ok(Kzg::<P::G1>::commit(&g1_affine_srs, &poly_d).into_affine())
+}
+
+pub(crate) fn hash_representation<P: Pairing>(
+    g1_affine_d: &P::G1Affine,
+    g2_affine_t: &P::G2Affine,
+    g2_affine_adjusted_t: &P::G2Affine,
+    encoding: UniquenessEncoding,
+) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    let mut hasher = Blake2b512::new();
+
+    g1_affine_d
+        .serialize_with_mode(&mut buffer, COMPRESS_MOD)
+        .map_err(|_| Error::FailedToSerializeElement)?;
+    g2_affine_t
+        .serialize_with_mode(&mut buffer, COMPRESS_MOD)
+        .map_err(|_| Error::FailedToSerializeElement)?;
+    g2_affine_adjusted_t
+        .serialize_with_mode(&mut buffer, COMPRESS_MOD)
+        .map_err(|_| Error::FailedToSerializeElement)?;
+    buffer.push(encoding as u8);
+
+    hasher.update(&buffer);
+
+    Ok(hasher.finalize().to_vec())
+}
+
+// Returns the multiplier c of the position offsets E(w^i) = (i mod s) * c.
+pub(crate) fn position_multiplier<P: Pairing>(
+    max_table_value: impl FnOnce() -> <P::ScalarField as PrimeField>::BigInt,
+    g2_affine_t: &P::G2Affine,
+    segment_size: usize,
+    encoding: UniquenessEncoding,
+) -> Result<P::ScalarField, Error> {
+    match encoding {
+        UniquenessEncoding::None => Ok(P::ScalarField::zero()),
+        UniquenessEncoding::PositionOffset => {
+            // Find T_{max} over the canonical integer representatives in [0, p).
+            let max_value = max_table_value();
+            // s * c = s * 2 * (T_{max} + 1) <= 2^{log(s) + 1 + bits(T_{max})}
+            // must not exceed p, so that the adjusted values of different
+            // positions never wrap around.
+            let num_bits = max_value.num_bits() + 1 + segment_size.trailing_zeros();
+            if num_bits >= P::ScalarField::MODULUS_BIT_SIZE {
+                return Err(Error::TableValuesOutOfRange(num_bits as usize));
+            }
+            let fr_max_add_one = P::ScalarField::from_bigint(max_value)
+                .ok_or(Error::TableValuesOutOfRange(num_bits as usize))?
+                + P::ScalarField::one();
+
+            Ok(P::ScalarField::from(2u64) * fr_max_add_one)
+        },
+        UniquenessEncoding::RandomPositionTag => {
+            // Derive the tag from the commitment to the table, which fixes
+            // all the table values before the tag is known.
+            let mut buffer = Vec::new();
+            g2_affine_t
+                .serialize_with_mode(&mut buffer, COMPRESS_MOD)
+                .map_err(|_| Error::FailedToSerializeElement)?;
+            let mut hasher = Blake2b512::new();
+            hasher.update(b"segment_lookup_position_tag");
+            hasher.update(&buffer);
+
+            Ok(P::ScalarField::from_le_bytes_mod_order(&hasher.finalize()))
+        },
+    }
+}
+
+// Computes c_i = sum_j a_{j-i} * v_j for i in 0..N, given the nonzero v_j.
+// The outputs and the inputs are split into blocks of L elements. A block of
+// outputs only depends on a block of inputs through 2L - 1 consecutive a_k,
+// which is a circular convolution of size 2L. With R blocks of inputs that
+// hold a nonzero v_j, this takes O(N * (R + log(L))) operations on T, and L
+// is chosen to minimize it.
+fn sparse_correlation<F: FftField, T: DomainCoeff<F>>(
+    domain: &Radix2EvaluationDomain<F>,
+    fr_list_a: &[F],
+    values: &[(usize, T)],
+) -> Result<Vec<T>, Error> {
+    let domain_size = domain.size();
+    let num_input_blocks = |block_size: usize| {
+        values
+            .iter()
+            .map(|&(j, _)| j / block_size)
+            .collect::<BTreeSet<_>>()
+            .len()
+    };
+    let block_size = (0..=domain_size.trailing_zeros())
+        .map(|log_block_size| 1 << log_block_size)
+        .min_by_key(|&block_size| {
+            2 * num_input_blocks(block_size) + block_size.trailing_zeros() as usize
+        })
+        .unwrap_or(domain_size);
+    let domain_block = Radix2EvaluationDomain::<F>::new(2 * block_size)
+        .ok_or(Error::FailedToCreateEvaluationDomain)?;
+
+    // Transform each block of inputs, reversed within the block.
+    let mut input_blocks: BTreeMap<usize, Vec<T>> = BTreeMap::new();
+    for &(j, v_j) in values {
+        input_blocks
+            .entry(j / block_size)
+            .or_insert_with(|| vec![T::zero(); 2 * block_size])[block_size - 1 - j % block_size] +=
+            v_j;
+    }
+    for input_block in input_blocks.values_mut() {
+        domain_block.fft_in_place(input_block);
+    }
+
+    // For output i = b * L + u and input j = b' * L + m, a_{j-i} is the entry
+    // m - u + L - 1 of h = (a_{(b' - b) * L - L + 1 + t}) for t in 0..2L - 1.
+    let output_blocks = (0..domain_size / block_size)
+        .into_par_iter()
+        .flat_map_iter(|output_block_index| {
+            let mut block = vec![T::zero(); 2 * block_size];
+            for (&input_block_index, input_block) in &input_blocks {
+                let start = (input_block_index * block_size + 2 * domain_size
+                    - output_block_index * block_size
+                    - (block_size - 1))
+                    % domain_size;
+                let mut fr_list_h: Vec<F> = (0..2 * block_size - 1)
+                    .map(|t| fr_list_a[(start + t) % domain_size])
+                    .collect();
+                fr_list_h.push(F::zero());
+                domain_block.fft_in_place(&mut fr_list_h);
+                for ((element, &v_hat), &h_hat) in block.iter_mut().zip(input_block).zip(&fr_list_h)
+                {
+                    let mut product = v_hat;
+                    product *= h_hat;
+                    *element += product;
+                }
+            }
+            domain_block.ifft_in_place(&mut block);
+
+            (0..block_size).map(move |u| block[2 * block_size - 2 - u])
+        })
+        .collect();
+
+    Ok(output_blocks)
+}
+
 fn compute_quotients<P: Pairing>(
     poly_t: &DensePolynomial<P::ScalarField>,
     domain: &Radix2EvaluationDomain<P::ScalarField>,
@@ -219,17 +707,19 @@ fn compute_quotients<P: Pairing>(
 #[cfg(test)]
 pub mod rand_segments {
     use ark_ec::pairing::Pairing;
-    use ark_std::UniformRand;
+    use ark_std::rand::RngCore;
 
     use crate::public_parameters::PublicParameters;
 
+    /// Generates random 64-bit table values, which are within the bound of
+    /// the default [`super::UniquenessEncoding::PositionOffset`].
     pub fn generate<P: Pairing>(pp: &PublicParameters<P>) -> Vec<Vec<P::ScalarField>> {
         let mut rng = ark_std::test_rng();
-        let mut segments = Vec::with_capacity(pp.num_table_segments);
-        for _ in 0..pp.num_table_segments {
-            let mut segment = Vec::with_capacity(pp.segment_size);
-            for _ in 0..pp.segment_size {
-                segment.push(P::ScalarField::rand(&mut rng));
+        let mut segments = Vec::with_capacity(pp.logical_num_table_segments);
+        for _ in 0..pp.logical_num_table_segments {
+            let mut segment = Vec::with_capacity(pp.logical_segment_size);
+            for _ in 0..pp.logical_segment_size {
+                segment.push(P::ScalarField::from(rng.next_u64()));
             }
             segments.push(segment);
         }
@@ -241,10 +731,17 @@ pub mod rand_segments {
 #[cfg(test)]
 mod tests {
     use ark_bn254::Bn254;
-    use ark_std::test_rng;
+    use ark_serialize::{Compress, Validate};
+    use ark_std::{test_rng, UniformRand};
+
+    use crate::prover::prove;
+    use crate::verifier::{verify, verify_debug};
+    use crate::witness::Witness;
 
     use super::*;
 
+    type Fr = <Bn254 as Pairing>::ScalarField;
+
     #[test]
     fn test_table_new() {
         let mut rng = test_rng();
@@ -259,6 +756,43 @@ mod tests {
         Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
     }
 
+    #[test]
+    fn test_table_new_with_padding() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(5)
+            .num_witness_segments(3)
+            .segment_size(3)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate::<Bn254>(&pp);
+
+        let t = Table::<Bn254>::new(&pp, segments.clone()).expect("Failed to create table");
+        assert_eq!(t.num_segments, 8);
+        assert_eq!(t.segment_size, 4);
+        assert_eq!(t.values.len(), 32);
+        for (i, segment) in t.values.chunks(t.segment_size).enumerate() {
+            if i == pp.padding_segment_index() {
+                assert!(segment.iter().all(|v| v.is_zero()));
+                continue;
+            }
+            let expected = &segments[if i < segments.len() { i } else { 0 }];
+            assert_eq!(&segment[..3], &expected[..]);
+            assert!(segment[3].is_zero());
+        }
+
+        // The segment index is only built on the first lookup, and covers the
+        // logical segments without padding.
+        assert!(t.segment_index.get().is_none());
+        for (i, segment) in segments.iter().enumerate() {
+            assert_eq!(t.find_segment(segment), Some(i));
+        }
+        assert_eq!(t.find_segment(&[Fr::zero(); 3]), None);
+        assert!(t.segment_index.get().is_some());
+
+        t.preprocess(&pp).expect("Failed to preprocess table");
+    }
+
     #[test]
     fn test_table_preprocess() {
         let mut rng = test_rng();
@@ -274,4 +808,370 @@ mod tests {
 
         t.preprocess(&pp).expect("Failed to preprocess table");
     }
+
+    // Proves a lookup whose first witness segment is a table segment shifted
+    // by one position, and checks that it is rejected, while the honest
+    // lookup is accepted.
+    fn assert_shifted_lookup_rejected(encoding: UniquenessEncoding) {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(2)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = (0..8)
+            .map(|i| (0..4).map(|j| Fr::from((i * 4 + j) as u64)).collect())
+            .collect();
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess_with_encoding(&pp, encoding).unwrap();
+        let vk = pp.verifier_key().unwrap();
+
+        let queried_segment_indices = [3, 6];
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_ok());
+
+        let mut evaluations = witness.evaluations.clone();
+        evaluations[..4].rotate_left(1);
+        let shifted_witness = Witness {
+            num_segments: witness.num_segments,
+            segment_size: witness.segment_size,
+            num_queries: witness.num_queries,
+            segment_indices: witness.segment_indices.clone(),
+            poly: DensePolynomial::from_coefficients_vec(pp.domain_v.ifft(&evaluations)),
+            evaluations,
+            poly_blinder: witness.poly_blinder.clone(),
+        };
+        let statement = shifted_witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        // The prover does not check the lookup, and the verifier rejects the
+        // quotient A(X) of the shifted segment.
+        let proof = prove(&pp, &tpp, &shifted_witness, statement, &mut rng).unwrap();
+        assert!(matches!(
+            verify_debug(&vk, &tpp.verifier_key(), statement, &proof),
+            Err(Error::Pairing4Failed)
+        ));
+        assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_shifted_lookup_rejected_without_encoding() {
+        assert_shifted_lookup_rejected(UniquenessEncoding::None);
+    }
+
+    #[test]
+    fn test_shifted_lookup_rejected_with_position_offset() {
+        assert_shifted_lookup_rejected(UniquenessEncoding::PositionOffset);
+    }
+
+    #[test]
+    fn test_shifted_lookup_rejected_with_random_position_tag() {
+        assert_shifted_lookup_rejected(UniquenessEncoding::RandomPositionTag);
+    }
+
+    #[test]
+    fn test_position_offset_out_of_range() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let mut segments = rand_segments::generate(&pp);
+        segments[5][1] = -Fr::one();
+
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        assert!(matches!(
+            t.preprocess_with_encoding(&pp, UniquenessEncoding::PositionOffset),
+            Err(Error::TableValuesOutOfRange(_))
+        ));
+        assert!(t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::RandomPositionTag)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_position_offset_boundary() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(2)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        // The largest T_{max} with s * 2 * (T_{max} + 1) <= 2^{MODULUS_BIT_SIZE - 1}.
+        let fr_max = Fr::from(2u64).pow([(Fr::MODULUS_BIT_SIZE - 4) as u64]) - Fr::one();
+        let mut segments: Vec<Vec<Fr>> = (0..8)
+            .map(|i| (0..4).map(|j| Fr::from((i * 4 + j) as u64)).collect())
+            .collect();
+        segments[2] = vec![fr_max, Fr::zero(), fr_max, fr_max];
+
+        let t = Table::<Bn254>::new(&pp, segments.clone()).unwrap();
+        let tpp = t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::PositionOffset)
+            .unwrap();
+        let fr_c = Fr::from(2u64) * (fr_max + Fr::one());
+        assert_eq!(tpp.fr_position_multiplier, fr_c);
+        // The default keeps the position offset of earlier versions.
+        assert_eq!(
+            t.preprocess(&pp).unwrap().adjusted_table_values,
+            tpp.adjusted_table_values
+        );
+
+        // The adjusted values of position j lie in [j * c, j * c + T_{max}] as
+        // integers. The largest value (s - 1) * c + T_{max} does not wrap
+        // around p.
+        for (i, value) in tpp.adjusted_table_values.iter().enumerate() {
+            let fr_lower = Fr::from((i % 4) as u64) * fr_c;
+            assert!(fr_lower.into_bigint() <= value.into_bigint());
+            assert!(value.into_bigint() <= (fr_lower + fr_max).into_bigint());
+        }
+        assert_eq!(tpp.adjusted_table_values[9], fr_c);
+        assert_eq!(
+            tpp.adjusted_table_values[11],
+            Fr::from(3u64) * fr_c + fr_max
+        );
+
+        let witness = Witness::new(&pp, &tpp.adjusted_table_values, &[2, 5]).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(
+            &pp.verifier_key().unwrap(),
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut rng
+        )
+        .is_ok());
+
+        // One more bit of T_{max} may exceed p.
+        segments[2][0] = fr_max + Fr::one();
+        let t = Table::<Bn254>::new(&pp, segments).unwrap();
+        assert!(matches!(
+            t.preprocess_with_encoding(&pp, UniquenessEncoding::PositionOffset),
+            Err(Error::TableValuesOutOfRange(254))
+        ));
+    }
+
+    #[test]
+    fn test_update_segment() {
+        let mut rng = test_rng();
+        for segment_size in [3, 16] {
+            let pp = PublicParameters::<Bn254>::builder()
+                .num_table_segments(5)
+                .num_witness_segments(2)
+                .segment_size(segment_size)
+                .lagrange_quotients(true)
+                .build(&mut rng)
+                .expect("Failed to setup public parameters");
+            let segments: Vec<Vec<Fr>> = (0..5)
+                .map(|i| {
+                    (0..segment_size)
+                        .map(|j| Fr::from((i * segment_size + j) as u64))
+                        .collect()
+                })
+                .collect();
+
+            for encoding in [UniquenessEncoding::None, UniquenessEncoding::PositionOffset] {
+                let t = Table::<Bn254>::new(&pp, segments.clone()).unwrap();
+                let mut tpp = t.preprocess_with_encoding(&pp, encoding).unwrap();
+
+                // Segment 0 is also copied into the dummy segments.
+                let mut new_segments = segments.clone();
+                for segment_index in [2, 0] {
+                    let new_values: Vec<Fr> = (0..segment_size)
+                        .map(|j| Fr::from(((segment_index + 3) * j + 1) as u64))
+                        .collect();
+                    tpp.update_segment(&pp, segment_index, &new_values).unwrap();
+                    new_segments[segment_index] = new_values;
+                }
+
+                let new_t = Table::<Bn254>::new(&pp, new_segments).unwrap();
+                let expected_tpp = new_t.preprocess_with_encoding(&pp, encoding).unwrap();
+                assert_eq!(tpp.g1_affine_list_q1, expected_tpp.g1_affine_list_q1);
+                assert_eq!(tpp.g1_affine_d, expected_tpp.g1_affine_d);
+                assert_eq!(tpp.g2_affine_t, expected_tpp.g2_affine_t);
+                assert_eq!(tpp.g2_affine_adjusted_t, expected_tpp.g2_affine_adjusted_t);
+                assert_eq!(
+                    tpp.adjusted_table_values,
+                    expected_tpp.adjusted_table_values
+                );
+                assert_eq!(tpp.hash_representation, expected_tpp.hash_representation);
+            }
+        }
+
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(5)
+            .num_witness_segments(2)
+            .segment_size(3)
+            .lagrange_quotients(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments: Vec<Vec<Fr>> = (0..5)
+            .map(|i| (0..3).map(|j| Fr::from((i * 3 + j) as u64)).collect())
+            .collect();
+        let t = Table::<Bn254>::new(&pp, segments).unwrap();
+
+        // The new values must be below the position multiplier
+        // c = 2 * (T_{max} + 1) = 30 of the table.
+        let mut tpp = t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::PositionOffset)
+            .unwrap();
+        assert!(matches!(
+            tpp.update_segment(&pp, 1, &[Fr::from(30u64), Fr::zero(), Fr::zero()]),
+            Err(Error::TableValuesOutOfRange(_))
+        ));
+        assert!(tpp
+            .update_segment(&pp, 1, &[Fr::from(29u64), Fr::zero(), Fr::zero()])
+            .is_ok());
+        assert!(matches!(
+            tpp.update_segment(&pp, 5, &[Fr::zero(); 3]),
+            Err(Error::InvalidSegmentIndex(5))
+        ));
+
+        // The random position tag depends on the table, so it cannot be kept.
+        let mut tpp = t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::RandomPositionTag)
+            .unwrap();
+        assert!(matches!(
+            tpp.update_segment(&pp, 1, &[Fr::zero(); 3]),
+            Err(Error::UnsupportedUniquenessEncoding)
+        ));
+
+        // The update needs the Lagrange quotients.
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(5)
+            .num_witness_segments(2)
+            .segment_size(3)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let mut tpp = t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::None)
+            .unwrap();
+        assert!(matches!(
+            tpp.update_segment(&pp, 1, &[Fr::zero(); 3]),
+            Err(Error::LagrangeQuotientsNotEnabled)
+        ));
+    }
+
+    #[test]
+    fn test_prove_and_verify_after_update_segment() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(2)
+            .segment_size(4)
+            .lagrange_quotients(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).unwrap();
+        let mut tpp = t
+            .preprocess_with_encoding(&pp, UniquenessEncoding::None)
+            .unwrap();
+
+        let new_values: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        tpp.update_segment(&pp, 6, &new_values).unwrap();
+
+        let witness = Witness::new(&pp, &tpp.adjusted_table_values, &[6, 1]).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        assert!(verify(
+            &pp.verifier_key().unwrap(),
+            &tpp.verifier_key(),
+            statement,
+            &proof,
+            &mut rng
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_table_preprocessed_parameters_serialization() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).expect("Failed to preprocess table");
+
+        for compress in [Compress::Yes, Compress::No] {
+            let mut bytes = Vec::new();
+            tpp.serialize_with_mode(&mut bytes, compress).unwrap();
+
+            let tpp_deserialized = TablePreprocessedParameters::<Bn254>::deserialize_with_mode(
+                &bytes[..],
+                compress,
+                Validate::Yes,
+            )
+            .unwrap();
+
+            assert_eq!(tpp.g1_affine_list_q1, tpp_deserialized.g1_affine_list_q1);
+            assert_eq!(tpp.g1_affine_d, tpp_deserialized.g1_affine_d);
+            assert_eq!(
+                tpp.g2_affine_adjusted_t,
+                tpp_deserialized.g2_affine_adjusted_t
+            );
+            assert_eq!(
+                tpp.adjusted_table_values,
+                tpp_deserialized.adjusted_table_values
+            );
+            assert_eq!(
+                tpp.uniqueness_encoding,
+                tpp_deserialized.uniqueness_encoding
+            );
+            assert_eq!(
+                tpp.hash_representation,
+                tpp_deserialized.hash_representation
+            );
+        }
+    }
+
+    #[test]
+    fn test_table_verifier_key() {
+        let mut rng = test_rng();
+        let tvk_sizes: Vec<usize> = [8, 32]
+            .into_iter()
+            .map(|num_table_segments| {
+                let pp = PublicParameters::<Bn254>::builder()
+                    .num_table_segments(num_table_segments)
+                    .num_witness_segments(2)
+                    .segment_size(4)
+                    .build(&mut rng)
+                    .expect("Failed to setup public parameters");
+                let segments = rand_segments::generate(&pp);
+                let t = Table::<Bn254>::new(&pp, segments).unwrap();
+                let tpp = t.preprocess(&pp).unwrap();
+
+                // The verifier only needs the deserialized key.
+                let mut bytes = Vec::new();
+                tpp.verifier_key().serialize_compressed(&mut bytes).unwrap();
+                let tvk = TableVerifierKey::<Bn254>::deserialize_compressed(&bytes[..]).unwrap();
+
+                let witness = Witness::new(&pp, &tpp.adjusted_table_values, &[3, 1]).unwrap();
+                let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+                let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+                assert!(verify(
+                    &pp.verifier_key().unwrap(),
+                    &tvk,
+                    statement,
+                    &proof,
+                    &mut rng
+                )
+                .is_ok());
+
+                bytes.len()
+            })
+            .collect();
+
+        assert_eq!(tvk_sizes[0], tvk_sizes[1]);
+    }
 }
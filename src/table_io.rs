@@ -0,0 +1,392 @@
+use crate::error::Error;
+use crate::public_parameters::PublicParameters;
+use crate::table::Table;
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+// Table files hold one segment per row, without padding. Rows and columns in
+// parse errors are counted from 1.
+//
+// CSV and JSON values are decimal or 0x-prefixed hexadecimal integers below
+// the field modulus. A JSON value is either a string or a non-negative integer
+// number. In CSV files, empty lines and lines starting with '#' are skipped.
+//
+// The binary format is the number of segments and the segment size as
+// little-endian u64, followed by the values in row-major order, each in its
+// canonical little-endian encoding.
+impl<P: Pairing> Table<P> {
+    /// Loads a table from CSV with comma-separated values.
+    pub fn from_csv<R: Read>(pp: &PublicParameters<P>, reader: R) -> Result<Self, Error> {
+        let mut segments = Vec::with_capacity(pp.logical_num_table_segments);
+        for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|err| Error::FailedToReadTable(err.to_string()))?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let row = line_index + 1;
+            let segment = line
+                .split(',')
+                .enumerate()
+                .map(|(column_index, value)| {
+                    parse_field_element(value.trim())
+                        .map_err(|reason| parse_error(row, column_index + 1, reason))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            check_segment_size(pp, row, segment.len())?;
+            segments.push(segment);
+        }
+
+        Self::new(pp, segments)
+    }
+
+    /// Loads a table from JSON, as an array of segments.
+    pub fn from_json<R: Read>(pp: &PublicParameters<P>, reader: R) -> Result<Self, Error> {
+        let rows: Vec<Vec<Value>> = serde_json::from_reader(BufReader::new(reader))
+            .map_err(|err| Error::FailedToReadTable(err.to_string()))?;
+
+        let segments = rows
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                check_segment_size(pp, row_index + 1, row.len())?;
+                row.iter()
+                    .enumerate()
+                    .map(|(column_index, value)| {
+                        let element = match value {
+                            Value::String(value) => parse_field_element(value),
+                            Value::Number(number) => number
+                                .as_u64()
+                                .map(P::ScalarField::from)
+                                .ok_or_else(|| format!("{number} is not a non-negative integer")),
+                            _ => Err(format!("{value} is not a string or a number")),
+                        };
+                        element
+                            .map_err(|reason| parse_error(row_index + 1, column_index + 1, reason))
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(pp, segments)
+    }
+
+    /// Loads a table from the binary format.
+    pub fn from_binary<R: Read>(pp: &PublicParameters<P>, reader: R) -> Result<Self, Error> {
+        let mut reader = BufReader::new(reader);
+        read_binary_header(pp, &mut reader)?;
+        let segments = (0..pp.logical_num_table_segments)
+            .map(|row_index| read_binary_segment(pp, &mut reader, row_index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(pp, segments)
+    }
+
+    /// Writes the table, without padding, as CSV with decimal values.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let mut writer = BufWriter::new(writer);
+        for segment in self.logical_segments() {
+            let line = segment
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            writeln!(writer, "{line}").map_err(|err| Error::FailedToWriteTable(err.to_string()))?;
+        }
+
+        writer
+            .flush()
+            .map_err(|err| Error::FailedToWriteTable(err.to_string()))
+    }
+
+    /// Writes the table, without padding, as JSON with decimal string values.
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let rows: Vec<Vec<String>> = self
+            .logical_segments()
+            .map(|segment| segment.iter().map(|value| value.to_string()).collect())
+            .collect();
+
+        let mut writer = BufWriter::new(writer);
+        serde_json::to_writer(&mut writer, &rows)
+            .map_err(|err| Error::FailedToWriteTable(err.to_string()))?;
+        writer
+            .flush()
+            .map_err(|err| Error::FailedToWriteTable(err.to_string()))
+    }
+
+    /// Writes the table, without padding, in the binary format.
+    pub fn write_binary<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let mut writer = BufWriter::new(writer);
+        for size in [self.logical_num_segments, self.logical_segment_size] {
+            writer
+                .write_all(&(size as u64).to_le_bytes())
+                .map_err(|err| Error::FailedToWriteTable(err.to_string()))?;
+        }
+        for value in self.logical_segments().flatten() {
+            value
+                .serialize_with_mode(&mut writer, Compress::No)
+                .map_err(|err| Error::FailedToWriteTable(err.to_string()))?;
+        }
+
+        writer
+            .flush()
+            .map_err(|err| Error::FailedToWriteTable(err.to_string()))
+    }
+
+    fn logical_segments(&self) -> impl Iterator<Item = &[P::ScalarField]> {
+        self.values
+            .chunks(self.segment_size)
+            .take(self.logical_num_segments)
+            .map(|segment| &segment[..self.logical_segment_size])
+    }
+}
+
+// Parses a decimal or 0x-prefixed hexadecimal integer, and rejects values
+// that are not below the field modulus instead of reducing them.
+fn parse_field_element<F: PrimeField>(value: &str) -> Result<F, String> {
+    let (digits, radix) = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(digits) => (digits, 16),
+        None => (value, 10),
+    };
+    if digits.is_empty() {
+        return Err(format!("{value:?} is not an integer"));
+    }
+
+    // Little-endian bytes of the integer, without trailing zeros.
+    let max_num_bytes = F::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+    let mut bytes: Vec<u8> = Vec::with_capacity(max_num_bytes);
+    for c in digits.chars() {
+        let mut carry = c
+            .to_digit(radix)
+            .ok_or_else(|| format!("{value:?} is not an integer"))?;
+        for byte in bytes.iter_mut() {
+            let product = *byte as u32 * radix + carry;
+            *byte = product as u8;
+            carry = product >> 8;
+        }
+        if carry > 0 {
+            bytes.push(carry as u8);
+        }
+        if bytes.len() > max_num_bytes {
+            return Err(format!("{value} is not below the field modulus"));
+        }
+    }
+
+    let element = F::from_le_bytes_mod_order(&bytes);
+    let mut element_bytes = element.into_bigint().to_bytes_le();
+    while element_bytes.last() == Some(&0) {
+        element_bytes.pop();
+    }
+    if element_bytes != bytes {
+        return Err(format!("{value} is not below the field modulus"));
+    }
+
+    Ok(element)
+}
+
+fn check_segment_size<P: Pairing>(
+    pp: &PublicParameters<P>,
+    row: usize,
+    num_values: usize,
+) -> Result<(), Error> {
+    if num_values == pp.logical_segment_size {
+        return Ok(());
+    }
+
+    Err(parse_error(
+        row,
+        num_values.min(pp.logical_segment_size) + 1,
+        format!(
+            "expected {} values, found {}",
+            pp.logical_segment_size, num_values
+        ),
+    ))
+}
+
+fn parse_error(row: usize, column: usize, reason: String) -> Error {
+    Error::TableParseFailed {
+        row,
+        column,
+        reason,
+    }
+}
+
+// Reads the header of the binary format and checks the sizes against the
+// public parameters.
+pub(crate) fn read_binary_header<P: Pairing, R: Read>(
+    pp: &PublicParameters<P>,
+    reader: &mut R,
+) -> Result<(), Error> {
+    let num_segments = read_u64(reader)?;
+    let segment_size = read_u64(reader)?;
+    if num_segments != pp.logical_num_table_segments {
+        return Err(Error::InvalidNumberOfSegments(num_segments));
+    }
+    if segment_size != pp.logical_segment_size {
+        return Err(Error::InvalidSegmentSize(segment_size));
+    }
+
+    Ok(())
+}
+
+// Reads the segment at the given row of the binary format.
+pub(crate) fn read_binary_segment<P: Pairing, R: Read>(
+    pp: &PublicParameters<P>,
+    reader: &mut R,
+    row_index: usize,
+) -> Result<Vec<P::ScalarField>, Error> {
+    let element_size = P::ScalarField::zero().serialized_size(Compress::No);
+    let mut bytes = vec![0u8; element_size * pp.logical_segment_size];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|err| Error::FailedToReadTable(err.to_string()))?;
+
+    bytes
+        .chunks(element_size)
+        .enumerate()
+        .map(|(column_index, element_bytes)| {
+            P::ScalarField::deserialize_with_mode(element_bytes, Compress::No, Validate::Yes)
+                .map_err(|_| {
+                    parse_error(
+                        row_index + 1,
+                        column_index + 1,
+                        "value is not below the field modulus".to_string(),
+                    )
+                })
+        })
+        .collect()
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<usize, Error> {
+    let mut bytes = [0u8; 8];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|err| Error::FailedToReadTable(err.to_string()))?;
+
+    usize::try_from(u64::from_le_bytes(bytes))
+        .map_err(|err| Error::FailedToReadTable(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::Bn254;
+    use ark_std::test_rng;
+
+    use crate::table::rand_segments;
+
+    use super::*;
+
+    type Fr = <Bn254 as Pairing>::ScalarField;
+
+    fn setup() -> PublicParameters<Bn254> {
+        let mut rng = test_rng();
+        PublicParameters::builder()
+            .num_table_segments(3)
+            .num_witness_segments(2)
+            .segment_size(3)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters")
+    }
+
+    fn assert_parse_failed(result: Result<Table<Bn254>, Error>, row: usize, column: usize) {
+        match result {
+            Err(Error::TableParseFailed {
+                row: r, column: c, ..
+            }) => assert_eq!((r, c), (row, column)),
+            Err(err) => panic!("Unexpected error: {err:?}"),
+            Ok(_) => panic!("Table parsing should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_table_csv_round_trip() {
+        let pp = setup();
+        let segments = rand_segments::generate::<Bn254>(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+
+        let mut csv = Vec::new();
+        t.write_csv(&mut csv).expect("Failed to write table");
+        let loaded = Table::<Bn254>::from_csv(&pp, &csv[..]).expect("Failed to load table");
+
+        assert_eq!(loaded.values, t.values);
+    }
+
+    #[test]
+    fn test_table_json_round_trip() {
+        let pp = setup();
+        let segments = rand_segments::generate::<Bn254>(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+
+        let mut json = Vec::new();
+        t.write_json(&mut json).expect("Failed to write table");
+        let loaded = Table::<Bn254>::from_json(&pp, &json[..]).expect("Failed to load table");
+
+        assert_eq!(loaded.values, t.values);
+    }
+
+    #[test]
+    fn test_table_binary_round_trip() {
+        let pp = setup();
+        let segments = rand_segments::generate::<Bn254>(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+
+        let mut binary = Vec::new();
+        t.write_binary(&mut binary).expect("Failed to write table");
+        assert_eq!(binary.len(), 16 + 3 * 3 * 32);
+        let loaded = Table::<Bn254>::from_binary(&pp, &binary[..]).expect("Failed to load table");
+
+        assert_eq!(loaded.values, t.values);
+
+        // A value equal to the modulus is rejected.
+        let modulus = Fr::MODULUS.to_bytes_le();
+        binary[16 + 4 * 32..16 + 5 * 32].copy_from_slice(&modulus);
+        assert_parse_failed(Table::<Bn254>::from_binary(&pp, &binary[..]), 2, 2);
+    }
+
+    #[test]
+    fn test_table_from_csv_and_json_with_decimal_and_hex() {
+        let pp = setup();
+        let csv = "# segments\n1, 2, 0x3\n\n0x04,5,6\n7,8,0X9\n";
+        let json = r#"[[1, "2", "0x3"], ["0x04", 5, "6"], [7, 8, "0X9"]]"#;
+        let expected: Vec<Vec<Fr>> = (0..3)
+            .map(|i| (1..=3).map(|j| Fr::from(3 * i + j)).collect())
+            .collect();
+        let t = Table::<Bn254>::new(&pp, expected).expect("Failed to create table");
+
+        let from_csv = Table::<Bn254>::from_csv(&pp, csv.as_bytes()).expect("Failed to load CSV");
+        let from_json =
+            Table::<Bn254>::from_json(&pp, json.as_bytes()).expect("Failed to load JSON");
+
+        assert_eq!(from_csv.values, t.values);
+        assert_eq!(from_json.values, t.values);
+    }
+
+    #[test]
+    fn test_table_parse_errors() {
+        let pp = setup();
+        let modulus = Fr::MODULUS.to_string();
+
+        let csv = "1,2,3\n4,x5,6\n7,8,9\n";
+        assert_parse_failed(Table::<Bn254>::from_csv(&pp, csv.as_bytes()), 2, 2);
+        let csv = format!("1,2,3\n4,5,6\n7,8,{modulus}\n");
+        assert_parse_failed(Table::<Bn254>::from_csv(&pp, csv.as_bytes()), 3, 3);
+        let csv = "# header\n1,2,3\n4,5\n7,8,9\n";
+        assert_parse_failed(Table::<Bn254>::from_csv(&pp, csv.as_bytes()), 3, 3);
+
+        let json = r#"[[1, 2, 3], [4, 5, 6], [7, -8, 9]]"#;
+        assert_parse_failed(Table::<Bn254>::from_json(&pp, json.as_bytes()), 3, 2);
+        let json = r#"[[1, 2, 3, 4], [4, 5, 6], [7, 8, 9]]"#;
+        assert_parse_failed(Table::<Bn254>::from_json(&pp, json.as_bytes()), 1, 4);
+
+        let csv = "1,2,3\n4,5,6\n";
+        let res = Table::<Bn254>::from_csv(&pp, csv.as_bytes());
+        assert!(matches!(res, Err(Error::InvalidNumberOfSegments(2))));
+    }
+}
@@ -4,7 +4,7 @@ use rayon::prelude::*;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
-/// Modified from: https://github.com/geometryxyz/fk/blob/main/src/toeplitz.rs
+// Modified from: https://github.com/geometryxyz/fk/blob/main/src/toeplitz.rs
 
 /*
     fm f(m-1) ... f1
@@ -1,23 +1,42 @@
 use crate::error::Error;
-use ark_ff::PrimeField;
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonSponge};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField};
 use ark_serialize::CanonicalSerialize;
 use merlin::Transcript as MerlinTranscript;
 use std::marker::PhantomData;
 
-/// Modified from https://github.com/caulk-crypto/caulk/blob/main/src/transcript.rs
+pub use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
 
-#[derive(Copy, Clone)]
-pub(crate) enum Label {
+// Modified from https://github.com/caulk-crypto/caulk/blob/main/src/transcript.rs
+
+/// The label that separates the lookup on the fresh transcript of
+/// [`crate::prover::prove`] and the other functions that do not take a
+/// transcript. A proof made with them verifies with the `_with_transcript`
+/// verifiers on a fresh [`Transcript`] and the default options.
+pub const DEFAULT_LABEL: &[u8] = b"segment_lookup";
+
+/// The labels of the messages and challenges of the protocol.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Label {
     ChallengeBeta,
     ChallengeDelta,
+    ChallengeEpsilon,
     ChallengeGamma,
     ChallengeEta,
     ChallengeCaulkAlpha,
     ChallengeCaulkBeta,
+    ChallengeColumn,
 
     PublicParameters,
     TablePreprocessedParameters,
     Statement,
+    NumQueries,
+    ColumnStatements,
+    DynamicTable,
+    DomainSeparator,
+    Context,
 
     G1M,
     G1MDivW,
@@ -27,12 +46,20 @@ pub(crate) enum Label {
     G1Ql,
     G1D,
     G1Qd,
+    G1Hm,
+    G1Qt,
+    G1Ga,
+    G1Gb,
     G1A,
     G1Qa,
     G1Qb,
     G1A0,
     G1B0,
-    G1Px,
+    G1Pa,
+    G1Ha,
+    G1Rb,
+    G1Hb,
+    G1Pb,
 
     FrB0AtGamma,
     FrFAtGamma,
@@ -42,6 +69,8 @@ pub(crate) enum Label {
     FrQlAtGamma,
     FrDAtGamma,
     FrQdAtGamma,
+    FrRAtGamma,
+    FrGbAtGamma,
 
     CaulkG1D,
     CaulkG1UBar,
@@ -54,13 +83,20 @@ impl Label {
         match self {
             Label::ChallengeBeta => b"beta",
             Label::ChallengeDelta => b"delta",
+            Label::ChallengeEpsilon => b"epsilon",
             Label::ChallengeGamma => b"gamma",
             Label::ChallengeEta => b"eta",
             Label::ChallengeCaulkAlpha => b"caulk_alpha",
             Label::ChallengeCaulkBeta => b"caulk_beta",
+            Label::ChallengeColumn => b"column",
             Label::PublicParameters => b"public_parameters",
             Label::TablePreprocessedParameters => b"table_preprocessed_parameters",
             Label::Statement => b"statement",
+            Label::NumQueries => b"num_queries",
+            Label::ColumnStatements => b"column_statements",
+            Label::DynamicTable => b"dynamic_table",
+            Label::DomainSeparator => b"domain_separator",
+            Label::Context => b"context",
             Label::G1M => b"g1_m",
             Label::G1MDivW => b"g1_m_div_w",
             Label::G1Qm => b"g1_qm",
@@ -69,12 +105,20 @@ impl Label {
             Label::G1Ql => b"g1_ql",
             Label::G1D => b"g1_d",
             Label::G1Qd => b"g1_qd",
+            Label::G1Hm => b"g1_hm",
+            Label::G1Qt => b"g1_qt",
+            Label::G1Ga => b"g1_ga",
+            Label::G1Gb => b"g1_gb",
             Label::G1A => b"g1_a",
             Label::G1Qa => b"g1_qa",
             Label::G1Qb => b"g1_qb",
             Label::G1A0 => b"g1_a0",
             Label::G1B0 => b"g1_b0",
-            Label::G1Px => b"g1_px",
+            Label::G1Pa => b"g1_pa",
+            Label::G1Ha => b"g1_ha",
+            Label::G1Rb => b"g1_rb",
+            Label::G1Hb => b"g1_hb",
+            Label::G1Pb => b"g1_pb",
             Label::FrB0AtGamma => b"fr_b0_at_gamma",
             Label::FrFAtGamma => b"fr_f_at_gamma",
             Label::FrLAtGamma => b"fr_l at_gamma",
@@ -83,6 +127,8 @@ impl Label {
             Label::FrQlAtGamma => b"fr_ql_at_gamma",
             Label::FrDAtGamma => b"fr_d_at_gamma",
             Label::FrQdAtGamma => b"fr_qd_at_gamma",
+            Label::FrRAtGamma => b"fr_r_at_gamma",
+            Label::FrGbAtGamma => b"fr_gb_at_gamma",
             Label::CaulkG1D => b"caulk_g1_d",
             Label::CaulkG1UBar => b"caulk_g1_u_bar",
             Label::CaulkG1H2 => b"caulk_g1_h2",
@@ -91,7 +137,50 @@ impl Label {
     }
 }
 
-pub(crate) struct Transcript<F: PrimeField> {
+/// The Fiat-Shamir transcript of the protocol over the scalar field `F`.
+/// The prover and the verifier append the same messages in the same order,
+/// and derive the verifier challenges from them.
+pub trait TranscriptProtocol<F: PrimeField> {
+    /// Append a scalar field element to the transcript
+    fn append_scalar(&mut self, label: Label, scalar: &F) -> Result<(), Error>;
+
+    /// Append a point of a curve with the scalar field `F` to the transcript
+    fn append_point<G: AffineRepr<ScalarField = F>>(
+        &mut self,
+        label: Label,
+        point: &G,
+    ) -> Result<(), Error>;
+
+    /// Append a byte string to the transcript
+    fn append_bytes(&mut self, label: Label, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Get a challenge bound to all the elements appended so far
+    fn squeeze_challenge(&mut self, label: Label) -> Result<F, Error>;
+
+    fn append_scalars(&mut self, labels_and_scalars: &[(Label, F)]) -> Result<(), Error> {
+        for (label, scalar) in labels_and_scalars {
+            self.append_scalar(*label, scalar)?;
+        }
+
+        Ok(())
+    }
+
+    fn append_points<G: AffineRepr<ScalarField = F>>(
+        &mut self,
+        labels_and_points: &[(Label, G)],
+    ) -> Result<(), Error> {
+        for (label, point) in labels_and_points {
+            self.append_point(*label, point)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The default transcript, based on Merlin (STROBE), which absorbs the
+/// uncompressed serialization of the elements.
+#[derive(Clone)]
+pub struct Transcript<F: PrimeField> {
     transcript: MerlinTranscript,
     _marker: PhantomData<F>,
 }
@@ -103,48 +192,283 @@ impl<F: PrimeField> Default for Transcript<F> {
 }
 
 impl<F: PrimeField> Transcript<F> {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             transcript: MerlinTranscript::new(b"Init SegLookup Transcript"),
-            _marker: PhantomData::default(),
+            _marker: PhantomData,
         }
     }
+}
+
+impl<F: PrimeField> TranscriptProtocol<F> for Transcript<F> {
+    fn append_scalar(&mut self, label: Label, scalar: &F) -> Result<(), Error> {
+        self.transcript.append_scalar(label, scalar)
+    }
+
+    fn append_point<G: AffineRepr<ScalarField = F>>(
+        &mut self,
+        label: Label,
+        point: &G,
+    ) -> Result<(), Error> {
+        self.transcript.append_point(label, point)
+    }
+
+    fn append_bytes(&mut self, label: Label, bytes: &[u8]) -> Result<(), Error> {
+        TranscriptProtocol::<F>::append_bytes(&mut self.transcript, label, bytes)
+    }
+
+    fn squeeze_challenge(&mut self, label: Label) -> Result<F, Error> {
+        self.transcript.squeeze_challenge(label)
+    }
+}
+
+// A Merlin transcript of an outer protocol can be passed to
+// `prove_with_transcript` and `verify_with_transcript` as it is.
+impl<F: PrimeField> TranscriptProtocol<F> for MerlinTranscript {
+    fn append_scalar(&mut self, label: Label, scalar: &F) -> Result<(), Error> {
+        append_serialized(self, label, scalar)
+    }
+
+    fn append_point<G: AffineRepr<ScalarField = F>>(
+        &mut self,
+        label: Label,
+        point: &G,
+    ) -> Result<(), Error> {
+        append_serialized(self, label, point)
+    }
+
+    fn append_bytes(&mut self, label: Label, bytes: &[u8]) -> Result<(), Error> {
+        self.append_message(label.as_bytes(), bytes);
+
+        Ok(())
+    }
 
     /// Get a uniform random field element for field size < 384
-    pub(crate) fn squeeze_challenge(&mut self, label: Label) -> Result<F, Error> {
+    fn squeeze_challenge(&mut self, label: Label) -> Result<F, Error> {
         let mut bytes = [0u8; 64];
-        self.transcript
-            .challenge_bytes(label.as_bytes(), &mut bytes);
+        self.challenge_bytes(label.as_bytes(), &mut bytes);
         let challenge = F::from_le_bytes_mod_order(bytes.as_ref());
-        self.append_element(label, &challenge)?;
+        self.append_scalar(label, &challenge)?;
 
         Ok(challenge)
     }
+}
+
+fn append_serialized<T: CanonicalSerialize>(
+    transcript: &mut MerlinTranscript,
+    label: Label,
+    element: &T,
+) -> Result<(), Error> {
+    let mut buf = vec![];
+    element
+        .serialize_uncompressed(&mut buf)
+        .map_err(|_| Error::FailedToSerializeElement)?;
+    transcript.append_message(label.as_bytes(), buf.as_ref());
+
+    Ok(())
+}
 
-    /// Append a field/group element to the transcript
-    pub(crate) fn append_element<T: CanonicalSerialize>(
+/// A transcript based on the Poseidon sponge over the scalar field `F` of a
+/// curve, for proofs that are verified inside a circuit.
+///
+/// The elements are absorbed as elements of `F`: a scalar as itself, a point
+/// as the limbs of its coordinates followed by its infinity flag, and bytes
+/// as chunks of (MODULUS_BIT_SIZE - 1) / 8 bytes after their length. A base
+/// field element is split into little-endian limbs of MODULUS_BIT_SIZE - 1
+/// bits of `F`. Labels are absorbed as bytes before the element.
+#[derive(Clone)]
+pub struct PoseidonTranscript<F: PrimeField + Absorb> {
+    sponge: PoseidonSponge<F>,
+}
+
+impl<F: PrimeField + Absorb> Default for PoseidonTranscript<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: PrimeField + Absorb> PoseidonTranscript<F> {
+    /// Creates a transcript with the parameters of [`poseidon_config`].
+    pub fn new() -> Self {
+        Self::with_config(&poseidon_config())
+    }
+
+    /// Creates a transcript with the given Poseidon parameters, e.g. the ones
+    /// of the circuit that verifies the proof.
+    pub fn with_config(config: &PoseidonConfig<F>) -> Self {
+        let mut sponge = PoseidonSponge::new(config);
+        sponge.absorb(&bytes_to_limbs::<F>(b"Init SegLookup Transcript"));
+
+        Self { sponge }
+    }
+}
+
+impl<F: PrimeField + Absorb> TranscriptProtocol<F> for PoseidonTranscript<F> {
+    fn append_scalar(&mut self, label: Label, scalar: &F) -> Result<(), Error> {
+        let mut limbs = bytes_to_limbs(label.as_bytes());
+        limbs.push(*scalar);
+        self.sponge.absorb(&limbs);
+
+        Ok(())
+    }
+
+    fn append_point<G: AffineRepr<ScalarField = F>>(
         &mut self,
         label: Label,
-        element: &T,
+        point: &G,
     ) -> Result<(), Error> {
-        let mut buf = vec![];
-        element
-            .serialize_uncompressed(&mut buf)
-            .map_err(|_| Error::FailedToSerializeElement)?;
-        self.transcript
-            .append_message(label.as_bytes(), buf.as_ref());
+        let mut limbs = bytes_to_limbs(label.as_bytes());
+        point_to_limbs(point, &mut limbs);
+        self.sponge.absorb(&limbs);
 
         Ok(())
     }
 
-    pub(crate) fn append_elements<T: CanonicalSerialize>(
-        &mut self,
-        labels_and_elements: &[(Label, T)],
-    ) -> Result<(), Error> {
-        for (label, element) in labels_and_elements {
-            self.append_element(*label, element)?;
-        }
+    fn append_bytes(&mut self, label: Label, bytes: &[u8]) -> Result<(), Error> {
+        let mut limbs = bytes_to_limbs(label.as_bytes());
+        limbs.extend(bytes_to_limbs::<F>(bytes));
+        self.sponge.absorb(&limbs);
 
         Ok(())
     }
+
+    fn squeeze_challenge(&mut self, label: Label) -> Result<F, Error> {
+        self.sponge.absorb(&bytes_to_limbs::<F>(label.as_bytes()));
+
+        Ok(self.sponge.squeeze_field_elements(1)[0])
+    }
+}
+
+/// Poseidon with rate 2, alpha = 5, 8 full rounds and 57 partial rounds, the
+/// instance for 255-bit fields with 128-bit security of the Poseidon paper.
+/// Requires gcd(5, p - 1) = 1, as for the scalar fields of BN254 and
+/// BLS12-381.
+pub fn poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (full_rounds, partial_rounds, alpha, rate) = (8, 57, 5, 2);
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds as u64,
+        partial_rounds as u64,
+        0,
+    );
+
+    PoseidonConfig::new(full_rounds, partial_rounds, alpha, mds, ark, rate, 1)
+}
+
+fn bytes_to_limbs<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    let chunk_size = (F::MODULUS_BIT_SIZE as usize - 1) / 8;
+    let mut limbs = vec![F::from(bytes.len() as u64)];
+    limbs.extend(bytes.chunks(chunk_size).map(F::from_le_bytes_mod_order));
+
+    limbs
+}
+
+fn point_to_limbs<G: AffineRepr>(point: &G, limbs: &mut Vec<G::ScalarField>) {
+    let (x, y) = point.xy().unwrap_or_default();
+    x.to_base_prime_field_elements()
+        .chain(y.to_base_prime_field_elements())
+        .for_each(|coordinate| prime_field_to_limbs(coordinate, limbs));
+    limbs.push(G::ScalarField::from(point.is_zero()));
+}
+
+// An element of the scalar field itself is a single limb.
+fn prime_field_to_limbs<S: PrimeField, F: PrimeField>(element: S, limbs: &mut Vec<F>) {
+    let bigint = element.into_bigint();
+    if S::characteristic() == F::characteristic() {
+        limbs.push(F::from_le_bytes_mod_order(&bigint.to_bytes_le()));
+        return;
+    }
+
+    let limb_size = F::MODULUS_BIT_SIZE as usize - 1;
+    let bits = bigint.to_bits_le();
+    limbs.extend(
+        bits[..S::MODULUS_BIT_SIZE as usize]
+            .chunks(limb_size)
+            .map(|chunk| F::from_le_bytes_mod_order(&F::BigInt::from_bits_le(chunk).to_bytes_le())),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Fq, Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn test_point_to_limbs() {
+        let mut rng = test_rng();
+        let point = G1Affine::rand(&mut rng);
+
+        // Each 254-bit coordinate is split into two limbs of 253 bits.
+        let mut limbs = Vec::<Fr>::new();
+        point_to_limbs(&point, &mut limbs);
+        assert_eq!(limbs.len(), 5);
+        let limb_size = Fr::MODULUS_BIT_SIZE - 1;
+        let shift = Fq::from(2u64).pow([limb_size as u64]);
+        for (coordinate, coordinate_limbs) in [(point.x, &limbs[0..2]), (point.y, &limbs[2..4])] {
+            let recomposed = coordinate_limbs
+                .iter()
+                .rev()
+                .fold(Fq::from(0u64), |acc, limb| {
+                    acc * shift + Fq::from_le_bytes_mod_order(&limb.into_bigint().to_bytes_le())
+                });
+            assert_eq!(recomposed, coordinate);
+        }
+        assert_eq!(limbs[4], Fr::from(0u64));
+
+        let mut limbs = Vec::<Fr>::new();
+        point_to_limbs(&G1Affine::zero(), &mut limbs);
+        assert_eq!(
+            limbs,
+            vec![0, 0, 0, 0, 1]
+                .into_iter()
+                .map(Fr::from)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_poseidon_transcript() {
+        let mut rng = test_rng();
+        let point = G1Affine::rand(&mut rng);
+        let scalar = Fr::rand(&mut rng);
+
+        let challenge = |label: Label, point: G1Affine| {
+            let mut transcript = PoseidonTranscript::<Fr>::new();
+            transcript
+                .append_bytes(Label::PublicParameters, &[1, 2, 3])
+                .unwrap();
+            transcript.append_point(label, &point).unwrap();
+            transcript.append_scalar(Label::FrAAtZero, &scalar).unwrap();
+            transcript.squeeze_challenge(Label::ChallengeBeta).unwrap()
+        };
+
+        assert_eq!(challenge(Label::G1M, point), challenge(Label::G1M, point));
+        assert_ne!(challenge(Label::G1M, point), challenge(Label::G1Qm, point));
+        assert_ne!(
+            challenge(Label::G1M, point),
+            challenge(Label::G1M, G1Affine::zero())
+        );
+
+        // Consecutive challenges differ.
+        let mut transcript = PoseidonTranscript::<Fr>::new();
+        let beta = transcript.squeeze_challenge(Label::ChallengeBeta).unwrap();
+        let delta = transcript.squeeze_challenge(Label::ChallengeDelta).unwrap();
+        assert_ne!(beta, delta);
+
+        // Byte strings are absorbed with their length.
+        let bytes_challenge = |messages: [&[u8]; 2]| {
+            let mut transcript = PoseidonTranscript::<Fr>::new();
+            for message in messages {
+                transcript.append_bytes(Label::Context, message).unwrap();
+            }
+            transcript.squeeze_challenge(Label::ChallengeBeta).unwrap()
+        };
+        assert_ne!(
+            bytes_challenge([b"ab", b"c"]),
+            bytes_challenge([b"a", b"bc"])
+        );
+    }
 }
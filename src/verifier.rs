@@ -1,39 +1,641 @@
 use crate::error::Error;
-use crate::multi_unity::multi_unity_verify;
-use crate::prover::Proof;
-use crate::public_parameters::PublicParameters;
-use crate::table::TablePreprocessedParameters;
-use crate::transcript::{Label, Transcript};
+use crate::multi_column::{
+    column_challenge_powers, compress_statements, MultiColumnTableVerifierKey,
+};
+use crate::multi_unity::multi_unity_verify_defer_pairing;
+use crate::prover::{
+    CaulkRound1Message, CaulkRound2Message, CaulkRound3Message, DynamicTableProof, Proof, Round,
+    Round10Message, Round12Message, Round14Message, Round1Message,
+};
+use crate::public_parameters::VerifierKey;
+use crate::table::TableVerifierKey;
+use crate::transcript::{Label, Transcript, TranscriptProtocol, DEFAULT_LABEL};
+use crate::witness::Statement;
 use ark_ec::pairing::Pairing;
 use ark_ec::{AffineRepr, CurveGroup};
-use ark_ff::Field;
+use ark_ff::{Field, PrimeField};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_std::rand::Rng;
 use ark_std::{One, Zero};
 use rayon::prelude::*;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg};
 
-const BATCH_SIZE: usize = 8;
+const BATCH_SIZE: usize = 9;
+
+/// The options of a proof verified on a transcript, which must match the
+/// [`crate::prover::ProofOptions`] of the prover. By default, the lookup is
+/// separated with [`DEFAULT_LABEL`] and has an empty context.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifyOptions<'a> {
+    label: &'a [u8],
+    context: &'a [u8],
+}
+
+impl Default for VerifyOptions<'_> {
+    fn default() -> Self {
+        Self {
+            label: DEFAULT_LABEL,
+            context: &[],
+        }
+    }
+}
+
+impl<'a> VerifyOptions<'a> {
+    /// Sets the label that separates this lookup from the other
+    /// sub-arguments on the transcript of an outer protocol.
+    pub fn label(mut self, label: &'a [u8]) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// Sets the application context the proof is bound to.
+    pub fn context(mut self, context: &'a [u8]) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub(crate) fn append_to<F: PrimeField, T: TranscriptProtocol<F>>(
+        &self,
+        transcript: &mut T,
+    ) -> Result<(), Error> {
+        transcript.append_bytes(Label::DomainSeparator, self.label)?;
+        transcript.append_bytes(Label::Context, self.context)
+    }
+}
 
 pub fn verify<P: Pairing, R: Rng + ?Sized>(
-    pp: &PublicParameters<P>,
-    tpp: &TablePreprocessedParameters<P>,
-    statement: P::G1Affine,
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
+    proof: &Proof<P>,
+    rng: &mut R,
+) -> Result<(), Error> {
+    verify_with_transcript(
+        vk,
+        tvk,
+        statement,
+        proof,
+        &mut Transcript::new(),
+        &VerifyOptions::default(),
+        rng,
+    )
+}
+
+/// Verifies a proof made with [`crate::prover::prove_with_transcript`] on a
+/// transcript of the same [`TranscriptProtocol`] in the same state as the
+/// prover's, with the same label and context.
+pub fn verify_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
+    proof: &Proof<P>,
+    transcript: &mut T,
+    options: &VerifyOptions,
+    rng: &mut R,
+) -> Result<(), Error> {
+    options.append_to(transcript)?;
+    let equations = pairing_equations_with_transcript(vk, tvk, statement, proof, transcript)?;
+
+    verify_equations(&equations, rng)
+}
+
+/// Verifies a lookup into a multi-column table, given one statement
+/// commitment per column. The statements are compressed with the same
+/// transcript-derived challenge as in [`crate::prover::prove_multi_column`].
+pub fn verify_multi_column<P: Pairing, R: Rng + ?Sized>(
+    vk: &VerifierKey<P>,
+    tvk: &MultiColumnTableVerifierKey<P>,
+    statements: &[Statement<P>],
+    proof: &Proof<P>,
+    rng: &mut R,
+) -> Result<(), Error> {
+    verify_multi_column_with_transcript(
+        vk,
+        tvk,
+        statements,
+        proof,
+        &mut Transcript::new(),
+        &VerifyOptions::default(),
+        rng,
+    )
+}
+
+/// Verifies a proof made with
+/// [`crate::prover::prove_multi_column_with_transcript`] on a transcript in
+/// the same state as the prover's, with the same options.
+pub fn verify_multi_column_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
+    vk: &VerifierKey<P>,
+    tvk: &MultiColumnTableVerifierKey<P>,
+    statements: &[Statement<P>],
+    proof: &Proof<P>,
+    transcript: &mut T,
+    options: &VerifyOptions,
+    rng: &mut R,
+) -> Result<(), Error> {
+    options.append_to(transcript)?;
+    let fr_zeta_powers = column_challenge_powers::<P, _>(
+        transcript,
+        &vk.hash_representation,
+        &tvk.hash_representation,
+        tvk.num_columns(),
+        statements,
+    )?;
+
+    let compressed_tvk = tvk.compress(&fr_zeta_powers);
+    let compressed_statement = compress_statements::<P>(statements, &fr_zeta_powers)?;
+
+    let equations = pairing_equations_with_transcript(
+        vk,
+        &compressed_tvk,
+        compressed_statement,
+        proof,
+        transcript,
+    )?;
+
+    verify_equations(&equations, rng)
+}
+
+/// Verifies a lookup into a dynamic table given as `table_statement` =
+/// [T(tau)]_1. The prover's [T(tau)]_2 is checked against it with one more
+/// pairing equation, folded into the same multi-pairing.
+pub fn verify_dynamic_table<P: Pairing, R: Rng + ?Sized>(
+    vk: &VerifierKey<P>,
+    table_statement: P::G1Affine,
+    statement: Statement<P>,
+    proof: &DynamicTableProof<P>,
+    rng: &mut R,
+) -> Result<(), Error> {
+    verify_dynamic_table_with_transcript(
+        vk,
+        table_statement,
+        statement,
+        proof,
+        &mut Transcript::new(),
+        &VerifyOptions::default(),
+        rng,
+    )
+}
+
+/// Verifies a proof made with
+/// [`crate::prover::prove_dynamic_table_with_transcript`] on a transcript in
+/// the same state as the prover's, with the same options.
+pub fn verify_dynamic_table_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+    R: Rng + ?Sized,
+>(
+    vk: &VerifierKey<P>,
+    table_statement: P::G1Affine,
+    statement: Statement<P>,
+    proof: &DynamicTableProof<P>,
+    transcript: &mut T,
+    options: &VerifyOptions,
+    rng: &mut R,
+) -> Result<(), Error> {
+    let tvk = TableVerifierKey::from_dynamic_table_commitment(proof.g2_affine_t)?;
+
+    options.append_to(transcript)?;
+    transcript.append_point(Label::DynamicTable, &table_statement)?;
+    let mut equations =
+        pairing_equations_with_transcript(vk, &tvk, statement, &proof.proof, transcript)?;
+
+    // e([T(tau)]_1, [1]_2) = e([1]_1, [T(tau)]_2).
+    equations.push(PairingEquation {
+        error: Error::DynamicTableCommitmentsMismatch,
+        pairs: vec![
+            (table_statement.into_group(), vk.g2_affine_one.into_group()),
+            (
+                -vk.g1_affine_one.into_group(),
+                proof.g2_affine_t.into_group(),
+            ),
+        ],
+    });
+
+    verify_equations(&equations, rng)
+}
+
+/// Verifies many proofs against the same table with one multi-pairing.
+/// If the batch is rejected, the index of the first proof that fails on its
+/// own is located by bisection and reported in [`Error::BatchedProofFailed`].
+pub fn verify_batch<P: Pairing, R: Rng + ?Sized>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statements_and_proofs: &[(Statement<P>, Proof<P>)],
+    rng: &mut R,
+) -> Result<(), Error> {
+    verify_batch_with_transcript(
+        vk,
+        tvk,
+        statements_and_proofs,
+        &Transcript::new(),
+        &VerifyOptions::default(),
+        rng,
+    )
+}
+
+/// Verifies many proofs made with [`crate::prover::prove_with_transcript`],
+/// each on its own copy of `transcript` in the same state, with the same
+/// options.
+pub fn verify_batch_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField> + Clone + Sync,
+    R: Rng + ?Sized,
+>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statements_and_proofs: &[(Statement<P>, Proof<P>)],
+    transcript: &T,
+    options: &VerifyOptions,
+    rng: &mut R,
+) -> Result<(), Error> {
+    let equations_list = statements_and_proofs
+        .par_iter()
+        .enumerate()
+        .map(|(i, (statement, proof))| {
+            let mut transcript = transcript.clone();
+            options.append_to(&mut transcript)?;
+
+            pairing_equations_with_transcript(vk, tvk, *statement, proof, &mut transcript)
+                .map_err(|_| Error::BatchedProofFailed(i))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if fold_pairing_equations(equations_list.iter().flatten(), rng).check() {
+        return Ok(());
+    }
+
+    // Fallback: bisect the batch to find an offending proof.
+    let index = find_failed_proof(&equations_list, 0, rng).ok_or(Error::BatchedPairingFailed)?;
+
+    Err(Error::BatchedProofFailed(index))
+}
+
+// Returns the index of the first proof whose pairing equations fail on their
+// own, checking each half of the batch separately. Returns None if no single
+// proof is confirmed to fail.
+fn find_failed_proof<P: Pairing, R: Rng + ?Sized>(
+    equations_list: &[Vec<PairingEquation<P>>],
+    offset: usize,
+    rng: &mut R,
+) -> Option<usize> {
+    if equations_list.len() <= 1 {
+        let failed = equations_list
+            .first()
+            .is_some_and(|equations| !fold_pairing_equations(equations, rng).check());
+
+        return failed.then_some(offset);
+    }
+
+    let mid = equations_list.len() / 2;
+    let (left, right) = equations_list.split_at(mid);
+    [(left, offset), (right, offset + mid)]
+        .into_iter()
+        .find_map(|(half, half_offset)| {
+            if fold_pairing_equations(half.iter().flatten(), rng).check() {
+                return None;
+            }
+
+            find_failed_proof(half, half_offset, rng)
+        })
+}
+
+/// Verifies the proof with one final exponentiation per pairing equation,
+/// reporting exactly which check failed.
+/// This is slower than [`verify`] and is intended for debugging.
+pub fn verify_debug<P: Pairing>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
     proof: &Proof<P>,
+) -> Result<(), Error> {
+    let equations = pairing_equations(vk, tvk, statement, proof)?;
+
+    equations.into_iter().try_for_each(|equation| {
+        let (g1_list, g2_list): (Vec<_>, Vec<_>) = equation.pairs.into_iter().unzip();
+        if !P::multi_pairing(g1_list, g2_list).is_zero() {
+            return Err(equation.error);
+        }
+
+        Ok(())
+    })
+}
+
+/// The verifier of the interactive protocol, which receives the prover's
+/// messages one round at a time. The challenges it sends after each message
+/// are chosen by the caller, and must be sampled uniformly at random after
+/// the message is received. The rounds must be received in order; [`verify`]
+/// derives the challenges from the transcript instead.
+pub struct VerifierState<'a, P: Pairing> {
+    vk: &'a VerifierKey<P>,
+    tvk: &'a TableVerifierKey<P>,
+    statement: Statement<P>,
+    round: Round,
+    round1: Option<Round1Message<P>>,
+    caulk_round1: Option<CaulkRound1Message<P>>,
+    caulk_round2: Option<CaulkRound2Message<P>>,
+    caulk_round3: Option<CaulkRound3Message<P>>,
+    round10: Option<Round10Message<P>>,
+    round12: Option<Round12Message<P>>,
+    round14: Option<Round14Message<P>>,
+    challenges: Challenges<P::ScalarField>,
+}
+
+impl<'a, P: Pairing> VerifierState<'a, P> {
+    pub fn new(
+        vk: &'a VerifierKey<P>,
+        tvk: &'a TableVerifierKey<P>,
+        statement: Statement<P>,
+    ) -> Self {
+        Self {
+            vk,
+            tvk,
+            statement,
+            round: Round::Round1,
+            round1: None,
+            caulk_round1: None,
+            caulk_round2: None,
+            caulk_round3: None,
+            round10: None,
+            round12: None,
+            round14: None,
+            challenges: Challenges {
+                caulk_alpha: P::ScalarField::zero(),
+                caulk_beta: P::ScalarField::zero(),
+                beta: P::ScalarField::zero(),
+                delta: P::ScalarField::zero(),
+                epsilon: P::ScalarField::zero(),
+                gamma: P::ScalarField::zero(),
+                eta: P::ScalarField::zero(),
+            },
+        }
+    }
+
+    /// Receives the Round 1 commitments, and checks the number of queries of
+    /// the statement.
+    pub fn receive_round1(&mut self, message: Round1Message<P>) -> Result<(), Error> {
+        self.round.advance(Round::Round1)?;
+        check_num_queries(self.vk, self.statement.num_queries)?;
+        self.round1 = Some(message);
+
+        Ok(())
+    }
+
+    /// Receives the first multi-unity message, answered with `caulk_alpha`.
+    pub fn receive_caulk_round1(
+        &mut self,
+        message: CaulkRound1Message<P>,
+        caulk_alpha: P::ScalarField,
+    ) -> Result<(), Error> {
+        self.round.advance(Round::CaulkRound1)?;
+        self.caulk_round1 = Some(message);
+        self.challenges.caulk_alpha = caulk_alpha;
+
+        Ok(())
+    }
+
+    /// Receives the second multi-unity message, answered with `caulk_beta`.
+    pub fn receive_caulk_round2(
+        &mut self,
+        message: CaulkRound2Message<P>,
+        caulk_beta: P::ScalarField,
+    ) -> Result<(), Error> {
+        self.round.advance(Round::CaulkRound2)?;
+        self.caulk_round2 = Some(message);
+        self.challenges.caulk_beta = caulk_beta;
+
+        Ok(())
+    }
+
+    /// Receives the last multi-unity message, answered with the Round 9
+    /// challenges `beta`, `delta`, and `epsilon`.
+    pub fn receive_caulk_round3(
+        &mut self,
+        message: CaulkRound3Message<P>,
+        beta: P::ScalarField,
+        delta: P::ScalarField,
+        epsilon: P::ScalarField,
+    ) -> Result<(), Error> {
+        self.round.advance(Round::CaulkRound3)?;
+        self.caulk_round3 = Some(message);
+        self.challenges.beta = beta;
+        self.challenges.delta = delta;
+        self.challenges.epsilon = epsilon;
+
+        Ok(())
+    }
+
+    /// Receives the Round 10 commitments, answered with `gamma`.
+    pub fn receive_round10(
+        &mut self,
+        message: Round10Message<P>,
+        gamma: P::ScalarField,
+    ) -> Result<(), Error> {
+        self.round.advance(Round::Round10)?;
+        self.round10 = Some(message);
+        self.challenges.gamma = gamma;
+
+        Ok(())
+    }
+
+    /// Receives the Round 12 evaluations, answered with `eta`.
+    pub fn receive_round12(
+        &mut self,
+        message: Round12Message<P>,
+        eta: P::ScalarField,
+    ) -> Result<(), Error> {
+        self.round.advance(Round::Round12)?;
+        self.round12 = Some(message);
+        self.challenges.eta = eta;
+
+        Ok(())
+    }
+
+    /// Receives the Round 14 batch opening.
+    pub fn receive_round14(&mut self, message: Round14Message<P>) -> Result<(), Error> {
+        self.round.advance(Round::Round14)?;
+        self.round14 = Some(message);
+
+        Ok(())
+    }
+
+    /// Round 15: performs the point checks and the pairing checks on the
+    /// received messages.
+    pub fn verify<R: Rng + ?Sized>(mut self, rng: &mut R) -> Result<(), Error> {
+        self.round.advance(Round::Done)?;
+        let proof = match (
+            self.round1,
+            self.caulk_round1,
+            self.caulk_round2,
+            self.caulk_round3,
+            self.round10,
+            self.round12,
+            self.round14,
+        ) {
+            (
+                Some(round1),
+                Some(caulk_round1),
+                Some(caulk_round2),
+                Some(caulk_round3),
+                Some(round10),
+                Some(round12),
+                Some(round14),
+            ) => Proof::from_messages(
+                round1,
+                caulk_round1,
+                caulk_round2,
+                caulk_round3,
+                round10,
+                round12,
+                round14,
+            ),
+            _ => return Err(Error::UnexpectedRound("done")),
+        };
+        let equations = pairing_equations_with_challenges(
+            self.vk,
+            self.tvk,
+            self.statement,
+            &proof,
+            &self.challenges,
+        )?;
+
+        verify_equations(&equations, rng)
+    }
+}
+
+// A pairing equation prod_i e(g1_i, g2_i) = 1,
+// tagged with the error reported when it fails on its own.
+pub(crate) struct PairingEquation<P: Pairing> {
+    pub(crate) error: Error,
+    pub(crate) pairs: Vec<(P::G1, P::G2)>,
+}
+
+// Pairs of a random linear combination of pairing equations.
+// Pairs that share the same G2 element are merged to save Miller loops.
+pub(crate) struct FoldedPairingEquations<P: Pairing> {
+    g1_list: Vec<P::G1>,
+    g2_affine_list: Vec<P::G2Affine>,
+}
+
+impl<P: Pairing> FoldedPairingEquations<P> {
+    fn new() -> Self {
+        Self {
+            g1_list: Vec::new(),
+            g2_affine_list: Vec::new(),
+        }
+    }
+
+    fn add_scaled(&mut self, equation: &PairingEquation<P>, fr_weight: P::ScalarField) {
+        let (g1_list, g2_list): (Vec<_>, Vec<_>) = equation.pairs.iter().copied().unzip();
+        let g2_affine_list = P::G2::normalize_batch(&g2_list);
+        for (g1, g2_affine) in g1_list.into_iter().zip(g2_affine_list) {
+            let g1_scaled = g1.mul(fr_weight);
+            match self.g2_affine_list.iter().position(|&g2| g2 == g2_affine) {
+                Some(index) => self.g1_list[index].add_assign(g1_scaled),
+                None => {
+                    self.g1_list.push(g1_scaled);
+                    self.g2_affine_list.push(g2_affine);
+                },
+            }
+        }
+    }
+
+    pub(crate) fn check(self) -> bool {
+        let g1_affine_list = P::G1::normalize_batch(&self.g1_list);
+
+        P::multi_pairing(g1_affine_list, self.g2_affine_list).is_zero()
+    }
+}
+
+// Checks all the pairing equations with a single final exponentiation.
+pub(crate) fn verify_equations<P: Pairing, R: Rng + ?Sized>(
+    equations: &[PairingEquation<P>],
     rng: &mut R,
 ) -> Result<(), Error> {
+    if !fold_pairing_equations(equations, rng).check() {
+        return Err(Error::BatchedPairingFailed);
+    }
+
+    Ok(())
+}
+
+// Folds the pairing equations with independent random 128-bit weights from
+// the rng. The first equation keeps the weight one. 128-bit weights bound the
+// soundness error by 2^-128 and halve the cost of the scalar multiplications.
+pub(crate) fn fold_pairing_equations<'a, P: Pairing, R: Rng + ?Sized>(
+    equations: impl IntoIterator<Item = &'a PairingEquation<P>>,
+    rng: &mut R,
+) -> FoldedPairingEquations<P> {
+    let mut folded = FoldedPairingEquations::new();
+    for (i, equation) in equations.into_iter().enumerate() {
+        let fr_weight = if i == 0 {
+            P::ScalarField::one()
+        } else {
+            P::ScalarField::from(rng.gen::<u128>())
+        };
+        folded.add_scaled(equation, fr_weight);
+    }
+
+    folded
+}
+
+// Replays the transcript, performs the point checks, and returns all the
+// pairing equations that a valid proof satisfies.
+pub(crate) fn pairing_equations<P: Pairing>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
+    proof: &Proof<P>,
+) -> Result<Vec<PairingEquation<P>>, Error> {
     let mut transcript = Transcript::<P::ScalarField>::new();
-    transcript.append_elements(&[
-        (Label::PublicParameters, pp.hash_representation.clone()),
-        (
-            Label::TablePreprocessedParameters,
-            tpp.hash_representation.clone(),
-        ),
-    ])?;
+    VerifyOptions::default().append_to(&mut transcript)?;
+
+    pairing_equations_with_transcript(vk, tvk, statement, proof, &mut transcript)
+}
 
-    transcript.append_element(Label::Statement, &statement)?;
+pub(crate) fn pairing_equations_with_transcript<
+    P: Pairing,
+    T: TranscriptProtocol<P::ScalarField>,
+>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
+    proof: &Proof<P>,
+    transcript: &mut T,
+) -> Result<Vec<PairingEquation<P>>, Error> {
+    let challenges = challenges_with_transcript(vk, tvk, statement, proof, transcript)?;
+
+    pairing_equations_with_challenges(vk, tvk, statement, proof, &challenges)
+}
+
+// Replays the prover's messages on the transcript, and returns the challenges
+// derived from it.
+pub(crate) fn challenges_with_transcript<P: Pairing, T: TranscriptProtocol<P::ScalarField>>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
+    proof: &Proof<P>,
+    transcript: &mut T,
+) -> Result<Challenges<P::ScalarField>, Error> {
+    transcript.append_bytes(Label::PublicParameters, &vk.hash_representation)?;
+    transcript.append_bytes(Label::TablePreprocessedParameters, &tvk.hash_representation)?;
+
+    transcript.append_point(Label::Statement, &statement.g1_affine_f)?;
 
-    transcript.append_elements(&[
+    check_num_queries(vk, statement.num_queries)?;
+    transcript.append_scalar(
+        Label::NumQueries,
+        &P::ScalarField::from(statement.num_queries as u64),
+    )?;
+
+    transcript.append_points(&[
         (Label::G1M, proof.g1_affine_m),
         (Label::G1MDivW, proof.g1_affine_m_div_w),
         (Label::G1Qm, proof.g1_affine_qm),
@@ -42,6 +644,10 @@ pub fn verify<P: Pairing, R: Rng + ?Sized>(
         (Label::G1Ql, proof.g1_affine_ql),
         (Label::G1D, proof.g1_affine_d),
         (Label::G1Qd, proof.g1_affine_qd),
+        (Label::G1Hm, proof.g1_affine_hm),
+        (Label::G1Qt, proof.g1_affine_qt),
+        (Label::G1Ga, proof.g1_affine_ga),
+        (Label::G1Gb, proof.g1_affine_gb),
         (Label::CaulkG1D, proof.g1_affine_d),
         (Label::CaulkG1UBar, proof.multi_unity_proof.g1_u_bar),
         (Label::CaulkG1H2, proof.multi_unity_proof.g1_h_2),
@@ -49,25 +655,30 @@ pub fn verify<P: Pairing, R: Rng + ?Sized>(
 
     let caulk_alpha = transcript.squeeze_challenge(Label::ChallengeCaulkAlpha)?;
 
-    transcript.append_element(Label::CaulkG1H1, &proof.multi_unity_proof.g1_h_1)?;
+    transcript.append_point(Label::CaulkG1H1, &proof.multi_unity_proof.g1_h_1)?;
 
     let caulk_beta = transcript.squeeze_challenge(Label::ChallengeCaulkBeta)?;
 
     let beta = transcript.squeeze_challenge(Label::ChallengeBeta)?;
     let delta = transcript.squeeze_challenge(Label::ChallengeDelta)?;
+    let epsilon = transcript.squeeze_challenge(Label::ChallengeEpsilon)?;
 
-    transcript.append_elements(&[
+    transcript.append_points(&[
         (Label::G1A, proof.g1_affine_a),
         (Label::G1Qa, proof.g1_affine_qa),
         (Label::G1Qb, proof.g1_affine_qb),
         (Label::G1A0, proof.g1_affine_a0),
         (Label::G1B0, proof.g1_affine_b0),
-        (Label::G1Px, proof.g1_affine_px),
+        (Label::G1Pa, proof.g1_affine_pa),
+        (Label::G1Ha, proof.g1_affine_ha),
+        (Label::G1Rb, proof.g1_affine_rb),
+        (Label::G1Hb, proof.g1_affine_hb),
+        (Label::G1Pb, proof.g1_affine_pb),
     ])?;
 
     let gamma = transcript.squeeze_challenge(Label::ChallengeGamma)?;
 
-    transcript.append_elements(&[
+    transcript.append_scalars(&[
         (Label::FrB0AtGamma, proof.fr_b0_at_gamma),
         (Label::FrFAtGamma, proof.fr_f_at_gamma),
         (Label::FrLAtGamma, proof.fr_l_at_gamma),
@@ -76,188 +687,266 @@ pub fn verify<P: Pairing, R: Rng + ?Sized>(
         (Label::FrQlAtGamma, proof.fr_ql_at_gamma),
         (Label::FrDAtGamma, proof.fr_d_at_gamma),
         (Label::FrQdAtGamma, proof.fr_qd_at_gamma),
+        (Label::FrRAtGamma, proof.fr_r_at_gamma),
+        (Label::FrGbAtGamma, proof.fr_gb_at_gamma),
     ])?;
 
     let eta = transcript.squeeze_challenge(Label::ChallengeEta)?;
 
-    let g2_affine_one = pp.g2_affine_srs[0];
-    let g2_affine_tau = pp.g2_affine_srs[1];
-
-    let checks: Vec<Result<(), Error>> = vec![
-        // Round 2: The first pairing check.
-        // This is intended to check the correctness of multiplicity polynomials.
-        first_pairing_check(
-            &proof,
-            &pp.g2_affine_srs,
-            pp.g2_affine_zw,
-            pp.num_table_segments,
-        ),
-        // Round 3-8: Multi-unity check.
-        multi_unity_verify(
-            pp,
-            caulk_alpha,
-            caulk_beta,
-            &proof.g1_affine_d,
-            &proof.multi_unity_proof,
-            rng,
-        )
-        .map_err(|_| Error::FailedToCheckMultiUnity),
-        // Round 11: The second pairing check.
-        // This is intended to check the correctness of polynomial A.
-        second_pairing_check::<P>(
-            &proof,
-            beta,
-            delta,
-            proof.g1_affine_m,
-            tpp.g2_affine_adjusted_t,
-            pp.g2_affine_zw,
-            &pp.g2_affine_srs,
-        ),
-        // Round 11: Degree pairing check.
-        degree_check(
-            &proof,
-            pp.num_table_segments,
-            pp.num_witness_segments,
-            pp.segment_size,
-            &pp.g2_affine_srs,
-        ),
-        // Round 15-4: The third pairing check.
-        third_pairing_check(
-            &proof,
-            statement,
-            beta,
-            delta,
-            gamma,
-            eta,
-            pp.num_table_segments,
-            pp.num_witness_segments,
-            pp.segment_size,
-            &pp.domain_v,
-            g2_affine_tau,
-            g2_affine_one,
-        ),
-        // Round 15-4: The fourth pairing check.
-        fourth_pairing_check(&proof, g2_affine_tau, g2_affine_one),
-        // Round 15-4: The first point check.
-        first_point_check(
-            &proof,
-            gamma,
-            pp.num_witness_segments,
-            &pp.domain_v,
-            &pp.domain_w,
-        ),
-        // Round 15-4: The second point check.
-        second_point_check(&proof, gamma, &pp.domain_k),
-    ];
+    Ok(Challenges {
+        caulk_alpha,
+        caulk_beta,
+        beta,
+        delta,
+        epsilon,
+        gamma,
+        eta,
+    })
+}
 
-    checks.into_iter().try_for_each(|check| check)?;
+fn check_num_queries<P: Pairing>(vk: &VerifierKey<P>, num_queries: usize) -> Result<(), Error> {
+    if num_queries == 0 || num_queries > vk.logical_num_witness_segments {
+        return Err(Error::InvalidNumberOfQueries(num_queries));
+    }
 
     Ok(())
 }
 
-fn first_pairing_check<P: Pairing>(
+// The challenges sent by the verifier, either sampled interactively or derived
+// from the transcript.
+pub(crate) struct Challenges<F> {
+    pub(crate) caulk_alpha: F,
+    pub(crate) caulk_beta: F,
+    pub(crate) beta: F,
+    pub(crate) delta: F,
+    pub(crate) epsilon: F,
+    pub(crate) gamma: F,
+    pub(crate) eta: F,
+}
+
+// Performs the point checks, and returns all the pairing equations that a
+// valid proof satisfies under the given challenges.
+pub(crate) fn pairing_equations_with_challenges<P: Pairing>(
+    vk: &VerifierKey<P>,
+    tvk: &TableVerifierKey<P>,
+    statement: Statement<P>,
     proof: &Proof<P>,
-    g2_affine_srs: &[P::G2Affine],
-    g2_affine_zw: P::G2Affine,
-    num_table_segments: usize,
-) -> Result<(), Error> {
-    let g1_affine_m = proof.g1_affine_m;
-    let g1_neg_m_div_w = -proof.g1_affine_m_div_w.into_group();
-    let g2_affine_tau_pow_ns = g2_affine_srs[num_table_segments];
-    let g2_neg_one = -g2_affine_srs[0].into_group();
-    let g1_affine_qm = proof.g1_affine_qm;
-
-    let left_pairing = P::pairing(
-        g1_affine_m + g1_neg_m_div_w,
-        g2_affine_tau_pow_ns + g2_neg_one,
+    challenges: &Challenges<P::ScalarField>,
+) -> Result<Vec<PairingEquation<P>>, Error> {
+    let Challenges {
+        caulk_alpha,
+        caulk_beta,
+        beta,
+        delta,
+        epsilon,
+        gamma,
+        eta,
+    } = *challenges;
+
+    // Round 15-4: The first point check.
+    first_point_check(
+        proof,
+        gamma,
+        vk.num_witness_segments,
+        &vk.domain_v,
+        &vk.domain_w,
+    )?;
+    // Round 15-4: The second point check.
+    second_point_check(proof, gamma, &vk.domain_k)?;
+
+    let g2_affine_one = vk.g2_affine_one;
+    let g2_affine_tau = vk.g2_affine_tau;
+
+    let mut equations = Vec::with_capacity(12);
+    // Round 2: The first pairing check.
+    // This is intended to check the correctness of multiplicity polynomials.
+    equations.push(first_pairing_equation(
+        proof,
+        vk.g2_affine_tau_pow_n,
+        vk.g2_affine_zw,
+        g2_affine_one,
+    ));
+    // Round 2: Padding pairing checks.
+    // These are intended to pin the padding queries to the padding segment.
+    equations.extend(padding_equations(proof, statement.num_queries, vk)?);
+    // Round 3-8: Multi-unity check.
+    let multi_unity_equations = multi_unity_verify_defer_pairing(
+        vk,
+        caulk_alpha,
+        caulk_beta,
+        &proof.g1_affine_d,
+        &proof.multi_unity_proof,
+    )?;
+    equations.extend(
+        multi_unity_equations
+            .into_iter()
+            .map(|pairs| PairingEquation {
+                error: Error::FailedToCheckMultiUnity,
+                pairs,
+            }),
     );
-    let right_pairing = P::pairing(g1_affine_qm, g2_affine_zw);
+    // Round 11: The second pairing check.
+    // This is intended to check the correctness of polynomial A.
+    equations.push(second_pairing_equation::<P>(
+        proof,
+        beta,
+        delta,
+        tvk.g2_affine_adjusted_t,
+        vk.g2_affine_zw,
+        g2_affine_tau,
+        g2_affine_one,
+    ));
+    // Round 11: Degree pairing checks.
+    // These are intended to bound the degrees of A_0 and B_0.
+    equations.extend(degree_check_equations(
+        proof,
+        vk.g2_affine_degree_check_a0,
+        vk.g2_affine_degree_check_b0,
+        vk.g2_affine_zv,
+        g2_affine_one,
+    ));
+    // Round 15-4: The third pairing check.
+    equations.push(third_pairing_equation(
+        proof,
+        statement.g1_affine_f,
+        beta,
+        delta,
+        epsilon,
+        gamma,
+        eta,
+        vk.num_table_segments,
+        vk.num_witness_segments,
+        vk.segment_size,
+        &vk.domain_v,
+        vk.g1_affine_gamma,
+        vk.g1_affine_one,
+        g2_affine_tau,
+        g2_affine_one,
+    )?);
+    // Round 15-4: The fourth pairing check.
+    equations.push(fourth_pairing_equation(
+        proof,
+        epsilon,
+        vk.g1_affine_one,
+        vk.g2_affine_zw,
+        g2_affine_tau,
+        g2_affine_one,
+    ));
 
-    if left_pairing != right_pairing {
-        return Err(Error::Pairing1Failed);
-    }
+    Ok(equations)
+}
 
-    Ok(())
+// e(M - M_{div w}, [tau^n - 1]_2) = e(Q_M, [Z_W(tau)]_2).
+fn first_pairing_equation<P: Pairing>(
+    proof: &Proof<P>,
+    g2_affine_tau_pow_n: P::G2Affine,
+    g2_affine_zw: P::G2Affine,
+    g2_affine_one: P::G2Affine,
+) -> PairingEquation<P> {
+    let g1_m_sub_m_div_w = proof.g1_affine_m.into_group() - proof.g1_affine_m_div_w;
+    let g2_tau_pow_n_sub_one = g2_affine_tau_pow_n.into_group() - g2_affine_one;
+
+    PairingEquation {
+        error: Error::Pairing1Failed,
+        pairs: vec![
+            (g1_m_sub_m_div_w, g2_tau_pow_n_sub_one),
+            (-proof.g1_affine_qm.into_group(), g2_affine_zw.into_group()),
+        ],
+    }
 }
 
-fn second_pairing_check<P: Pairing>(
+// e(A, [T(tau)]_2) * e(delta * A, [tau]_2) = e(Q_A, [Z_W(tau)]_2) * e(M -
+// beta * A, [1]_2).
+fn second_pairing_equation<P: Pairing>(
     proof: &Proof<P>,
     beta: P::ScalarField,
     delta: P::ScalarField,
-    g1_affine_m: P::G1Affine,
     g2_affine_t: P::G2Affine,
     g2_affine_zw: P::G2Affine,
-    g2_affine_srs: &[P::G2Affine],
-) -> Result<(), Error> {
+    g2_affine_tau: P::G2Affine,
+    g2_affine_one: P::G2Affine,
+) -> PairingEquation<P> {
     let g1_affine_a = proof.g1_affine_a;
-    let g2_affine_tau = g2_affine_srs[1];
-    let left_pairing = P::pairing(g1_affine_a, g2_affine_t + g2_affine_tau.mul(delta));
-
-    let g1_affine_qa = proof.g1_affine_qa;
-    let g1_affine_neg_beta_mul_a = g1_affine_a.mul(-beta).into_affine();
-    let g2_one = g2_affine_srs[0];
-    let right_pairing = P::multi_pairing(
-        &[
-            g1_affine_qa,
-            g1_affine_m.add(g1_affine_neg_beta_mul_a).into_affine(),
+    let g1_m_sub_beta_mul_a = proof.g1_affine_m.into_group() - g1_affine_a.mul(beta);
+
+    PairingEquation {
+        error: Error::Pairing2Failed,
+        pairs: vec![
+            (g1_affine_a.into_group(), g2_affine_t.into_group()),
+            (g1_affine_a.mul(delta), g2_affine_tau.into_group()),
+            (-proof.g1_affine_qa.into_group(), g2_affine_zw.into_group()),
+            (-g1_m_sub_beta_mul_a, g2_affine_one.into_group()),
         ],
-        &[g2_affine_zw, g2_one],
-    );
-
-    if left_pairing != right_pairing {
-        return Err(Error::Pairing2Failed);
     }
-
-    Ok(())
 }
 
-fn degree_check<P: Pairing>(
+// e(A_0, [tau^{t_A}]_2) = e(P_A, [1]_2) unless the SRS bounds A_0,
+// e(R_B, [tau^{t_B}]_2) = e(P_B, [1]_2), and
+// e(B_0 - R_B, [1]_2) = e(H_B, [Z_V(tau)]_2).
+fn degree_check_equations<P: Pairing>(
     proof: &Proof<P>,
-    num_table_segments: usize,
-    num_witness_segments: usize,
-    segment_size: usize,
-    g2_affine_srs: &[P::G2Affine],
-) -> Result<(), Error> {
-    let g1_affine_px = proof.g1_affine_px;
-    let g2_affine_one = g2_affine_srs[0];
-    if num_table_segments > num_witness_segments {
-        let deg_tau = (num_table_segments - num_witness_segments) * segment_size - 1;
-        let g1_affine_b0 = proof.g1_affine_b0;
-        let left_pairing = P::pairing(g1_affine_b0, g2_affine_srs[deg_tau]);
-        let right_pairing = P::pairing(g1_affine_px, g2_affine_one);
-
-        if left_pairing != right_pairing {
-            return Err(Error::DegreeCheckFailed);
-        }
-    } else if num_table_segments < num_witness_segments {
-        let deg_tau = (num_witness_segments - num_table_segments) * segment_size - 1;
-        let g1_affine_a0 = proof.g1_affine_a0;
-        let left_pairing = P::pairing(g1_affine_a0, g2_affine_srs[deg_tau]);
-        let right_pairing = P::pairing(g1_affine_px, g2_affine_one);
-
-        if left_pairing != right_pairing {
-            return Err(Error::DegreeCheckFailed);
-        }
+    g2_affine_degree_check_a0: Option<P::G2Affine>,
+    g2_affine_degree_check_b0: P::G2Affine,
+    g2_affine_zv: P::G2Affine,
+    g2_affine_one: P::G2Affine,
+) -> Vec<PairingEquation<P>> {
+    let mut equations = Vec::with_capacity(3);
+    if let Some(g2_affine_degree_check_a0) = g2_affine_degree_check_a0 {
+        equations.push(PairingEquation {
+            error: Error::DegreeCheckFailed,
+            pairs: vec![
+                (
+                    proof.g1_affine_a0.into_group(),
+                    g2_affine_degree_check_a0.into_group(),
+                ),
+                (-proof.g1_affine_pa.into_group(), g2_affine_one.into_group()),
+            ],
+        });
     }
+    equations.push(PairingEquation {
+        error: Error::DegreeCheckFailed,
+        pairs: vec![
+            (
+                proof.g1_affine_rb.into_group(),
+                g2_affine_degree_check_b0.into_group(),
+            ),
+            (-proof.g1_affine_pb.into_group(), g2_affine_one.into_group()),
+        ],
+    });
+    equations.push(PairingEquation {
+        error: Error::DegreeCheckFailed,
+        pairs: vec![
+            (
+                proof.g1_affine_b0.into_group() - proof.g1_affine_rb,
+                g2_affine_one.into_group(),
+            ),
+            (-proof.g1_affine_hb.into_group(), g2_affine_zv.into_group()),
+        ],
+    });
 
-    Ok(())
+    equations
 }
 
-fn third_pairing_check<P: Pairing>(
+// e(H_P, [tau]_2) = e(P - [p_{gamma}]_1 + gamma * H_P, [1]_2).
+#[allow(clippy::too_many_arguments)]
+fn third_pairing_equation<P: Pairing>(
     proof: &Proof<P>,
     statement: P::G1Affine,
     beta: P::ScalarField,
     delta: P::ScalarField,
+    epsilon: P::ScalarField,
     gamma: P::ScalarField,
     eta: P::ScalarField,
     num_table_segments: usize,
     num_witness_segments: usize,
     segment_size: usize,
     domain_v: &Radix2EvaluationDomain<P::ScalarField>,
+    g1_affine_gamma: P::G1Affine,
+    g1_affine_one: P::G1Affine,
     g2_affine_tau: P::G2Affine,
     g2_affine_one: P::G2Affine,
-) -> Result<(), Error> {
-    // Round 15-1: Compute b_0 = ns * a_0 / (ks)
+) -> Result<PairingEquation<P>, Error> {
+    // Round 15-1: Compute b_0' = ns * a_0' / (ks), the masked b_0.
     let table_elem_size = num_table_segments * segment_size;
     let fr_table_elem_size = P::ScalarField::from(table_elem_size as u64);
     let witness_elem_size = num_witness_segments * segment_size;
@@ -272,15 +961,16 @@ fn third_pairing_check<P: Pairing>(
     let fr_inv_zv_at_gamma = fr_zv_at_gamma
         .inverse()
         .ok_or(Error::FailedToInverseFieldElement)?;
-    // Compute b_{gamma} = b_{0, gamma} * gamma + b_0
-    let fr_b_at_gamma = proof.fr_b0_at_gamma * gamma + fr_b_at_zero;
+    // Compute b_{gamma} = b_{0, gamma} * gamma + b_0' - epsilon * g_{B, gamma}
+    let fr_b_at_gamma =
+        proof.fr_b0_at_gamma * gamma + fr_b_at_zero - epsilon * proof.fr_gb_at_gamma;
     let mut fr_qb_at_gamma = proof.fr_f_at_gamma + beta + (delta * proof.fr_l_at_gamma);
     fr_qb_at_gamma = fr_qb_at_gamma * fr_b_at_gamma - P::ScalarField::one();
-    fr_qb_at_gamma = fr_qb_at_gamma * fr_inv_zv_at_gamma;
+    fr_qb_at_gamma *= fr_inv_zv_at_gamma;
 
     // Compute p_{gamma} = l_{gamma, v} + eta * l_{gamma} + eta^2 * q_{gamma, L} +
     // eta^3 * d_{gamma} + eta^4 * q_{gamma, D} + eta^5 * b_{0, gamma} + eta^6 *
-    // f_{gamma} + eta^7 * q_{B, gamma}
+    // f_{gamma} + eta^7 * q_{B, gamma} + eta^8 * g_{B, gamma}
     let eta_pow_list = (0..BATCH_SIZE)
         .map(|i| eta.pow([i as u64]))
         .collect::<Vec<P::ScalarField>>();
@@ -293,20 +983,23 @@ fn third_pairing_check<P: Pairing>(
         &proof.fr_b0_at_gamma,
         &proof.fr_f_at_gamma,
         &fr_qb_at_gamma,
+        &proof.fr_gb_at_gamma,
     ]
     .par_iter()
     .zip(eta_pow_list.par_iter())
-    .map(|(fr, eta_pow_x)| {
-        let term = fr.mul(eta_pow_x);
-
-        term
-    })
+    .map(|(fr, eta_pow_x)| fr.mul(eta_pow_x))
     .collect();
     let mut fr_p_at_gamma = P::ScalarField::zero();
     for term in fr_p_at_gamma_terms {
         fr_p_at_gamma = fr_p_at_gamma.add(&term);
     }
 
+    // A Pedersen statement [F(tau) + gamma * R(tau)]_1 is opened as
+    // [F(tau) + gamma * (R(tau) - r_{gamma})]_1, whose blinding part is
+    // opened by the prover in H_P(X). r_{gamma} is zero for a plain statement.
+    let g1_affine_statement =
+        (statement.into_group() - g1_affine_gamma.mul(proof.fr_r_at_gamma)).into_affine();
+
     let g1_affine_p_terms: Vec<P::G1> = [
         &proof.g1_affine_l_div_v,
         &proof.g1_affine_l,
@@ -314,52 +1007,110 @@ fn third_pairing_check<P: Pairing>(
         &proof.g1_affine_d,
         &proof.g1_affine_qd,
         &proof.g1_affine_b0,
-        &statement,
+        &g1_affine_statement,
         &proof.g1_affine_qb,
+        &proof.g1_affine_gb,
     ]
     .par_iter()
     .zip(eta_pow_list.par_iter())
-    .map(|(g1, eta_pow_x)| {
-        let term = g1.mul(eta_pow_x);
-
-        term
-    })
+    .map(|(g1, eta_pow_x)| g1.mul(eta_pow_x))
     .collect();
     let mut g1_p = P::G1::zero();
     for term in g1_affine_p_terms {
         g1_p = g1_p.add(&term);
     }
-    let g1_affine_p = g1_p.into_affine();
-
-    let left_pairing = P::pairing(proof.g1_affine_hp, g2_affine_tau);
-    let g1_gamma_mul_hp = proof.g1_affine_hp.mul(gamma).into_affine();
-    let g1_neg_p_at_gamma = fr_to_curve_element::<P::G1>(-fr_p_at_gamma).into_affine();
-    let right_pairing = P::pairing(
-        g1_affine_p + g1_neg_p_at_gamma + g1_gamma_mul_hp,
-        g2_affine_one,
-    );
 
-    if left_pairing != right_pairing {
-        return Err(Error::Pairing3Failed);
-    }
+    let g1_gamma_mul_hp = proof.g1_affine_hp.mul(gamma);
+    let g1_neg_p_at_gamma = g1_affine_one.mul(-fr_p_at_gamma);
+
+    Ok(PairingEquation {
+        error: Error::Pairing3Failed,
+        pairs: vec![
+            (proof.g1_affine_hp.into_group(), g2_affine_tau.into_group()),
+            (
+                -(g1_p + g1_neg_p_at_gamma + g1_gamma_mul_hp),
+                g2_affine_one.into_group(),
+            ),
+        ],
+    })
+}
 
-    Ok(())
+// With z = w^{ps} for the padding segment p and m_p = k - q,
+// e(M - [m_p]_1 + z * H_M, [1]_2) = e(H_M, [tau]_2), so that only the
+// padding queries use the padding segment, and
+// e(D - [z]_1, [S_q(tau)]_2) = e(Q_T, [Z_K(tau)]_2), so that they all do.
+fn padding_equations<P: Pairing>(
+    proof: &Proof<P>,
+    num_queries: usize,
+    vk: &VerifierKey<P>,
+) -> Result<Vec<PairingEquation<P>>, Error> {
+    let g2_affine_padding_selector = *vk
+        .g2_affine_list_padding_selectors
+        .get(num_queries)
+        .ok_or(Error::InvalidNumberOfQueries(num_queries))?;
+    let fr_padding_root = vk
+        .domain_w
+        .element(vk.logical_num_table_segments * vk.segment_size);
+    let fr_padding_multiplicity =
+        P::ScalarField::from((vk.num_witness_segments - num_queries) as u64);
+
+    let g1_m_sub_padding_multiplicity = proof.g1_affine_m.into_group()
+        - vk.g1_affine_one.mul(fr_padding_multiplicity)
+        + proof.g1_affine_hm.mul(fr_padding_root);
+    let g1_d_sub_padding_root =
+        proof.g1_affine_d.into_group() - vk.g1_affine_one.mul(fr_padding_root);
+
+    Ok(vec![
+        PairingEquation {
+            error: Error::PaddingMultiplicityCheckFailed,
+            pairs: vec![
+                (g1_m_sub_padding_multiplicity, vk.g2_affine_one.into_group()),
+                (
+                    -proof.g1_affine_hm.into_group(),
+                    vk.g2_affine_tau.into_group(),
+                ),
+            ],
+        },
+        PairingEquation {
+            error: Error::PaddingPositionCheckFailed,
+            pairs: vec![
+                (
+                    g1_d_sub_padding_root,
+                    g2_affine_padding_selector.into_group(),
+                ),
+                (
+                    -proof.g1_affine_qt.into_group(),
+                    vk.g2_affine_zk.into_group(),
+                ),
+            ],
+        },
+    ])
 }
 
-fn fourth_pairing_check<P: Pairing>(
+// e(A + epsilon * G_A - [a_0']_1, [1]_2) = e(R_A, [tau]_2) * e(H_A, [Z_W(tau)]_2),
+// which is e(A - [a_0]_1, [1]_2) = e(A_0, [tau]_2) unless A(X) is blinded.
+fn fourth_pairing_equation<P: Pairing>(
     proof: &Proof<P>,
+    epsilon: P::ScalarField,
+    g1_affine_one: P::G1Affine,
+    g2_affine_zw: P::G2Affine,
     g2_affine_tau: P::G2Affine,
     g2_affine_one: P::G2Affine,
-) -> Result<(), Error> {
-    let g1_affine_neg_a0 = fr_to_curve_element::<P::G1>(-proof.fr_a_at_zero).into_affine();
-    let left_pairing = P::pairing(proof.g1_affine_a + g1_affine_neg_a0, g2_affine_one);
-    let right_pairing = P::pairing(proof.g1_affine_a0, g2_affine_tau);
-
-    if left_pairing != right_pairing {
-        return Err(Error::Pairing4Failed);
+) -> PairingEquation<P> {
+    let g1_neg_a0 = g1_affine_one.mul(-proof.fr_a_at_zero);
+    let g1_epsilon_mul_ga = proof.g1_affine_ga.mul(epsilon);
+
+    PairingEquation {
+        error: Error::Pairing4Failed,
+        pairs: vec![
+            (
+                g1_neg_a0 + g1_epsilon_mul_ga + proof.g1_affine_a,
+                g2_affine_one.into_group(),
+            ),
+            (-proof.g1_affine_a0.into_group(), g2_affine_tau.into_group()),
+            (-proof.g1_affine_ha.into_group(), g2_affine_zw.into_group()),
+        ],
     }
-
-    Ok(())
 }
 
 fn first_point_check<P: Pairing>(
@@ -380,7 +1131,7 @@ fn first_point_check<P: Pairing>(
     g1_l_at_gamma_div_v.mul_assign(fr_neg_gamma_pow_k_sub_one * domain_w.group_gen);
     g1_l_at_gamma.mul_assign(fr_gamma_pow_k_sub_one);
     g1_ql_at_gamma.mul_assign(fr_neg_zv_at_gamma);
-    let mut g1_check1 = g1_l_at_gamma_div_v.clone();
+    let mut g1_check1 = g1_l_at_gamma_div_v;
     g1_check1.add_assign(g1_l_at_gamma);
     g1_check1.add_assign(g1_ql_at_gamma);
 
@@ -401,7 +1152,7 @@ fn second_point_check<P: Pairing>(
     let g1_d_at_gamma = fr_to_curve_element::<P::G1>(proof.fr_d_at_gamma);
 
     g1_l_at_gamma.add_assign(g1_d_at_gamma.neg());
-    let mut g1_check2 = g1_l_at_gamma.clone();
+    let mut g1_check2 = g1_l_at_gamma;
     let mut g1_qd_at_gamma = fr_to_curve_element::<P::G1>(proof.fr_qd_at_gamma);
     g1_qd_at_gamma.mul_assign(-fr_zk_at_gamma);
     g1_check2.add_assign(g1_qd_at_gamma);
@@ -423,11 +1174,15 @@ fn fr_to_curve_element<C: CurveGroup>(fr: C::ScalarField) -> C {
 #[cfg(test)]
 mod tests {
     use ark_bn254::Bn254;
+    use ark_poly::Polynomial;
+    use ark_serialize::{CanonicalSerialize, Compress};
     use ark_std::rand::RngCore;
     use ark_std::{test_rng, UniformRand};
 
-    use crate::prover::prove;
-    use crate::table::{rand_segments, Table};
+    use crate::prover::{prove, prove_with_transcript, ProofOptions, ProverState};
+    use crate::public_parameters::PublicParameters;
+    use crate::table::{rand_segments, Table, TablePreprocessedParameters};
+    use crate::transcript::PoseidonTranscript;
     use crate::witness::Witness;
 
     use super::*;
@@ -454,23 +1209,333 @@ mod tests {
             let tpp = t.preprocess(&pp).unwrap();
 
             let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-                .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
                 .collect();
 
             let witness =
                 Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
 
-            let statement = witness.generate_statement(&pp.g1_affine_srs);
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
 
             let rng = &mut test_rng();
 
             let proof =
                 prove::<Bn254, _>(&pp, &tpp, &witness, statement, rng).expect("Failed to prove");
 
-            assert!(verify::<Bn254, _>(&pp, &tpp, statement, &proof, rng).is_ok());
+            assert!(verify::<Bn254, _>(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof,
+                rng
+            )
+            .is_ok());
+            assert!(verify_debug::<Bn254>(
+                &pp.verifier_key().unwrap(),
+                &tpp.verifier_key(),
+                statement,
+                &proof
+            )
+            .is_ok());
         }
     }
 
+    #[test]
+    fn test_verify_debug_reports_failed_check() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let mut proof = prove(&pp, &tpp, &witness, statement, &mut rng).expect("Failed to prove");
+
+        // [H_P(tau)]_1 is not absorbed by the transcript,
+        // so only the third pairing equation is affected.
+        proof.g1_affine_hp = (proof.g1_affine_hp + G1Affine::generator()).into_affine();
+
+        assert!(matches!(
+            verify(&vk, &tpp.verifier_key(), statement, &proof, &mut rng),
+            Err(Error::BatchedPairingFailed)
+        ));
+        assert!(matches!(
+            verify_debug(&vk, &tpp.verifier_key(), statement, &proof),
+            Err(Error::Pairing3Failed)
+        ));
+    }
+
+    #[test]
+    fn test_degree_check_rejects_high_degree_b0() {
+        // With n = k in zero-knowledge mode, the SRS bounds neither A_0(X) nor
+        // B_0(X), so both are checked.
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(4)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        assert!(vk.g2_affine_degree_check_a0.is_some());
+        let segments = rand_segments::generate(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let witness =
+            Witness::new_blinded(&pp, &tpp.adjusted_table_values, &[0, 1, 1, 3], &mut rng).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).expect("Failed to prove");
+
+        let degree_checks_hold = |proof: &Proof<Bn254>| {
+            degree_check_equations(
+                proof,
+                vk.g2_affine_degree_check_a0,
+                vk.g2_affine_degree_check_b0,
+                vk.g2_affine_zv,
+                vk.g2_affine_one,
+            )
+            .into_iter()
+            .all(|equation| {
+                let (g1_list, g2_list): (Vec<_>, Vec<_>) = equation.pairs.into_iter().unzip();
+                Bn254::multi_pairing(g1_list, g2_list).is_zero()
+            })
+        };
+        assert!(degree_checks_hold(&proof));
+
+        // B(X) + c * Z_V(X) agrees with B(X) on V, but shifts B(0) by -c, so
+        // its B_0(X) gains c * X^{ks - 1}. R_B(X) then has degree ks - 1, and
+        // [P_B(tau)]_1 would need [tau^{D + 1}]_1 beyond the SRS.
+        let c = Fr::rand(&mut rng);
+        let witness_element_size = pp.num_witness_segments * pp.segment_size;
+        let g1_high = pp.g1_affine_srs.as_slice().unwrap()[witness_element_size - 1].mul(c);
+        let g1_highest = pp.g1_affine_srs.as_slice().unwrap()[pp.g1_affine_srs.len() - 1].mul(c);
+        let mut forged_proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        forged_proof.g1_affine_b0 = (forged_proof.g1_affine_b0 + g1_high).into_affine();
+        forged_proof.g1_affine_rb = (forged_proof.g1_affine_rb + g1_high).into_affine();
+        forged_proof.g1_affine_pb = (forged_proof.g1_affine_pb + g1_highest).into_affine();
+        assert!(!degree_checks_hold(&forged_proof));
+
+        // Keeping R_B(X) breaks B_0(X) = R_B(X) + Z_V(X) * H_B(X) instead.
+        let mut forged_proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        forged_proof.g1_affine_b0 = (forged_proof.g1_affine_b0 + g1_high).into_affine();
+        assert!(!degree_checks_hold(&forged_proof));
+    }
+
+    #[test]
+    fn test_interactive_verify() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+        let witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let tvk = tpp.verifier_key();
+
+        // Runs the protocol with random challenges. The verifier answers the
+        // Round 12 message with `verifier_eta`, or with the prover's eta.
+        let run = |verifier_eta: Option<Fr>, rng: &mut _| -> Result<(), Error> {
+            let mut prover = ProverState::new(&pp, &tpp, &witness);
+            let mut verifier = VerifierState::new(&vk, &tvk, statement);
+
+            verifier.receive_round1(prover.round1(rng)?)?;
+            let caulk_alpha = Fr::rand(rng);
+            verifier.receive_caulk_round1(prover.caulk_round1(rng)?, caulk_alpha)?;
+            let caulk_beta = Fr::rand(rng);
+            verifier.receive_caulk_round2(prover.caulk_round2(caulk_alpha)?, caulk_beta)?;
+            let (beta, delta, epsilon) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+            verifier.receive_caulk_round3(
+                prover.caulk_round3(caulk_beta)?,
+                beta,
+                delta,
+                epsilon,
+            )?;
+            let gamma = Fr::rand(rng);
+            verifier.receive_round10(prover.round10(beta, delta, epsilon)?, gamma)?;
+            let eta = Fr::rand(rng);
+            let round12 = prover.round12(gamma)?;
+            verifier.receive_round12(round12, verifier_eta.unwrap_or(eta))?;
+            verifier.receive_round14(prover.round14(eta)?)?;
+
+            verifier.verify(rng)
+        };
+
+        assert!(run(None, &mut rng).is_ok());
+        assert!(matches!(
+            run(Some(Fr::rand(&mut rng)), &mut rng),
+            Err(Error::BatchedPairingFailed)
+        ));
+
+        // Messages must be received in order.
+        let mut verifier = VerifierState::<Bn254>::new(&vk, &tvk, statement);
+        assert!(matches!(
+            verifier.receive_round14(Round14Message {
+                g1_affine_hp: G1Affine::generator(),
+            }),
+            Err(Error::UnexpectedRound("round 1"))
+        ));
+        assert!(matches!(
+            verifier.verify(&mut rng),
+            Err(Error::UnexpectedRound("round 1"))
+        ));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+        let tpp = t.preprocess(&pp).unwrap();
+
+        let mut statements_and_proofs: Vec<(Statement<Bn254>, Proof<Bn254>)> = (0..5)
+            .map(|_| {
+                let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+                    .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+                    .collect();
+                let witness =
+                    Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices)
+                        .unwrap();
+                let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+                let proof =
+                    prove(&pp, &tpp, &witness, statement, &mut rng).expect("Failed to prove");
+
+                (statement, proof)
+            })
+            .collect();
+
+        let tvk = tpp.verifier_key();
+
+        assert!(verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng).is_ok());
+        assert!(verify_batch(&vk, &tvk, &[], &mut rng).is_ok());
+
+        // An invalid opening proof only fails the pairing equations.
+        let g1_affine_hp = statements_and_proofs[3].1.g1_affine_hp;
+        statements_and_proofs[3].1.g1_affine_hp =
+            (g1_affine_hp + G1Affine::generator()).into_affine();
+        assert!(matches!(
+            verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng),
+            Err(Error::BatchedProofFailed(3))
+        ));
+
+        // With offending proofs in both halves, the first one is reported, and
+        // the one in the right half is found once the left one is fixed.
+        let g1_affine_hp_0 = statements_and_proofs[0].1.g1_affine_hp;
+        statements_and_proofs[0].1.g1_affine_hp =
+            (g1_affine_hp_0 + G1Affine::generator()).into_affine();
+        assert!(matches!(
+            verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng),
+            Err(Error::BatchedProofFailed(0))
+        ));
+        statements_and_proofs[0].1.g1_affine_hp = g1_affine_hp_0;
+        assert!(matches!(
+            verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng),
+            Err(Error::BatchedProofFailed(3))
+        ));
+        statements_and_proofs[3].1.g1_affine_hp = g1_affine_hp;
+
+        // A wrong statement changes the challenges and fails the point checks.
+        statements_and_proofs[1].0.g1_affine_f =
+            G1Affine::generator().mul(Fr::rand(&mut rng)).into_affine();
+        assert!(matches!(
+            verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng),
+            Err(Error::BatchedProofFailed(1))
+        ));
+    }
+
+    #[test]
+    fn test_verify_batch_with_transcript() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let t = Table::<Bn254>::new(&pp, rand_segments::generate(&pp)).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let tvk = tpp.verifier_key();
+
+        // Each proof is made on a copy of the same outer Poseidon transcript.
+        let mut transcript = PoseidonTranscript::<Fr>::new();
+        transcript
+            .append_bytes(Label::Context, b"outer protocol")
+            .unwrap();
+        let statements_and_proofs: Vec<(Statement<Bn254>, Proof<Bn254>)> =
+            [[0, 1, 2, 3], [4, 4, 5, 6]]
+                .iter()
+                .map(|queried_segment_indices| {
+                    let witness =
+                        Witness::new(&pp, &tpp.adjusted_table_values, queried_segment_indices)
+                            .unwrap();
+                    let statement =
+                        witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+                    let proof = prove_with_transcript(
+                        &pp,
+                        &tpp,
+                        &witness,
+                        statement,
+                        &mut transcript.clone(),
+                        &ProofOptions::default().label(b"lookup"),
+                        &mut rng,
+                    )
+                    .unwrap();
+
+                    (statement, proof)
+                })
+                .collect();
+
+        assert!(verify_batch_with_transcript(
+            &vk,
+            &tvk,
+            &statements_and_proofs,
+            &transcript,
+            &VerifyOptions::default().label(b"lookup"),
+            &mut rng
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_batch_with_transcript(
+                &vk,
+                &tvk,
+                &statements_and_proofs,
+                &PoseidonTranscript::<Fr>::new(),
+                &VerifyOptions::default().label(b"lookup"),
+                &mut rng
+            ),
+            Err(Error::BatchedProofFailed(0))
+        ));
+        assert!(verify_batch(&vk, &tvk, &statements_and_proofs, &mut rng).is_err());
+    }
+
     #[test]
     fn test_failed_verify() {
         let mut rng = test_rng();
@@ -486,17 +1551,18 @@ mod tests {
                 .expect("Failed to setup public parameters");
             let segments = rand_segments::generate(&pp);
 
+            let vk = pp.verifier_key().unwrap();
             let t = Table::<Bn254>::new(&pp, segments.clone()).expect("Failed to create table");
             let tpp = t.preprocess(&pp).unwrap();
 
             let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-                .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
                 .collect();
 
             let witness =
                 Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
 
-            let statement = witness.generate_statement(&pp.g1_affine_srs);
+            let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
 
             let rng = &mut test_rng();
 
@@ -506,7 +1572,7 @@ mod tests {
                 .map(|segment| {
                     segment
                         .iter()
-                        .map(|&s| s + Fr::rand(rng))
+                        .map(|&s| s + Fr::from(rng.next_u64()))
                         .collect::<Vec<Fr>>()
                 })
                 .collect();
@@ -515,11 +1581,11 @@ mod tests {
 
             let proof = prove(&pp, &new_tpp, &witness, statement, rng).expect("Failed to prove");
 
-            assert!(!verify(&pp, &tpp, statement, &proof, rng).is_ok());
+            assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
 
             // Wrong witness from wrong table
             let new_queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-                .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
                 .collect();
             let new_witness = Witness::new(
                 &pp,
@@ -530,29 +1596,343 @@ mod tests {
 
             let proof = prove(&pp, &tpp, &new_witness, statement, rng).expect("Failed to prove");
 
-            assert!(!verify(&pp, &tpp, statement, &proof, rng).is_ok());
+            assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
 
             // Wrong witness from wrong indices
             let new_queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-                .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+                .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
                 .collect();
             let new_witness = Witness {
                 num_segments: pp.num_witness_segments,
                 segment_size: pp.segment_size,
+                num_queries: witness.num_queries,
                 segment_indices: new_queried_segment_indices,
                 poly: witness.poly.clone(),
                 evaluations: witness.evaluations.clone(),
+                poly_blinder: witness.poly_blinder.clone(),
             };
 
             let proof = prove(&pp, &tpp, &new_witness, statement, rng).expect("Failed to prove");
 
-            assert!(!verify(&pp, &tpp, statement, &proof, rng).is_ok());
+            assert!(verify(&vk, &tpp.verifier_key(), statement, &proof, rng).is_err());
 
             // Wrong statement
-            let new_statement = G1Affine::generator().mul(Fr::rand(rng)).into_affine();
+            let new_statement = Statement {
+                g1_affine_f: G1Affine::generator().mul(Fr::rand(rng)).into_affine(),
+                ..statement
+            };
             let proof = prove(&pp, &tpp, &witness, statement, rng).expect("Failed to prove");
 
-            assert!(!verify(&pp, &tpp, new_statement, &proof, rng).is_ok());
+            assert!(verify(&vk, &tpp.verifier_key(), new_statement, &proof, rng).is_err());
         }
     }
+
+    // Simulates a zero-knowledge proof of `statement` from the trapdoor tau and
+    // the public table, without a witness. The simulator samples the
+    // commitments the verifier cannot relate to the statement and the opened
+    // evaluations uniformly, derives the others from the pairing equations,
+    // and forges the openings with tau, following the transcript of `prove`.
+    fn simulate_proof<R: Rng + ?Sized>(
+        pp: &PublicParameters<Bn254>,
+        tpp: &TablePreprocessedParameters<Bn254>,
+        statement: Statement<Bn254>,
+        tau: Fr,
+        rng: &mut R,
+    ) -> Proof<Bn254> {
+        let vk = pp.verifier_key().unwrap();
+        let tvk = tpp.verifier_key();
+        let g1_affine_one = vk.g1_affine_one;
+        let commit = |fr: Fr| g1_affine_one.mul(fr).into_affine();
+        let fr_zw_at_tau = vk.domain_w.evaluate_vanishing_polynomial(tau);
+        let fr_zv_at_tau = vk.domain_v.evaluate_vanishing_polynomial(tau);
+        let fr_zk_at_tau = vk.domain_k.evaluate_vanishing_polynomial(tau);
+        let transcript = &mut Transcript::<Fr>::new();
+        ProofOptions::<Bn254>::default()
+            .append_to(transcript)
+            .unwrap();
+        transcript
+            .append_bytes(Label::PublicParameters, &vk.hash_representation)
+            .unwrap();
+        transcript
+            .append_bytes(Label::TablePreprocessedParameters, &tvk.hash_representation)
+            .unwrap();
+        transcript
+            .append_point(Label::Statement, &statement.g1_affine_f)
+            .unwrap();
+        transcript
+            .append_scalar(Label::NumQueries, &Fr::from(statement.num_queries as u64))
+            .unwrap();
+
+        // Round 1: M(X), L(X), D(X), and the masks are uniform, and the
+        // quotients follow from the first pairing and the padding checks.
+        let (fr_m, fr_m_div_w) = (Fr::rand(rng), Fr::rand(rng));
+        let (fr_l, fr_l_div_v, fr_ql) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let (fr_d, fr_qd) = (Fr::rand(rng), Fr::rand(rng));
+        let (fr_ga, fr_gb) = (Fr::rand(rng), Fr::rand(rng));
+        let fr_tau_pow_n = tau.pow([vk.num_table_segments as u64]);
+        let fr_qm = (fr_m - fr_m_div_w) * (fr_tau_pow_n - Fr::one()) / fr_zw_at_tau;
+        let fr_padding_root = vk
+            .domain_w
+            .element(vk.logical_num_table_segments * vk.segment_size);
+        let fr_padding_multiplicity =
+            Fr::from((vk.num_witness_segments - statement.num_queries) as u64);
+        let fr_hm = (fr_m - fr_padding_multiplicity) / (tau - fr_padding_root);
+        let fr_padding_selector_at_tau: Fr = vk.domain_k.evaluate_all_lagrange_coefficients(tau)
+            [statement.num_queries..]
+            .iter()
+            .sum();
+        let fr_qt = (fr_d - fr_padding_root) * fr_padding_selector_at_tau / fr_zk_at_tau;
+        let round1 = Round1Message::<Bn254> {
+            g1_affine_m: commit(fr_m),
+            g1_affine_m_div_w: commit(fr_m_div_w),
+            g1_affine_qm: commit(fr_qm),
+            g1_affine_l: commit(fr_l),
+            g1_affine_l_div_v: commit(fr_l_div_v),
+            g1_affine_ql: commit(fr_ql),
+            g1_affine_d: commit(fr_d),
+            g1_affine_qd: commit(fr_qd),
+            g1_affine_hm: commit(fr_hm),
+            g1_affine_qt: commit(fr_qt),
+            g1_affine_ga: commit(fr_ga),
+            g1_affine_gb: commit(fr_gb),
+        };
+        round1.append_to(transcript).unwrap();
+
+        // Rounds 3-8: The multi-unity check of D(X), with uniform U_bar(X, Y),
+        // H_2(X, Y), H_1(Y), their partial evaluations, and v_1, v_2, v_3.
+        let (fr_u_bar, fr_h_2) = (Fr::rand(rng), Fr::rand(rng));
+        let caulk_round1 = CaulkRound1Message::<Bn254> {
+            g1_u_bar: commit(fr_u_bar),
+            g1_h_2: commit(fr_h_2),
+        };
+        transcript
+            .append_point(Label::CaulkG1D, &round1.g1_affine_d)
+            .unwrap();
+        caulk_round1.append_to(transcript).unwrap();
+        let caulk_alpha = transcript
+            .squeeze_challenge(Label::ChallengeCaulkAlpha)
+            .unwrap();
+        let fr_h_1 = Fr::rand(rng);
+        let caulk_round2 = CaulkRound2Message::<Bn254> {
+            g1_h_1: commit(fr_h_1),
+        };
+        caulk_round2.append_to(transcript).unwrap();
+        let caulk_beta = transcript
+            .squeeze_challenge(Label::ChallengeCaulkBeta)
+            .unwrap();
+        let fr_u_bar_alpha = Fr::rand(rng);
+        let fr_h_2_alpha = Fr::rand(rng);
+        let (fr_v1, fr_v2, fr_v3) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let domain_log_n = &vk.domain_log_n;
+        let fr_tau_pow_log_n = tau.pow([domain_log_n.size() as u64]);
+        // The openings with the degree bound log(n) - 1 are shifted by
+        // tau^{len - log(n) + 1} for the caulk SRS of length len.
+        let fr_degree_shift =
+            tau.pow([(vk.g1_affine_srs_caulk.len() + 1 - domain_log_n.size()) as u64]);
+        let fr_caulk_beta_mul_w = caulk_beta * domain_log_n.element(1);
+        let fr_u_bar_alpha_interpolation_at_tau =
+            fr_v2 * (tau - Fr::one()) * (tau - fr_caulk_beta_mul_w)
+                / ((caulk_beta - Fr::one()) * (caulk_beta - fr_caulk_beta_mul_w))
+                + fr_v3 * (tau - Fr::one()) * (tau - caulk_beta)
+                    / ((fr_caulk_beta_mul_w - Fr::one()) * (fr_caulk_beta_mul_w - caulk_beta));
+        let fr_pi4 = (fr_u_bar_alpha - fr_u_bar_alpha_interpolation_at_tau) * fr_degree_shift
+            / ((tau - Fr::one()) * (tau - caulk_beta) * (tau - fr_caulk_beta_mul_w));
+        let lagrange_basis_at_beta = domain_log_n.evaluate_all_lagrange_coefficients(caulk_beta);
+        let fr_u_alpha_beta = fr_v1 * lagrange_basis_at_beta[0] + fr_v2;
+        let fr_p = fr_u_alpha_beta * fr_u_alpha_beta
+            - (fr_v3
+                + vk.identity_poly_k.evaluate(&caulk_alpha)
+                    * lagrange_basis_at_beta[vk.log_num_table_segments - 1])
+            - fr_h_1 * domain_log_n.evaluate_vanishing_polynomial(caulk_beta)
+            - fr_h_2_alpha * vk.domain_k.evaluate_vanishing_polynomial(caulk_alpha);
+        let caulk_round3 = CaulkRound3Message::<Bn254> {
+            g1_u_bar_alpha: commit(fr_u_bar_alpha),
+            g1_h_2_alpha: commit(fr_h_2_alpha),
+            fr_v1,
+            fr_v2,
+            fr_v3,
+            g1_pi1: commit((fr_d - fr_v1) / (tau - caulk_alpha)),
+            g1_pi2: commit((fr_u_bar - fr_u_bar_alpha) / (fr_tau_pow_log_n - caulk_alpha)),
+            g1_pi3: commit((fr_h_2 - fr_h_2_alpha) / (fr_tau_pow_log_n - caulk_alpha)),
+            g1_pi4: commit(fr_pi4),
+            g1_pi5: commit(fr_p * fr_degree_shift / (tau - caulk_beta)),
+        };
+
+        // Round 10: A(X), B_0(X), and their splits are uniform, Q_A(X) follows
+        // from the second pairing with [T'(tau)]_1, and H_A(X) from the fourth.
+        let beta = transcript.squeeze_challenge(Label::ChallengeBeta).unwrap();
+        let delta = transcript.squeeze_challenge(Label::ChallengeDelta).unwrap();
+        let epsilon = transcript
+            .squeeze_challenge(Label::ChallengeEpsilon)
+            .unwrap();
+        let (fr_a, fr_a0, fr_a_at_zero) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let (fr_b0, fr_rb, fr_qb) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let g1_qa = (tpp.g1_affine_adjusted_t.mul(fr_a)
+            + g1_affine_one.mul(fr_a * (beta + delta * tau) - fr_m))
+        .mul(fr_zw_at_tau.inverse().unwrap());
+        let fr_ha = (fr_a + epsilon * fr_ga - fr_a_at_zero - fr_a0 * tau) / fr_zw_at_tau;
+        let (shift_a0, shift_b0) = pp.degree_check_shifts();
+        let round10 = Round10Message::<Bn254> {
+            g1_affine_a: commit(fr_a),
+            g1_affine_qa: g1_qa.into_affine(),
+            g1_affine_qb: commit(fr_qb),
+            g1_affine_a0: commit(fr_a0),
+            g1_affine_b0: commit(fr_b0),
+            g1_affine_pa: shift_a0.map_or(G1Affine::zero(), |shift_a0| {
+                commit(fr_a0 * tau.pow([shift_a0 as u64]))
+            }),
+            g1_affine_ha: commit(fr_ha),
+            g1_affine_rb: commit(fr_rb),
+            g1_affine_hb: commit((fr_b0 - fr_rb) / fr_zv_at_tau),
+            g1_affine_pb: commit(fr_rb * tau.pow([shift_b0 as u64])),
+        };
+        round10.append_to(transcript).unwrap();
+
+        // Round 12: The evaluations at gamma are uniform, except for the
+        // quotients that the point checks determine.
+        let gamma = transcript.squeeze_challenge(Label::ChallengeGamma).unwrap();
+        let fr_l_at_gamma = Fr::rand(rng);
+        let fr_l_at_gamma_div_v = Fr::rand(rng);
+        let fr_d_at_gamma = Fr::rand(rng);
+        let round12 = Round12Message::<Bn254> {
+            fr_b0_at_gamma: Fr::rand(rng),
+            fr_f_at_gamma: Fr::rand(rng),
+            fr_l_at_gamma,
+            fr_a_at_zero,
+            fr_l_at_gamma_div_v,
+            fr_ql_at_gamma: (gamma.pow([vk.num_witness_segments as u64]) - Fr::one())
+                * (fr_l_at_gamma - vk.domain_w.group_gen * fr_l_at_gamma_div_v)
+                / vk.domain_v.evaluate_vanishing_polynomial(gamma),
+            fr_d_at_gamma,
+            fr_qd_at_gamma: (fr_l_at_gamma - fr_d_at_gamma)
+                / vk.domain_k.evaluate_vanishing_polynomial(gamma),
+            fr_r_at_gamma: Fr::zero(),
+            fr_gb_at_gamma: Fr::rand(rng),
+        };
+        round12.append_to(transcript).unwrap();
+
+        // Round 14: H_P(X) opens the batch P(X) at gamma, for the statement
+        // [F(tau)]_1 in the group.
+        let eta = transcript.squeeze_challenge(Label::ChallengeEta).unwrap();
+        let fr_b_at_gamma = round12.fr_b0_at_gamma * gamma
+            + fr_a_at_zero * Fr::from(vk.num_table_segments as u64)
+                / Fr::from(vk.num_witness_segments as u64)
+            - epsilon * round12.fr_gb_at_gamma;
+        let fr_qb_at_gamma =
+            ((beta + round12.fr_f_at_gamma + delta * fr_l_at_gamma) * fr_b_at_gamma - Fr::one())
+                / vk.domain_v.evaluate_vanishing_polynomial(gamma);
+        let batch = [
+            (commit(fr_l_div_v), fr_l_at_gamma_div_v),
+            (commit(fr_l), fr_l_at_gamma),
+            (commit(fr_ql), round12.fr_ql_at_gamma),
+            (commit(fr_d), fr_d_at_gamma),
+            (commit(fr_qd), round12.fr_qd_at_gamma),
+            (commit(fr_b0), round12.fr_b0_at_gamma),
+            (statement.g1_affine_f, round12.fr_f_at_gamma),
+            (commit(fr_qb), fr_qb_at_gamma),
+            (commit(fr_gb), round12.fr_gb_at_gamma),
+        ];
+        let g1_p_sub_p_at_gamma: <Bn254 as Pairing>::G1 = batch
+            .iter()
+            .enumerate()
+            .map(|(i, (g1_affine, fr))| {
+                let fr_eta_pow_i = eta.pow([i as u64]);
+                g1_affine.mul(fr_eta_pow_i) - g1_affine_one.mul(*fr * fr_eta_pow_i)
+            })
+            .sum();
+        let round14 = Round14Message::<Bn254> {
+            g1_affine_hp: g1_p_sub_p_at_gamma
+                .mul((tau - gamma).inverse().unwrap())
+                .into_affine(),
+        };
+
+        Proof::from_messages(
+            round1,
+            caulk_round1,
+            caulk_round2,
+            caulk_round3,
+            round10,
+            round12,
+            round14,
+        )
+    }
+
+    #[test]
+    fn test_zero_knowledge_simulated_proof() {
+        // A simulator with the trapdoor and without the witness produces proofs
+        // that verify and have the same shape as real proofs.
+        let mut rng = test_rng();
+        let tau = Fr::rand(&mut rng);
+        let pp = PublicParameters::<Bn254>::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .tau(tau)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let vk = pp.verifier_key().unwrap();
+        let segments = rand_segments::generate(&pp);
+        let t = Table::new(&pp, segments).unwrap();
+        let tpp = t.preprocess(&pp).unwrap();
+        let tvk = tpp.verifier_key();
+        let witness =
+            Witness::new_blinded(&pp, &tpp.adjusted_table_values, &[1, 3, 6], &mut rng).unwrap();
+        let statement = witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+
+        let proof = prove(&pp, &tpp, &witness, statement, &mut rng).unwrap();
+        let simulated_proof = simulate_proof(&pp, &tpp, statement, tau, &mut rng);
+        assert!(verify(&vk, &tvk, statement, &proof, &mut rng).is_ok());
+        assert!(verify(&vk, &tvk, statement, &simulated_proof, &mut rng).is_ok());
+
+        for compress in [Compress::Yes, Compress::No] {
+            assert_eq!(
+                proof.serialized_size(compress),
+                simulated_proof.serialized_size(compress)
+            );
+        }
+        // Both proofs have the identity at the same points.
+        let g1_affine_list = |proof: &Proof<Bn254>| {
+            [
+                proof.g1_affine_m,
+                proof.g1_affine_m_div_w,
+                proof.g1_affine_qm,
+                proof.g1_affine_l,
+                proof.g1_affine_l_div_v,
+                proof.g1_affine_ql,
+                proof.g1_affine_d,
+                proof.g1_affine_qd,
+                proof.g1_affine_hm,
+                proof.g1_affine_qt,
+                proof.g1_affine_ga,
+                proof.g1_affine_gb,
+                proof.g1_affine_a,
+                proof.g1_affine_qa,
+                proof.g1_affine_qb,
+                proof.g1_affine_a0,
+                proof.g1_affine_b0,
+                proof.g1_affine_pa,
+                proof.g1_affine_ha,
+                proof.g1_affine_rb,
+                proof.g1_affine_hb,
+                proof.g1_affine_pb,
+                proof.g1_affine_hp,
+            ]
+        };
+        for (g1_affine, g1_affine_simulated) in g1_affine_list(&proof)
+            .into_iter()
+            .zip(g1_affine_list(&simulated_proof))
+        {
+            assert_eq!(g1_affine.is_zero(), g1_affine_simulated.is_zero());
+        }
+
+        // The simulated proof is bound to its statement, like a real one.
+        let other_witness =
+            Witness::new_blinded(&pp, &tpp.adjusted_table_values, &[0, 2, 2], &mut rng).unwrap();
+        let other_statement =
+            other_witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap());
+        let other_simulated_proof = simulate_proof(&pp, &tpp, other_statement, tau, &mut rng);
+        assert!(verify(&vk, &tvk, other_statement, &other_simulated_proof, &mut rng).is_ok());
+        assert!(verify(&vk, &tvk, statement, &other_simulated_proof, &mut rng).is_err());
+    }
 }
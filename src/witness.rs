@@ -2,39 +2,73 @@ use ark_ec::pairing::Pairing;
 use ark_ec::CurveGroup;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::{DenseUVPolynomial, EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::Rng;
+use ark_std::{UniformRand, Zero};
 
 use crate::error::Error;
 use crate::kzg::Kzg;
-use crate::public_parameters::PublicParameters;
+use crate::public_parameters::{PublicParameters, PEDERSEN_SRS_SIZE};
+use crate::table::{Table, TablePreprocessedParameters};
+
+/// The statement of a lookup: the commitment [F(tau)]_1 to the witness and
+/// the number of queried segments, which the verifier takes as input. The
+/// remaining segments up to k must be the padding segment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Statement<P: Pairing> {
+    pub g1_affine_f: P::G1Affine, // [F(tau)]_1
+    pub num_queries: usize,
+}
 
 pub struct Witness<P: Pairing> {
     pub num_segments: usize,
     pub segment_size: usize,
+    // Number of queried segments, followed by padding segments up to k.
+    pub num_queries: usize,
     pub segment_indices: Vec<usize>,
     pub poly: DensePolynomial<P::ScalarField>,
     pub evaluations: Vec<P::ScalarField>,
+    // r_F(X) of the blinded statement [F(tau) + Z_V(tau) * r_F(tau)]_1,
+    // or zero if the witness is not blinded.
+    pub(crate) poly_blinder: DensePolynomial<P::ScalarField>,
 }
 
 impl<P: Pairing> Witness<P> {
+    /// Creates a witness from the (padded) table values and up to k queried
+    /// segment indices. Fewer queries are padded with the padding segment
+    /// (see [`PublicParameters::padding_segment_index`]), which a query
+    /// cannot use, and the number of queries is part of the statement.
     pub fn new(
         pp: &PublicParameters<P>,
         table_values: &[P::ScalarField],
         queried_segment_indices: &[usize],
     ) -> Result<Self, Error> {
-        if queried_segment_indices.len() != pp.num_witness_segments {
+        if queried_segment_indices.is_empty()
+            || queried_segment_indices.len() > pp.logical_num_witness_segments
+        {
             return Err(Error::InvalidNumberOfQueries(queried_segment_indices.len()));
         }
 
+        if let Some(&segment_index) = queried_segment_indices
+            .iter()
+            .find(|&&i| i >= pp.logical_num_table_segments)
+        {
+            return Err(Error::InvalidSegmentIndex(segment_index));
+        }
+
+        let mut segment_indices = queried_segment_indices.to_vec();
+        segment_indices.resize(pp.num_witness_segments, pp.padding_segment_index());
+
         let mut table_element_indices =
             Vec::with_capacity(pp.num_witness_segments * pp.segment_size);
-        for &segment_index in queried_segment_indices {
+        for &segment_index in &segment_indices {
             for j in 0..pp.segment_size {
                 let index = segment_index * pp.segment_size + j;
                 if index >= table_values.len() {
                     return Err(Error::InvalidSegmentElementIndex(index));
                 }
 
-                table_element_indices.push(segment_index * pp.segment_size + j);
+                table_element_indices.push(index);
             }
         }
 
@@ -48,24 +82,159 @@ impl<P: Pairing> Witness<P> {
         Ok(Self {
             num_segments: pp.num_witness_segments,
             segment_size: pp.segment_size,
+            num_queries: queried_segment_indices.len(),
             poly: poly_f,
             evaluations: poly_eval_list_f,
-            segment_indices: queried_segment_indices.to_vec(),
+            segment_indices,
+            poly_blinder: DensePolynomial::zero(),
         })
     }
 
-    pub fn generate_statement(&self, g1_srs: &[P::G1Affine]) -> P::G1Affine {
-        Kzg::<P::G1>::commit(g1_srs, &self.poly).into_affine()
+    /// Creates a witness from the raw segment values produced by the
+    /// application, each of the segment size set in the public parameters.
+    /// The segments are resolved to table indices with the index of the table,
+    /// and the make-unique adjustment is applied from the preprocessed table.
+    /// Returns [`Error::SegmentNotFound`] with the position of the first
+    /// segment that is not in the table, or whose values in the preprocessed
+    /// table differ, e.g. after
+    /// [`TablePreprocessedParameters::update_segment`], which does not update
+    /// the [`Table`].
+    pub fn from_segment_values(
+        pp: &PublicParameters<P>,
+        table: &Table<P>,
+        tpp: &TablePreprocessedParameters<P>,
+        segment_values: &[Vec<P::ScalarField>],
+    ) -> Result<Self, Error> {
+        let queried_segment_indices = segment_values
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| {
+                if segment.len() != pp.logical_segment_size {
+                    return Err(Error::InvalidSegmentSize(segment.len()));
+                }
+
+                table
+                    .find_segment(segment)
+                    .filter(|&index| tpp.has_segment(index, pp.segment_size, segment))
+                    .ok_or(Error::SegmentNotFound(i))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Self::new(pp, &tpp.adjusted_table_values, &queried_segment_indices)
+    }
+
+    /// Creates a blinded witness, whose statement from
+    /// [`Self::generate_statement`] is the KZG commitment
+    /// [F(tau) + Z_V(tau) * r_F(tau)]_1 for a random r_F(X) of degree one.
+    /// F(X) + Z_V(X) * r_F(X) agrees with F(X) on domain V, and the
+    /// statement and the opening at gamma in the proof are uniformly
+    /// distributed, so they hide the witness even when it can be guessed,
+    /// e.g. from a small table.
+    ///
+    /// Use a blinded witness whenever the statement is produced by this
+    /// crate, which needs no further setup. The public parameters must be
+    /// built in zero-knowledge mode (see
+    /// [`crate::public_parameters::PublicParametersBuilder::zero_knowledge`]),
+    /// without which the rest of the proof reveals the witness.
+    pub fn new_blinded<R: Rng + ?Sized>(
+        pp: &PublicParameters<P>,
+        table_values: &[P::ScalarField],
+        queried_segment_indices: &[usize],
+        rng: &mut R,
+    ) -> Result<Self, Error> {
+        if !pp.zero_knowledge {
+            return Err(Error::ZeroKnowledgeNotEnabled);
+        }
+
+        let mut witness = Self::new(pp, table_values, queried_segment_indices)?;
+        witness.poly_blinder = DensePolynomial::from_coefficients_vec(vec![
+            P::ScalarField::rand(rng),
+            P::ScalarField::rand(rng),
+        ]);
+
+        Ok(witness)
+    }
+
+    pub fn generate_statement(&self, g1_srs: &[P::G1Affine]) -> Statement<P> {
+        Statement {
+            g1_affine_f: Kzg::<P::G1>::commit(g1_srs, &self.blinded_poly()).into_affine(),
+            num_queries: self.num_queries,
+        }
+    }
+
+    /// Generates the Pedersen statement [F(tau) + gamma * R(tau)]_1, where
+    /// R(X) is the blinding polynomial of `blinder`, and `g1_srs_gamma` is
+    /// [gamma * tau^i]_1 from public parameters built with Pedersen
+    /// statements. F(X) is the blinded polynomial of a witness from
+    /// [`Self::new_blinded`]. The statement hides the witness through both
+    /// blinders, and the proof must be made with the same `blinder`, given
+    /// in [`crate::prover::ProofOptions::pedersen_blinder`].
+    ///
+    /// Use a Pedersen statement when the statement is shared with an outer
+    /// protocol that commits with the second generator [gamma * tau^i]_1 and
+    /// keeps its own blinder, e.g. to link the lookup to a commitment made
+    /// elsewhere. Multi-column lookups do not support it.
+    pub fn generate_pedersen_statement(
+        &self,
+        g1_srs: &[P::G1Affine],
+        g1_srs_gamma: &[P::G1Affine],
+        blinder: &PedersenBlinder<P>,
+    ) -> Result<Statement<P>, Error> {
+        if g1_srs_gamma.len() < PEDERSEN_SRS_SIZE {
+            return Err(Error::PedersenStatementsNotEnabled);
+        }
+        let g1_f = Kzg::<P::G1>::commit(g1_srs, &self.blinded_poly());
+        let g1_blinding = Kzg::<P::G1>::commit(g1_srs_gamma, &blinder.poly);
+
+        Ok(Statement {
+            g1_affine_f: (g1_f + g1_blinding).into_affine(),
+            num_queries: self.num_queries,
+        })
+    }
+
+    // F(X) + Z_V(X) * r_F(X), which agrees with F(X) on domain V.
+    pub(crate) fn blinded_poly(&self) -> DensePolynomial<P::ScalarField> {
+        if self.poly_blinder.is_zero() {
+            return self.poly.clone();
+        }
+
+        // Z_V(X) * r_F(X) = X^{ks} * r_F(X) - r_F(X).
+        let mut poly_coeff_list_blinding = vec![P::ScalarField::zero(); self.evaluations.len()];
+        poly_coeff_list_blinding.extend_from_slice(&self.poly_blinder.coeffs);
+        let poly_blinding = DensePolynomial::from_coefficients_vec(poly_coeff_list_blinding);
+
+        &(&self.poly + &poly_blinding) - &self.poly_blinder
+    }
+}
+
+/// The blinding polynomial R(X) of a Pedersen statement
+/// [F(tau) + gamma * R(tau)]_1. It is of degree one, so the statement stays
+/// hiding after R(X) is opened at gamma in Round 12.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PedersenBlinder<P: Pairing> {
+    pub(crate) poly: DensePolynomial<P::ScalarField>,
+}
+
+impl<P: Pairing> PedersenBlinder<P> {
+    pub fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self {
+            poly: DensePolynomial::from_coefficients_vec(
+                (0..PEDERSEN_SRS_SIZE)
+                    .map(|_| P::ScalarField::rand(rng))
+                    .collect(),
+            ),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ark_bn254::Bn254;
+    use ark_poly::Polynomial;
     use ark_std::rand::RngCore;
     use ark_std::test_rng;
 
-    use crate::table::{rand_segments, Table};
+    use crate::table::rand_segments;
 
     use super::*;
 
@@ -83,9 +252,131 @@ mod tests {
         let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
 
         let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
-            .map(|_| rng.next_u32() as usize % pp.num_table_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
             .collect();
 
         Witness::new(&pp, &t.values, &queried_segment_indices).expect("Failed to create witness");
     }
+
+    #[test]
+    fn test_witness_padding_segment() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+
+        // The padding segment follows the logical table and is not a copy of
+        // a real segment.
+        let padding_segment_index = pp.padding_segment_index();
+        assert_eq!(padding_segment_index, 8);
+        assert_eq!(pp.num_table_segments, 16);
+        let padding_values =
+            &t.values[padding_segment_index * pp.segment_size..][..pp.segment_size];
+        assert!(padding_values.iter().all(|v| v.is_zero()));
+        assert_ne!(padding_values, &t.values[..pp.segment_size]);
+
+        // Short witnesses are padded with it.
+        let witness = Witness::new(&pp, &t.values, &[3, 5]).unwrap();
+        assert_eq!(
+            witness.segment_indices,
+            vec![3, 5, padding_segment_index, padding_segment_index]
+        );
+
+        // A witness that queries the padding segment or a dummy segment is
+        // rejected.
+        for segment_index in [padding_segment_index, pp.num_table_segments - 1] {
+            assert!(matches!(
+                Witness::new(&pp, &t.values, &[1, segment_index]),
+                Err(Error::InvalidSegmentIndex(i)) if i == segment_index
+            ));
+        }
+    }
+
+    #[test]
+    fn test_witness_from_segment_values() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(6)
+            .num_witness_segments(3)
+            .segment_size(3)
+            .lagrange_quotients(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+
+        let t = Table::<Bn254>::new(&pp, segments.clone()).expect("Failed to create table");
+        let mut tpp = t.preprocess(&pp).expect("Failed to preprocess table");
+
+        let queried_segment_indices = [4, 1, 4];
+        let queried_segments: Vec<_> = queried_segment_indices
+            .iter()
+            .map(|&i| segments[i].clone())
+            .collect();
+        let witness = Witness::from_segment_values(&pp, &t, &tpp, &queried_segments).unwrap();
+        let expected_witness =
+            Witness::new(&pp, &tpp.adjusted_table_values, &queried_segment_indices).unwrap();
+        assert_eq!(witness.segment_indices, expected_witness.segment_indices);
+        assert_eq!(witness.evaluations, expected_witness.evaluations);
+
+        let mut unknown_segments = queried_segments.clone();
+        unknown_segments[1][2] += <Bn254 as ark_ec::pairing::Pairing>::ScalarField::from(1u64);
+        assert!(matches!(
+            Witness::from_segment_values(&pp, &t, &tpp, &unknown_segments),
+            Err(Error::SegmentNotFound(1))
+        ));
+
+        // After an update of the preprocessed table, the table is stale: the
+        // old values of segment 1 are no longer in the preprocessed table, and
+        // its new values are not in the table.
+        tpp.update_segment(&pp, 1, &unknown_segments[1]).unwrap();
+        assert!(matches!(
+            Witness::from_segment_values(&pp, &t, &tpp, &queried_segments),
+            Err(Error::SegmentNotFound(1))
+        ));
+        assert!(matches!(
+            Witness::from_segment_values(&pp, &t, &tpp, &unknown_segments),
+            Err(Error::SegmentNotFound(1))
+        ));
+        assert!(Witness::from_segment_values(&pp, &t, &tpp, &queried_segments[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_witness_new_blinded() {
+        let mut rng = test_rng();
+        let pp = PublicParameters::builder()
+            .num_table_segments(8)
+            .num_witness_segments(4)
+            .segment_size(4)
+            .zero_knowledge(true)
+            .build(&mut rng)
+            .expect("Failed to setup public parameters");
+        let segments = rand_segments::generate(&pp);
+
+        let t = Table::<Bn254>::new(&pp, segments).expect("Failed to create table");
+
+        let queried_segment_indices: Vec<usize> = (0..pp.num_witness_segments)
+            .map(|_| rng.next_u32() as usize % pp.logical_num_table_segments)
+            .collect();
+
+        let witness = Witness::new(&pp, &t.values, &queried_segment_indices).unwrap();
+        let blinded_witness =
+            Witness::new_blinded(&pp, &t.values, &queried_segment_indices, &mut rng).unwrap();
+
+        // The blinded polynomial has the same evaluations on domain V,
+        // but the blinded statement differs from the plain one.
+        let poly_blinded = blinded_witness.blinded_poly();
+        for (i, x) in pp.domain_v.elements().enumerate() {
+            assert_eq!(poly_blinded.evaluate(&x), witness.evaluations[i]);
+        }
+        assert_ne!(
+            witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap()),
+            blinded_witness.generate_statement(pp.g1_affine_srs.as_slice().unwrap())
+        );
+    }
 }